@@ -1,15 +1,37 @@
+use crate::commands::PendingReport;
+use crate::config::FileFormat;
+use crate::llm::{LlmEvent, LlmJob};
+use crate::search::{self, SearchIndex, SearchQuery};
+use crate::stats::JournalStats;
+use crate::storage::{self, JournalEntry, MoodInsights, TagFilter, TrashedEntry};
+use crate::ui::components::TextEditor;
 use crate::ui::render_ui;
-use crate::{commands, config::Config};
+use crate::{commands, config::Config, llm, utils};
 use anyhow::Result;
-use crossterm::event::{self, Event, KeyCode, KeyEvent, KeyEventKind};
+use crossterm::event::{self, Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers};
 use ratatui::{backend::Backend, Terminal};
+use std::cell::Cell;
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::thread::JoinHandle;
 use std::time::{Duration, Instant};
+use time::{Date, OffsetDateTime};
+
+// How often the screen is redrawn while an LLM response is streaming in
+const STREAM_REFRESH: Duration = Duration::from_millis(50);
 
 #[derive(Clone, Copy, PartialEq)]
 pub enum AppScreen {
+    // Asks for the passphrase of an encrypted journal before anything else
+    Unlock,
     Main,
     AddJournal,
     ViewJournals,
+    Trash,
+    Calendar,
+    Insights,
+    Stats,
+    Review,
     Settings,
     Help,
     Quit,
@@ -23,21 +45,129 @@ pub enum InputMode {
 
 pub struct App {
     pub current_screen: AppScreen,
+    // Typed on the Unlock screen; never shown
+    pub passphrase_input: String,
     pub input_mode: InputMode,
-    pub current_input: String,
-    pub cursor_position: usize,
+    pub editor: TextEditor,
+    // Day a new entry is filed under when it isn't today
+    pub entry_day: Option<Date>,
+    // Set while the entry's date is being typed
+    pub entry_date_input: Option<String>,
+    // Ratings from 1 to 5 for the new entry
+    pub entry_mood: Option<u8>,
+    pub entry_energy: Option<u8>,
+    // Set while the mood and energy prompt is open
+    pub check_in: Option<CheckIn>,
     pub selected_menu_item: usize,
     pub message: Option<String>,
     pub message_type: MessageType,
     pub processing: bool,
+    pub pending_entry: Option<PendingEntry>,
+    pub llm_preview: String,
+    pub spinner_frame: usize,
+    pub review: Option<Review>,
+    pub pending_job: Option<JoinHandle<anyhow::Result<PendingReport>>>,
+    pub external_edit: Option<ExternalEdit>,
+    // Every loaded entry; `journal_entries` holds those passing the tag filter
+    pub all_journal_entries: Vec<JournalEntry>,
     pub journal_entries: Vec<JournalEntry>,
     pub selected_journal: usize,
+    pub tag_filter: TagFilter,
+    // Tags and mentions of all entries with their counts, most used first
+    pub tag_counts: Vec<(String, usize)>,
+    // Set while the tag sidebar is open; keys go to it
+    pub tag_sidebar: bool,
+    pub selected_tag: usize,
+    // Keys scroll the preview instead of moving through the list
+    pub preview_focused: bool,
+    pub preview_scroll: u16,
+    // Size of the preview as last drawn, to clamp scrolling
+    pub preview_height: Cell<u16>,
+    pub preview_max_scroll: Cell<u16>,
+    // Set while the selected entry is being edited in place
+    pub journal_editor: Option<TextEditor>,
+    pub journal_action: Option<JournalAction>,
+    pub trash_entries: Vec<TrashedEntry>,
+    pub selected_trash: usize,
+    pub calendar_day: Date,
+    // Year-at-a-glance heatmap instead of the month grid
+    pub calendar_year_view: bool,
+    // Color days by words written instead of by number of entries
+    pub calendar_by_length: bool,
+    // Days back from today shown on the Insights screen; None shows all
+    pub insights_days: Option<i64>,
+    // Built when the Insights and Statistics screens open rather than on
+    // every redraw, rebuilt when the entries are reloaded and dropped when the
+    // screens close
+    pub mood_insights: Option<MoodInsights>,
+    pub journal_stats: Option<JournalStats>,
+    pub search_input: String,
+    pub search_editing: bool,
+    pub search_matches: Vec<usize>,
+    pub search_snippets: HashMap<usize, String>,
+    pub search_index: Option<SearchIndex>,
     pub config: Config,
+    pub prompt_names: Vec<String>,
+    pub selected_prompt: usize,
     pub settings_selection: usize,
     pub settings_input: String,
     pub settings_editing: bool,
 }
 
+// A Markdown entry waiting for the LLM to format it
+pub struct PendingEntry {
+    pub entry: JournalEntry,
+    pub prompt: String,
+    pub job: LlmJob,
+}
+
+// Work for the external editor, run from the event loop because the terminal
+// has to be handed over to it
+pub enum ExternalEdit {
+    // The text on the Add Journal screen
+    Entry,
+    // A day file, edited in place
+    DayFile(PathBuf),
+}
+
+// The mood and energy prompt on the Add Journal screen
+pub struct CheckIn {
+    pub field: CheckInField,
+    // Save the entry when the prompt is done, for the prompt shown by 's'
+    pub then_save: bool,
+}
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum CheckInField {
+    Mood,
+    Energy,
+}
+
+// Ranges the Insights screen cycles through with 'r'
+pub const INSIGHTS_RANGES: [Option<i64>; 4] = [Some(30), Some(90), Some(365), None];
+
+// A View Journals or Trash operation waiting for a date or a confirmation
+pub enum JournalAction {
+    Delete,
+    // Move the selected entry to the typed date
+    Redate(String),
+    // Rename the selected entry's day file to the typed date
+    RenameDay(String),
+    // Delete the selected trash entry for good
+    Purge,
+}
+
+// An LLM-formatted entry waiting to be accepted, rejected, retried or edited
+pub struct Review {
+    pub entry: JournalEntry,
+    pub formatted: String,
+    pub prompt: String,
+    pub model: String,
+    // Set while the formatted text is being edited by hand
+    pub editor: Option<TextEditor>,
+    pub scroll: u16,
+}
+
 #[derive(Clone, PartialEq)]
 pub enum MessageType {
     Success,
@@ -46,29 +176,84 @@ pub enum MessageType {
     Warning,
 }
 
-#[derive(Clone)]
-pub struct JournalEntry {
-    pub date: String,
-    pub content: String,
-    pub filename: String,
-}
-
 impl App {
     pub fn new() -> Result<Self> {
         let config = Config::load()?;
+        let prompt_names: Vec<String> = llm::available_prompts(&config)
+            .into_iter()
+            .map(|prompt| prompt.name)
+            .collect();
+        let selected_prompt = prompt_names
+            .iter()
+            .position(|name| *name == config.llm.default_prompt)
+            .unwrap_or(0);
+        let today = config.journal_time_zone()?.now().date();
+
+        // An encrypted journal is unlocked with RUSTY_DIARY_PASSPHRASE when it
+        // is set, and on the Unlock screen otherwise
+        let (current_screen, message) = if !storage::is_encrypted(&config) {
+            (AppScreen::Main, None)
+        } else {
+            match storage::unlock_from_env(&config) {
+                Ok(true) => (AppScreen::Main, None),
+                Ok(false) => (AppScreen::Unlock, None),
+                Err(e) => (AppScreen::Unlock, Some(e.to_string())),
+            }
+        };
 
         Ok(Self {
-            current_screen: AppScreen::Main,
+            current_screen,
+            passphrase_input: String::new(),
             input_mode: InputMode::Normal,
-            current_input: String::new(),
-            cursor_position: 0,
+            editor: TextEditor::default(),
+            entry_day: None,
+            entry_date_input: None,
+            entry_mood: None,
+            entry_energy: None,
+            check_in: None,
             selected_menu_item: 0,
-            message: None,
-            message_type: MessageType::Info,
+            message_type: if message.is_some() {
+                MessageType::Error
+            } else {
+                MessageType::Info
+            },
+            message,
             processing: false,
+            pending_entry: None,
+            llm_preview: String::new(),
+            spinner_frame: 0,
+            review: None,
+            pending_job: None,
+            external_edit: None,
+            all_journal_entries: Vec::new(),
             journal_entries: Vec::new(),
             selected_journal: 0,
+            tag_filter: TagFilter::default(),
+            tag_counts: Vec::new(),
+            tag_sidebar: false,
+            selected_tag: 0,
+            preview_focused: false,
+            preview_scroll: 0,
+            preview_height: Cell::new(0),
+            preview_max_scroll: Cell::new(0),
+            journal_editor: None,
+            journal_action: None,
+            trash_entries: Vec::new(),
+            selected_trash: 0,
+            calendar_day: today,
+            calendar_year_view: false,
+            calendar_by_length: false,
+            insights_days: INSIGHTS_RANGES[0],
+            mood_insights: None,
+            journal_stats: None,
+            search_input: String::new(),
+            search_editing: false,
+            search_matches: Vec::new(),
+            search_snippets: HashMap::new(),
+            search_index: None,
             config,
+            prompt_names,
+            selected_prompt,
             settings_selection: 0,
             settings_input: String::new(),
             settings_editing: false,
@@ -80,17 +265,25 @@ impl App {
         let mut last_tick = Instant::now();
 
         loop {
+            self.poll_llm();
+            self.poll_pending();
             terminal.draw(|f| render_ui(f, self))?;
 
-            let timeout = tick_rate
+            let mut timeout = tick_rate
                 .checked_sub(last_tick.elapsed())
                 .unwrap_or_else(|| Duration::from_secs(0));
+            if self.processing {
+                timeout = timeout.min(STREAM_REFRESH);
+            }
 
             if crossterm::event::poll(timeout)? {
                 if let Event::Key(key) = event::read()? {
                     if key.kind == KeyEventKind::Press {
                         self.handle_key_event(key)?;
                     }
+                    if let Some(edit) = self.external_edit.take() {
+                        self.run_external_editor(terminal, edit)?;
+                    }
                 }
             }
 
@@ -107,11 +300,64 @@ impl App {
         Ok(())
     }
 
+    // Hands the terminal to the external editor and takes it back afterwards
+    fn run_external_editor<B: Backend>(
+        &mut self,
+        terminal: &mut Terminal<B>,
+        edit: ExternalEdit,
+    ) -> Result<()> {
+        crate::ui::leave_terminal()?;
+        let editor = self.config.editor_command.clone();
+        let result = match &edit {
+            ExternalEdit::Entry => utils::edit_text(
+                editor.as_deref(),
+                &self.editor.text(),
+                self.config.file_format.extension(),
+            )
+            .map(|text| {
+                self.editor = TextEditor::from_text(&text);
+                "Entry updated from the editor"
+            }),
+            ExternalEdit::DayFile(path) => commands::edit_day_file(&self.config, path).map(|_| {
+                let _ = search::update_index(&self.config, path);
+                self.load_journal_entries();
+                "Journal file saved"
+            }),
+        };
+        crate::ui::enter_terminal()?;
+        terminal.clear()?;
+
+        match result {
+            Ok(message) => {
+                self.message = Some(message.to_string());
+                self.message_type = MessageType::Success;
+            }
+            Err(e) => {
+                self.message = Some(format!("Failed to open the editor: {}", e));
+                self.message_type = MessageType::Error;
+            }
+        }
+        Ok(())
+    }
+
     fn handle_key_event(&mut self, key: KeyEvent) -> Result<()> {
         match self.current_screen {
+            AppScreen::Unlock => self.handle_unlock_input(key),
             AppScreen::Main => self.handle_main_input(key),
             AppScreen::AddJournal => self.handle_add_journal_input(key)?,
-            AppScreen::ViewJournals => self.handle_view_journals_input(key),
+            AppScreen::ViewJournals => {
+                let selected = self.selected_journal;
+                self.handle_view_journals_input(key);
+                // A newly selected entry is shown from the top
+                if self.selected_journal != selected {
+                    self.preview_scroll = 0;
+                }
+            }
+            AppScreen::Trash => self.handle_trash_input(key),
+            AppScreen::Calendar => self.handle_calendar_input(key),
+            AppScreen::Insights => self.handle_insights_input(key),
+            AppScreen::Stats => self.handle_stats_input(key),
+            AppScreen::Review => self.handle_review_input(key),
             AppScreen::Settings => self.handle_settings_input(key)?,
             AppScreen::Help => self.handle_help_input(key),
             AppScreen::Quit => {}
@@ -119,28 +365,75 @@ impl App {
         Ok(())
     }
 
-    fn handle_main_input(&mut self, key: KeyEvent) {
+    fn handle_unlock_input(&mut self, key: KeyEvent) {
         match key.code {
-            KeyCode::Char('q') | KeyCode::Esc => self.current_screen = AppScreen::Quit,
-            KeyCode::Up => {
-                if self.selected_menu_item > 0 {
-                    self.selected_menu_item -= 1;
-                }
+            KeyCode::Esc => self.current_screen = AppScreen::Quit,
+            KeyCode::Char('c') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                self.current_screen = AppScreen::Quit;
             }
-            KeyCode::Down => {
-                if self.selected_menu_item < 4 {
-                    self.selected_menu_item += 1;
+            KeyCode::Enter => {
+                let passphrase = std::mem::take(&mut self.passphrase_input);
+                match storage::unlock(&self.config, &passphrase) {
+                    Ok(()) => {
+                        self.message = None;
+                        self.current_screen = AppScreen::Main;
+                    }
+                    Err(e) => {
+                        self.message = Some(e.to_string());
+                        self.message_type = MessageType::Error;
+                    }
                 }
             }
+            KeyCode::Backspace => {
+                self.passphrase_input.pop();
+            }
+            KeyCode::Char(c) => self.passphrase_input.push(c),
+            _ => {}
+        }
+    }
+
+    fn handle_main_input(&mut self, key: KeyEvent) {
+        match key.code {
+            KeyCode::Char('q') | KeyCode::Esc => self.current_screen = AppScreen::Quit,
+            KeyCode::Up if self.selected_menu_item > 0 => self.selected_menu_item -= 1,
+            KeyCode::Down if self.selected_menu_item < 7 => self.selected_menu_item += 1,
             KeyCode::Enter => match self.selected_menu_item {
-                0 => self.current_screen = AppScreen::AddJournal,
+                0 => {
+                    self.entry_day = None;
+                    self.current_screen = AppScreen::AddJournal;
+                }
                 1 => {
                     self.load_journal_entries();
                     self.current_screen = AppScreen::ViewJournals;
                 }
-                2 => self.current_screen = AppScreen::Settings,
-                3 => self.current_screen = AppScreen::Help,
-                4 => self.current_screen = AppScreen::Quit,
+                2 => {
+                    self.load_journal_entries();
+                    // Keep load errors, but let the key hints show otherwise
+                    if self.message_type == MessageType::Success {
+                        self.message = None;
+                    }
+                    self.calendar_day = self.today();
+                    self.current_screen = AppScreen::Calendar;
+                }
+                3 => {
+                    self.load_journal_entries();
+                    if self.message_type == MessageType::Success {
+                        self.message = None;
+                    }
+                    self.refresh_insights();
+                    self.current_screen = AppScreen::Insights;
+                }
+                4 => {
+                    self.load_journal_entries();
+                    if self.message_type == MessageType::Success {
+                        self.message = None;
+                    }
+                    self.refresh_stats();
+                    self.current_screen = AppScreen::Stats;
+                }
+                5 => self.current_screen = AppScreen::Settings,
+                6 => self.current_screen = AppScreen::Help,
+                7 => self.current_screen = AppScreen::Quit,
                 _ => {}
             },
             _ => {}
@@ -148,18 +441,67 @@ impl App {
     }
 
     fn handle_add_journal_input(&mut self, key: KeyEvent) -> Result<()> {
+        // Only cancelling is possible while the LLM is working
+        if self.processing {
+            if key.code == KeyCode::Esc {
+                self.cancel_llm();
+            }
+            return Ok(());
+        }
+
+        if let Some(input) = self.entry_date_input.as_mut() {
+            match key.code {
+                KeyCode::Esc => self.entry_date_input = None,
+                KeyCode::Enter => self.apply_entry_date(),
+                KeyCode::Char(c) => input.push(c),
+                KeyCode::Backspace => {
+                    input.pop();
+                }
+                _ => {}
+            }
+            return Ok(());
+        }
+
+        if self.check_in.is_some() {
+            return self.handle_check_in_input(key);
+        }
+
         match self.input_mode {
             InputMode::Normal => match key.code {
+                KeyCode::Char('m') => self.open_check_in(false),
+                KeyCode::Char('d') => {
+                    let day = self.entry_day.unwrap_or(self.today());
+                    self.entry_date_input = Some(
+                        self.config
+                            .date_formatter()
+                            .and_then(|formatter| formatter.format(day))
+                            .unwrap_or_default(),
+                    );
+                }
                 KeyCode::Char('e') => {
                     self.input_mode = InputMode::Editing;
                     self.message = Some("Start typing your journal entry...".to_string());
                     self.message_type = MessageType::Info;
                 }
-                KeyCode::Char('s') => {
-                    if !self.current_input.is_empty() && !self.processing {
+                KeyCode::Char('s') if !self.editor.is_empty() => {
+                    if self.config.mood_check_in
+                        && self.entry_mood.is_none()
+                        && self.entry_energy.is_none()
+                    {
+                        self.open_check_in(true);
+                    } else {
                         self.save_journal_entry()?;
                     }
                 }
+                KeyCode::Char('E') => self.external_edit = Some(ExternalEdit::Entry),
+                KeyCode::Char('p') if !self.prompt_names.is_empty() => {
+                    self.selected_prompt = (self.selected_prompt + 1) % self.prompt_names.len();
+                    self.message = Some(format!(
+                        "Prompt: {}",
+                        self.prompt_names[self.selected_prompt]
+                    ));
+                    self.message_type = MessageType::Info;
+                }
                 KeyCode::Esc | KeyCode::Char('q') => {
                     self.reset_input_state();
                     self.current_screen = AppScreen::Main;
@@ -172,50 +514,625 @@ impl App {
                     self.message = Some("Press 's' to save or 'q' to quit".to_string());
                     self.message_type = MessageType::Info;
                 }
+                _ => {
+                    self.editor.handle_key(key);
+                }
+            },
+        }
+        Ok(())
+    }
+
+    fn open_check_in(&mut self, then_save: bool) {
+        self.check_in = Some(CheckIn {
+            field: CheckInField::Mood,
+            then_save,
+        });
+    }
+
+    // 1-5 or ←→ rate the selected field, Enter moves on to energy and then
+    // closes the prompt (saving when it was opened by 's'), Esc closes it
+    fn handle_check_in_input(&mut self, key: KeyEvent) -> Result<()> {
+        let Some(check_in) = self.check_in.as_mut() else {
+            return Ok(());
+        };
+        let rating = match check_in.field {
+            CheckInField::Mood => &mut self.entry_mood,
+            CheckInField::Energy => &mut self.entry_energy,
+        };
+
+        match key.code {
+            KeyCode::Esc => self.check_in = None,
+            KeyCode::Char(c @ '1'..='5') => {
+                *rating = c.to_digit(10).map(|digit| digit as u8);
+                return self.next_check_in_field();
+            }
+            KeyCode::Left | KeyCode::Char('h') => {
+                *rating = Some(rating.map_or(1, |rating| rating.saturating_sub(1).max(1)));
+            }
+            KeyCode::Right | KeyCode::Char('l') => {
+                *rating = Some(rating.map_or(storage::MAX_RATING, |rating| {
+                    (rating + 1).min(storage::MAX_RATING)
+                }));
+            }
+            KeyCode::Backspace | KeyCode::Delete | KeyCode::Char('0') => *rating = None,
+            KeyCode::Up | KeyCode::Down | KeyCode::Tab => {
+                check_in.field = match check_in.field {
+                    CheckInField::Mood => CheckInField::Energy,
+                    CheckInField::Energy => CheckInField::Mood,
+                };
+            }
+            KeyCode::Enter => return self.next_check_in_field(),
+            _ => {}
+        }
+        Ok(())
+    }
+
+    fn next_check_in_field(&mut self) -> Result<()> {
+        let Some(check_in) = self.check_in.as_mut() else {
+            return Ok(());
+        };
+        if check_in.field == CheckInField::Mood {
+            check_in.field = CheckInField::Energy;
+            return Ok(());
+        }
+
+        let then_save = check_in.then_save;
+        self.check_in = None;
+        if then_save {
+            self.save_journal_entry()?;
+        }
+        Ok(())
+    }
+
+    // Files the new entry under the typed date
+    fn apply_entry_date(&mut self) {
+        let Some(input) = self.entry_date_input.as_deref() else {
+            return;
+        };
+
+        match commands::parse_entry_date(input, &self.config) {
+            Ok(day) => {
+                self.entry_day = (day != self.today()).then_some(day);
+                self.entry_date_input = None;
+                self.message = None;
+            }
+            Err(e) => {
+                // The field stays open to correct the date
+                self.message = Some(e.to_string());
+                self.message_type = MessageType::Error;
+            }
+        }
+    }
+
+    fn handle_view_journals_input(&mut self, key: KeyEvent) {
+        if self.journal_action.is_some() {
+            self.handle_journal_action_input(key);
+            return;
+        }
+
+        if let Some(editor) = self.journal_editor.as_mut() {
+            match key.code {
+                KeyCode::Esc => {
+                    self.journal_editor = None;
+                    self.message = Some("Changes discarded".to_string());
+                    self.message_type = MessageType::Info;
+                }
+                KeyCode::Char('s') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                    self.save_journal_edit();
+                }
+                _ => {
+                    editor.handle_key(key);
+                }
+            }
+            return;
+        }
+
+        if self.search_editing {
+            match key.code {
+                KeyCode::Esc => self.clear_search(),
+                KeyCode::Enter => self.search_editing = false,
                 KeyCode::Char(c) => {
-                    self.current_input.insert(self.cursor_position, c);
-                    self.cursor_position += 1;
+                    self.search_input.push(c);
+                    self.update_search();
                 }
                 KeyCode::Backspace => {
-                    if self.cursor_position > 0 {
-                        self.cursor_position -= 1;
-                        self.current_input.remove(self.cursor_position);
-                    }
+                    self.search_input.pop();
+                    self.update_search();
                 }
-                KeyCode::Left => {
-                    if self.cursor_position > 0 {
-                        self.cursor_position -= 1;
-                    }
+                _ => {}
+            }
+            return;
+        }
+
+        if self.tag_sidebar {
+            self.handle_tag_sidebar_input(key);
+            return;
+        }
+
+        match key.code {
+            KeyCode::Esc if !self.search_input.is_empty() => self.clear_search(),
+            KeyCode::Esc if !self.tag_filter.is_empty() => {
+                self.tag_filter.labels.clear();
+                self.apply_tag_filter();
+            }
+            KeyCode::Esc | KeyCode::Char('q') => self.current_screen = AppScreen::Main,
+            KeyCode::Char('/') => {
+                self.search_editing = true;
+                self.update_search();
+            }
+            KeyCode::Char('n') => self.jump_to_match(true),
+            KeyCode::Char('N') => self.jump_to_match(false),
+            KeyCode::Tab => self.preview_focused = !self.preview_focused,
+            KeyCode::PageUp => self.scroll_preview(-(self.preview_height.get() as i32)),
+            KeyCode::PageDown => self.scroll_preview(self.preview_height.get() as i32),
+            KeyCode::Up | KeyCode::Char('k') if self.preview_focused => self.scroll_preview(-1),
+            KeyCode::Down | KeyCode::Char('j') if self.preview_focused => self.scroll_preview(1),
+            KeyCode::Home if self.preview_focused => self.preview_scroll = 0,
+            KeyCode::End if self.preview_focused => {
+                self.preview_scroll = self.preview_max_scroll.get();
+            }
+            KeyCode::Up | KeyCode::Char('k') if self.selected_journal > 0 => {
+                self.selected_journal -= 1;
+            }
+            KeyCode::Down | KeyCode::Char('j')
+                if self.selected_journal + 1 < self.journal_entries.len() =>
+            {
+                self.selected_journal += 1;
+            }
+            KeyCode::Char('r') => self.load_journal_entries(),
+            KeyCode::Char('E') => {
+                if let Some(entry) = self.journal_entries.get(self.selected_journal) {
+                    let path = self.config.journal_directory.join(&entry.filename);
+                    self.external_edit = Some(ExternalEdit::DayFile(path));
                 }
-                KeyCode::Right => {
-                    if self.cursor_position < self.current_input.len() {
-                        self.cursor_position += 1;
-                    }
+            }
+            KeyCode::Char('f') if self.pending_job.is_none() => self.start_processing_pending(),
+            KeyCode::Char('t') => {
+                self.message = None;
+                self.load_trash();
+            }
+            KeyCode::Char('T') => {
+                self.message = None;
+                self.tag_sidebar = true;
+            }
+            _ if self.journal_entries.is_empty() => {}
+            KeyCode::Char('e') => {
+                let body = &self.journal_entries[self.selected_journal].body;
+                self.journal_editor = Some(TextEditor::from_text(body));
+            }
+            KeyCode::Char('d') => self.journal_action = Some(JournalAction::Delete),
+            KeyCode::Char('m') => {
+                let date = self.journal_entries[self.selected_journal].date.clone();
+                self.journal_action = Some(JournalAction::Redate(date));
+            }
+            KeyCode::Char('R') => {
+                let date = self.journal_entries[self.selected_journal].date.clone();
+                self.journal_action = Some(JournalAction::RenameDay(date));
+            }
+            _ => {}
+        }
+    }
+
+    // Space toggles the selected tag in the filter, 'a' switches between
+    // matching all and any of the selected tags
+    fn handle_tag_sidebar_input(&mut self, key: KeyEvent) {
+        match key.code {
+            KeyCode::Esc | KeyCode::Char('q') | KeyCode::Char('T') | KeyCode::Tab => {
+                self.tag_sidebar = false;
+            }
+            KeyCode::Up | KeyCode::Char('k') if self.selected_tag > 0 => self.selected_tag -= 1,
+            KeyCode::Down | KeyCode::Char('j') if self.selected_tag + 1 < self.tag_counts.len() => {
+                self.selected_tag += 1;
+            }
+            KeyCode::Enter | KeyCode::Char(' ') => {
+                if let Some((label, _)) = self.tag_counts.get(self.selected_tag) {
+                    let label = label.clone();
+                    self.tag_filter.toggle(&label);
+                    self.apply_tag_filter();
+                }
+            }
+            KeyCode::Char('a') => {
+                self.tag_filter.match_any = !self.tag_filter.match_any;
+                self.apply_tag_filter();
+            }
+            KeyCode::Char('c') => {
+                self.tag_filter.labels.clear();
+                self.apply_tag_filter();
+            }
+            _ => {}
+        }
+    }
+
+    // Shows the entries passing the tag filter, keeping the selected entry
+    // when it's still shown
+    fn apply_tag_filter(&mut self) {
+        let selected_id = self
+            .journal_entries
+            .get(self.selected_journal)
+            .map(|entry| entry.id.clone());
+
+        self.journal_entries = self
+            .all_journal_entries
+            .iter()
+            .filter(|entry| self.tag_filter.matches(entry))
+            .cloned()
+            .collect();
+        self.selected_journal = 0;
+        if let Some(id) = selected_id {
+            self.select_journal_entry(&id);
+        }
+        self.update_search();
+    }
+
+    fn scroll_preview(&mut self, lines: i32) {
+        let scroll = (self.preview_scroll as i32 + lines).max(0) as u16;
+        self.preview_scroll = scroll.min(self.preview_max_scroll.get());
+    }
+
+    // Month grid: h/l move by day, j/k by week. Year heatmap: weeks are
+    // columns, so h/l move by week and j/k by day.
+    fn handle_calendar_input(&mut self, key: KeyEvent) {
+        let (horizontal, vertical) = if self.calendar_year_view {
+            (7, 1)
+        } else {
+            (1, 7)
+        };
+        let period = if self.calendar_year_view { 12 } else { 1 };
+        let day = self.calendar_day;
+        self.message = None;
+
+        self.calendar_day = match key.code {
+            KeyCode::Esc | KeyCode::Char('q') => {
+                self.current_screen = AppScreen::Main;
+                day
+            }
+            KeyCode::Left | KeyCode::Char('h') => add_days(day, -horizontal),
+            KeyCode::Right | KeyCode::Char('l') => add_days(day, horizontal),
+            KeyCode::Up | KeyCode::Char('k') => add_days(day, -vertical),
+            KeyCode::Down | KeyCode::Char('j') => add_days(day, vertical),
+            KeyCode::PageUp | KeyCode::Char('H') => utils::add_months(day, -period),
+            KeyCode::PageDown | KeyCode::Char('L') => utils::add_months(day, period),
+            KeyCode::Char('t') => self.today(),
+            KeyCode::Char('y') => {
+                self.calendar_year_view = !self.calendar_year_view;
+                day
+            }
+            KeyCode::Char('c') => {
+                self.calendar_by_length = !self.calendar_by_length;
+                day
+            }
+            KeyCode::Enter => {
+                self.open_calendar_day();
+                day
+            }
+            KeyCode::Char('a') => {
+                self.start_entry_for(day);
+                day
+            }
+            _ => day,
+        };
+    }
+
+    // Shows the day's entries in the journal browser, or starts an entry for
+    // a day without any
+    fn open_calendar_day(&mut self) {
+        let day = self.calendar_day;
+        if !self.tag_filter.is_empty() {
+            self.tag_filter.labels.clear();
+            self.apply_tag_filter();
+        }
+        match self
+            .journal_entries
+            .iter()
+            .position(|entry| entry.day == day)
+        {
+            Some(position) => {
+                self.clear_search();
+                self.selected_journal = position;
+                self.preview_scroll = 0;
+                self.message = None;
+                self.current_screen = AppScreen::ViewJournals;
+            }
+            None => self.start_entry_for(day),
+        }
+    }
+
+    fn start_entry_for(&mut self, day: Date) {
+        if day > self.today() {
+            self.message = Some("Entries can't be written for future days".to_string());
+            self.message_type = MessageType::Warning;
+            return;
+        }
+
+        self.entry_day = (day != self.today()).then_some(day);
+        self.message = None;
+        self.current_screen = AppScreen::AddJournal;
+    }
+
+    // Today in the journal's time zone
+    pub fn today(&self) -> Date {
+        self.config
+            .journal_time_zone()
+            .map(|time_zone| time_zone.now().date())
+            .unwrap_or_else(|_| OffsetDateTime::now_utc().date())
+    }
+
+    fn handle_insights_input(&mut self, key: KeyEvent) {
+        match key.code {
+            KeyCode::Esc | KeyCode::Char('q') => {
+                self.mood_insights = None;
+                self.current_screen = AppScreen::Main;
+            }
+            KeyCode::Char('r') => {
+                let index = INSIGHTS_RANGES
+                    .iter()
+                    .position(|days| *days == self.insights_days)
+                    .unwrap_or(0);
+                self.insights_days = INSIGHTS_RANGES[(index + 1) % INSIGHTS_RANGES.len()];
+                self.refresh_insights();
+            }
+            _ => {}
+        }
+    }
+
+    // The first day of the Insights range, unless it covers all time
+    pub fn insights_start(&self) -> Option<Date> {
+        self.insights_days
+            .and_then(|days| self.today().checked_sub(time::Duration::days(days - 1)))
+    }
+
+    fn refresh_insights(&mut self) {
+        let today = self.today();
+        let start = self.insights_start();
+        self.mood_insights = Some(MoodInsights::new(self.all_journal_entries.iter().filter(
+            |entry| start.is_none_or(|start| entry.day >= start) && entry.day <= today,
+        )));
+    }
+
+    fn refresh_stats(&mut self) {
+        self.journal_stats = Some(JournalStats::new(&self.all_journal_entries, self.today()));
+    }
+
+    fn handle_stats_input(&mut self, key: KeyEvent) {
+        if let KeyCode::Esc | KeyCode::Char('q') = key.code {
+            self.journal_stats = None;
+            self.current_screen = AppScreen::Main;
+        }
+    }
+
+    fn handle_trash_input(&mut self, key: KeyEvent) {
+        if self.journal_action.is_some() {
+            self.handle_journal_action_input(key);
+            return;
+        }
+
+        match key.code {
+            KeyCode::Esc | KeyCode::Char('q') => {
+                self.load_journal_entries();
+                self.current_screen = AppScreen::ViewJournals;
+            }
+            KeyCode::Up if self.selected_trash > 0 => self.selected_trash -= 1,
+            KeyCode::Down if self.selected_trash + 1 < self.trash_entries.len() => {
+                self.selected_trash += 1;
+            }
+            KeyCode::Enter | KeyCode::Char('u') => self.restore_trashed_entry(),
+            KeyCode::Char('d') if !self.trash_entries.is_empty() => {
+                self.journal_action = Some(JournalAction::Purge);
+            }
+            _ => {}
+        }
+    }
+
+    // Confirmation (y/n) for deletes, a date input for moves and renames
+    fn handle_journal_action_input(&mut self, key: KeyEvent) {
+        let Some(action) = self.journal_action.as_mut() else {
+            return;
+        };
+
+        match action {
+            JournalAction::Delete | JournalAction::Purge => {
+                if matches!(key.code, KeyCode::Char('y') | KeyCode::Char('Y')) {
+                    self.apply_journal_action();
+                } else {
+                    self.journal_action = None;
+                }
+            }
+            JournalAction::Redate(input) | JournalAction::RenameDay(input) => match key.code {
+                KeyCode::Esc => self.journal_action = None,
+                KeyCode::Enter => self.apply_journal_action(),
+                KeyCode::Char(c) => input.push(c),
+                KeyCode::Backspace => {
+                    input.pop();
                 }
-                KeyCode::Home => self.cursor_position = 0,
-                KeyCode::End => self.cursor_position = self.current_input.len(),
                 _ => {}
             },
         }
-        Ok(())
     }
 
-    fn handle_view_journals_input(&mut self, key: KeyEvent) {
-        match key.code {
-            KeyCode::Esc | KeyCode::Char('q') => self.current_screen = AppScreen::Main,
-            KeyCode::Up => {
-                if self.selected_journal > 0 {
-                    self.selected_journal -= 1;
+    fn apply_journal_action(&mut self) {
+        let Some(action) = self.journal_action.take() else {
+            return;
+        };
+        let selected = self.journal_entries.get(self.selected_journal).cloned();
+
+        let result = match (action, selected) {
+            (JournalAction::Purge, _) => {
+                let Some(trashed) = self.trash_entries.get(self.selected_trash) else {
+                    return;
+                };
+                let result = storage::purge_entry(trashed).map(|_| "Entry deleted permanently");
+                self.load_trash();
+                result.map(|message| (message.to_string(), None))
+            }
+            (_, None) => return,
+            (JournalAction::Delete, Some(entry)) => {
+                storage::trash_entry(&self.config, &entry).map(|_| {
+                    let _ = search::update_index(
+                        &self.config,
+                        &storage::entry_path(&self.config, &entry),
+                    );
+                    (
+                        "Entry moved to the trash, press 't' to restore it".to_string(),
+                        None,
+                    )
+                })
+            }
+            (JournalAction::Redate(input), Some(entry)) => {
+                commands::parse_entry_date(&input, &self.config).and_then(|day| {
+                    let from = storage::entry_path(&self.config, &entry);
+                    let to = storage::move_entry(&self.config, &entry, day)?;
+                    let _ = search::update_index(&self.config, &from);
+                    let _ = search::update_index(&self.config, &to);
+                    Ok((format!("Entry moved to {}", input.trim()), Some(entry.id)))
+                })
+            }
+            (JournalAction::RenameDay(input), Some(entry)) => {
+                commands::parse_entry_date(&input, &self.config).and_then(|day| {
+                    let day_file = storage::list_day_files(&self.config)?
+                        .into_iter()
+                        .find(|day_file| day_file.path == storage::entry_path(&self.config, &entry))
+                        .ok_or_else(|| anyhow::anyhow!("{} no longer exists", entry.filename))?;
+                    let to = storage::move_day_file(&self.config, &day_file, day)?;
+                    let _ = search::update_index(&self.config, &to);
+                    Ok((
+                        format!("{} renamed to {}", entry.date, input.trim()),
+                        Some(entry.id),
+                    ))
+                })
+            }
+        };
+
+        if self.current_screen == AppScreen::ViewJournals {
+            let previous = self.selected_journal;
+            self.load_journal_entries();
+            self.selected_journal = previous.min(self.journal_entries.len().saturating_sub(1));
+        }
+        match result {
+            Ok((message, selected_id)) => {
+                if let Some(id) = selected_id {
+                    self.select_journal_entry(&id);
                 }
+                self.message = Some(message);
+                self.message_type = MessageType::Success;
+            }
+            Err(e) => {
+                self.message = Some(format!("Failed to update the journal: {}", e));
+                self.message_type = MessageType::Error;
+            }
+        }
+    }
+
+    // Writes the in-place edit of the selected entry back to its day file
+    fn save_journal_edit(&mut self) {
+        let (Some(editor), Some(entry)) = (
+            self.journal_editor.as_ref(),
+            self.journal_entries.get(self.selected_journal),
+        ) else {
+            return;
+        };
+
+        let entry = JournalEntry {
+            body: editor.text(),
+            ..entry.clone()
+        };
+        match storage::update_entry(&self.config, &entry) {
+            Ok(path) => {
+                let _ = search::update_index(&self.config, &path);
+                self.journal_editor = None;
+                self.load_journal_entries();
+                self.select_journal_entry(&entry.id);
+                self.message = Some("Entry saved".to_string());
+                self.message_type = MessageType::Success;
+            }
+            Err(e) => {
+                // The editor stays open so nothing typed is lost
+                self.message = Some(format!("Failed to save the entry: {}", e));
+                self.message_type = MessageType::Error;
+            }
+        }
+    }
+
+    fn select_journal_entry(&mut self, id: &str) {
+        if let Some(position) = self.journal_entries.iter().position(|entry| entry.id == id) {
+            self.selected_journal = position;
+        }
+    }
+
+    fn load_trash(&mut self) {
+        match storage::list_trash(&self.config) {
+            Ok(trashed) => {
+                self.trash_entries = trashed;
+                self.selected_trash = self
+                    .selected_trash
+                    .min(self.trash_entries.len().saturating_sub(1));
+                self.current_screen = AppScreen::Trash;
+            }
+            Err(e) => {
+                self.message = Some(format!("Failed to read the trash: {}", e));
+                self.message_type = MessageType::Error;
+            }
+        }
+    }
+
+    fn restore_trashed_entry(&mut self) {
+        let Some(trashed) = self.trash_entries.get(self.selected_trash) else {
+            return;
+        };
+
+        match storage::restore_entry(&self.config, trashed) {
+            Ok(path) => {
+                let _ = search::update_index(&self.config, &path);
+                self.message = Some(format!("Entry restored to {}", trashed.entry.date));
+                self.message_type = MessageType::Success;
+                self.load_trash();
+            }
+            Err(e) => {
+                self.message = Some(format!("Failed to restore the entry: {}", e));
+                self.message_type = MessageType::Error;
+            }
+        }
+    }
+
+    fn handle_review_input(&mut self, key: KeyEvent) {
+        let Some(review) = self.review.as_mut() else {
+            self.current_screen = AppScreen::AddJournal;
+            return;
+        };
+
+        if let Some(editor) = review.editor.as_mut() {
+            if key.code == KeyCode::Esc {
+                review.formatted = editor.text();
+                review.editor = None;
+            } else {
+                editor.handle_key(key);
             }
-            KeyCode::Down => {
-                if !self.journal_entries.is_empty()
-                    && self.selected_journal < self.journal_entries.len() - 1
-                {
-                    self.selected_journal += 1;
+            return;
+        }
+
+        match key.code {
+            KeyCode::Char('a') | KeyCode::Enter => self.finish_review(true),
+            KeyCode::Char('r') => self.finish_review(false),
+            KeyCode::Char('e') => review.editor = Some(TextEditor::from_text(&review.formatted)),
+            KeyCode::Char('p') if !self.prompt_names.is_empty() => {
+                self.selected_prompt = (self.selected_prompt + 1) % self.prompt_names.len();
+            }
+            KeyCode::Char('t') => {
+                // Retry from the raw text with the selected prompt
+                if let Some(review) = self.review.take() {
+                    let prompt = self.prompt_names.get(self.selected_prompt).cloned();
+                    self.current_screen = AppScreen::AddJournal;
+                    self.start_formatting(review.entry, prompt.as_deref());
                 }
             }
-            KeyCode::Char('r') => self.load_journal_entries(),
+            KeyCode::Up | KeyCode::Char('k') => review.scroll = review.scroll.saturating_sub(1),
+            KeyCode::Down | KeyCode::Char('j') => review.scroll = review.scroll.saturating_add(1),
+            KeyCode::Esc => {
+                // Back to the raw text, which is still in the input
+                self.review = None;
+                self.current_screen = AppScreen::AddJournal;
+                self.message = Some("Formatted version discarded".to_string());
+                self.message_type = MessageType::Info;
+            }
             _ => {}
         }
     }
@@ -243,16 +1160,8 @@ impl App {
         } else {
             match key.code {
                 KeyCode::Esc | KeyCode::Char('q') => self.current_screen = AppScreen::Main,
-                KeyCode::Up => {
-                    if self.settings_selection > 0 {
-                        self.settings_selection -= 1;
-                    }
-                }
-                KeyCode::Down => {
-                    if self.settings_selection < 2 {
-                        self.settings_selection += 1;
-                    }
-                }
+                KeyCode::Up if self.settings_selection > 0 => self.settings_selection -= 1,
+                KeyCode::Down if self.settings_selection < 5 => self.settings_selection += 1,
                 KeyCode::Enter => {
                     self.start_editing_setting();
                 }
@@ -270,34 +1179,326 @@ impl App {
     }
 
     fn save_journal_entry(&mut self) -> Result<()> {
-        self.processing = true;
-        self.message = Some("Processing with LLM...".to_string());
+        let entry = commands::new_raw_entry(&self.editor.text(), self.entry_day, &self.config).map(
+            |mut entry| {
+                entry.metadata.mood = self.entry_mood.map(|mood| mood.to_string());
+                entry.metadata.energy = self.entry_energy.map(|energy| energy.to_string());
+                entry
+            },
+        );
+
+        if self.config.file_format != FileFormat::Markdown {
+            let saved = entry.and_then(|entry| commands::save_entry(entry, &self.config));
+            self.finish_save(saved);
+            return Ok(());
+        }
+
+        // Markdown entries are formatted on a worker thread and reviewed
+        // before they are saved
+        let prompt = self.prompt_names.get(self.selected_prompt).cloned();
+        match entry {
+            Ok(entry) => self.start_formatting(entry, prompt.as_deref()),
+            Err(e) => {
+                self.message = Some(format!("Failed to save journal: {}", e));
+                self.message_type = MessageType::Error;
+            }
+        }
+        Ok(())
+    }
+
+    fn start_formatting(&mut self, entry: JournalEntry, prompt: Option<&str>) {
+        match commands::build_prompt(&entry, prompt, &self.config) {
+            Ok((prompt, text)) => {
+                let job = LlmJob::spawn(&self.config.llm, text);
+                self.message = Some(format!(
+                    "Formatting with {} using the '{}' prompt... Press Esc to cancel",
+                    job.model(),
+                    prompt
+                ));
+                self.message_type = MessageType::Info;
+                self.llm_preview.clear();
+                self.processing = true;
+                self.pending_entry = Some(PendingEntry { entry, prompt, job });
+            }
+            Err(e) => {
+                self.message = Some(format!("Failed to format journal: {}", e));
+                self.message_type = MessageType::Error;
+            }
+        }
+    }
+
+    // Collects streamed tokens from the LLM worker and saves the entry once the
+    // response is complete
+    fn poll_llm(&mut self) {
+        let Some(pending) = &self.pending_entry else {
+            return;
+        };
+
+        let mut finished = None;
+        for event in pending.job.poll() {
+            match event {
+                LlmEvent::Token(token) => self.llm_preview.push_str(&token),
+                LlmEvent::Finished(result) => finished = Some(result),
+            }
+        }
+        let Some(result) = finished else {
+            return;
+        };
+
+        let Some(PendingEntry { entry, prompt, job }) = self.pending_entry.take() else {
+            return;
+        };
+        self.processing = false;
+        self.llm_preview.clear();
+
+        match result {
+            Ok(output) => {
+                self.review = Some(Review {
+                    entry,
+                    formatted: output.trim().to_string(),
+                    prompt,
+                    model: job.model().to_string(),
+                    editor: None,
+                    scroll: 0,
+                });
+                self.current_screen = AppScreen::Review;
+                self.message = None;
+            }
+            Err(e) => self.save_without_llm(entry, &prompt, e),
+        }
+    }
+
+    // Applies the LLM fallback policy to an entry that couldn't be formatted
+    fn save_without_llm(&mut self, mut entry: JournalEntry, prompt: &str, error: anyhow::Error) {
+        let reason = error.to_string();
+        if let Err(e) = commands::llm_fallback(&mut entry, prompt, error, &self.config) {
+            self.message = Some(format!("Failed to format journal: {}", e));
+            self.message_type = MessageType::Error;
+            return;
+        }
+
+        let queued = entry.metadata.pending_formatting;
+        match commands::save_entry(entry, &self.config) {
+            Ok(_) => {
+                self.message = Some(if queued {
+                    format!(
+                        "LLM unavailable ({}). Entry saved as written and queued for formatting",
+                        reason
+                    )
+                } else {
+                    format!("LLM unavailable ({}). Entry saved as written", reason)
+                });
+                self.message_type = MessageType::Warning;
+                self.reset_input_state();
+            }
+            Err(e) => {
+                self.message = Some(format!("Failed to save journal: {}", e));
+                self.message_type = MessageType::Error;
+            }
+        }
+    }
+
+    // Saves the reviewed entry, formatted when accepted and as written otherwise
+    fn finish_review(&mut self, accept: bool) {
+        let Some(Review {
+            mut entry,
+            formatted,
+            prompt,
+            model,
+            ..
+        }) = self.review.take()
+        else {
+            return;
+        };
+
+        let saved = if accept {
+            commands::apply_llm_output(&mut entry, &formatted, &prompt, &model, &self.config)
+                .map_err(|e| e.into())
+                .and_then(|_| commands::save_entry(entry, &self.config))
+        } else {
+            commands::save_entry(entry, &self.config)
+        };
+        self.current_screen = AppScreen::AddJournal;
+        self.finish_save(saved);
+    }
+
+    // Formats queued entries on a background thread
+    fn start_processing_pending(&mut self) {
+        let config = self.config.clone();
+        self.pending_job = Some(std::thread::spawn(move || {
+            commands::process_pending(&config)
+        }));
+        self.message = Some("Formatting pending entries...".to_string());
         self.message_type = MessageType::Info;
+    }
+
+    fn poll_pending(&mut self) {
+        if !self
+            .pending_job
+            .as_ref()
+            .is_some_and(|job| job.is_finished())
+        {
+            return;
+        }
+        let Some(job) = self.pending_job.take() else {
+            return;
+        };
+
+        let result = job
+            .join()
+            .unwrap_or_else(|_| Err(anyhow::anyhow!("the worker stopped unexpectedly")));
+        if self.current_screen == AppScreen::ViewJournals {
+            self.load_journal_entries();
+        }
+
+        match result {
+            Ok(PendingReport {
+                error: Some(error),
+                formatted,
+                remaining,
+            }) => {
+                self.message = Some(format!(
+                    "Formatted {} entries, {} still pending: {}",
+                    formatted, remaining, error
+                ));
+                self.message_type = MessageType::Warning;
+            }
+            Ok(report) if report.formatted == 0 => {
+                self.message = Some("No entries are waiting for formatting".to_string());
+                self.message_type = MessageType::Info;
+            }
+            Ok(report) => {
+                self.message = Some(format!("Formatted {} pending entries", report.formatted));
+                self.message_type = MessageType::Success;
+            }
+            Err(e) => {
+                self.message = Some(format!("Failed to process pending entries: {}", e));
+                self.message_type = MessageType::Error;
+            }
+        }
+    }
 
-        match commands::save_journal_entry_with_config(&self.current_input, &self.config) {
+    fn cancel_llm(&mut self) {
+        // Dropping the job stops the request
+        self.pending_entry = None;
+        self.processing = false;
+        self.llm_preview.clear();
+        self.message = Some("Formatting cancelled, the entry was not saved".to_string());
+        self.message_type = MessageType::Warning;
+    }
+
+    fn finish_save(&mut self, saved: Result<(), Box<dyn std::error::Error>>) {
+        match saved {
             Ok(_) => {
                 self.message = Some("Journal entry saved successfully!".to_string());
                 self.message_type = MessageType::Success;
                 self.reset_input_state();
             }
             Err(e) => {
+                // The text is kept so the save can be retried
                 self.message = Some(format!("Failed to save journal: {}", e));
                 self.message_type = MessageType::Error;
             }
         }
+        self.llm_preview.clear();
+    }
 
-        self.processing = false;
-        Ok(())
+    fn update_search(&mut self) {
+        self.search_matches.clear();
+        self.search_snippets.clear();
+
+        let query = SearchQuery::parse(&self.search_input);
+        if query.is_empty() {
+            return;
+        }
+
+        if self.search_index.is_none() {
+            self.search_index = SearchIndex::open(&self.config).ok();
+        }
+        let hits = match &self.search_index {
+            Some(index) => index.search(&query, &self.config),
+            None => Err(anyhow::anyhow!("search index unavailable")),
+        };
+
+        match hits {
+            Ok(hits) => {
+                // Matches are kept in rank order so 'n' walks from the best hit down
+                for hit in hits {
+                    if let Some(position) = self.journal_entries.iter().position(|entry| {
+                        entry.id == hit.entry_id && entry.filename == hit.filename
+                    }) {
+                        self.search_matches.push(position);
+                        self.search_snippets.insert(position, hit.snippet);
+                    }
+                }
+            }
+            Err(_) => {
+                // Fall back to scanning the loaded entries
+                self.search_matches = self
+                    .journal_entries
+                    .iter()
+                    .enumerate()
+                    .filter(|(_, entry)| query.matches(&entry.body))
+                    .map(|(index, _)| index)
+                    .collect();
+            }
+        }
+
+        // Keep the selection on a match while the query is being typed
+        if !self.search_matches.contains(&self.selected_journal) {
+            if let Some(&first) = self.search_matches.first() {
+                self.selected_journal = first;
+            }
+        }
+    }
+
+    fn jump_to_match(&mut self, forward: bool) {
+        if self.search_matches.is_empty() {
+            return;
+        }
+
+        let count = self.search_matches.len();
+        let next = match self
+            .search_matches
+            .iter()
+            .position(|&index| index == self.selected_journal)
+        {
+            Some(position) if forward => (position + 1) % count,
+            Some(position) => (position + count - 1) % count,
+            None => 0,
+        };
+        self.selected_journal = self.search_matches[next];
+    }
+
+    fn clear_search(&mut self) {
+        self.search_input.clear();
+        self.search_editing = false;
+        self.search_matches.clear();
+        self.search_snippets.clear();
     }
 
     fn load_journal_entries(&mut self) {
         match commands::load_journal_entries_with_config(&self.config) {
             Ok(entries) => {
-                self.journal_entries = entries;
-                self.selected_journal = 0;
+                self.tag_counts = storage::tag_counts(&entries);
+                self.selected_tag = self
+                    .selected_tag
+                    .min(self.tag_counts.len().saturating_sub(1));
+                self.all_journal_entries = entries;
+                if self.mood_insights.is_some() {
+                    self.refresh_insights();
+                }
+                if self.journal_stats.is_some() {
+                    self.refresh_stats();
+                }
+                self.journal_entries.clear();
+                self.preview_scroll = 0;
+                // Reopening the index picks up files changed since it was loaded
+                self.search_index = None;
+                self.apply_tag_filter();
                 self.message = Some(format!(
                     "Loaded {} journal entries",
-                    self.journal_entries.len()
+                    self.all_journal_entries.len()
                 ));
                 self.message_type = MessageType::Success;
             }
@@ -317,6 +1518,15 @@ impl App {
                 crate::config::FileFormat::PlainText => "txt".to_string(),
                 crate::config::FileFormat::Json => "json".to_string(),
             },
+            2 => self.config.date_format.clone(),
+            3 => self.config.time_zone.clone(),
+            4 => self.config.llm.model.clone(),
+            5 => if self.config.mood_check_in {
+                "on"
+            } else {
+                "off"
+            }
+            .to_string(),
             _ => String::new(),
         };
     }
@@ -335,6 +1545,68 @@ impl App {
                     _ => crate::config::FileFormat::Markdown,
                 };
             }
+            2 => {
+                // Change date format, rejecting patterns that can't name a journal file
+                if let Err(e) = crate::utils::DateFormat::new(&self.settings_input) {
+                    self.message = Some(e.to_string());
+                    self.message_type = MessageType::Error;
+                    return Ok(());
+                }
+                if self.settings_input != self.config.date_format {
+                    // Day files are renamed first so the journal keeps listing
+                    // them; the setting isn't saved when they can't be
+                    let updated = Config {
+                        date_format: self.settings_input.clone(),
+                        ..self.config.clone()
+                    };
+                    match storage::rename_day_files(&updated, &self.config.date_format) {
+                        Ok(renamed) => {
+                            self.config = updated;
+                            self.config.save()?;
+                            self.message = Some(format!(
+                                "Date format saved and {} day files renamed",
+                                renamed
+                            ));
+                            self.message_type = MessageType::Success;
+                        }
+                        Err(e) => {
+                            self.message = Some(format!(
+                                "Date format not changed, renaming the day files failed: {}",
+                                e
+                            ));
+                            self.message_type = MessageType::Error;
+                        }
+                    }
+                    let _ = SearchIndex::delete(&self.config);
+                    self.search_index = None;
+                    return Ok(());
+                }
+            }
+            3 => {
+                // Change time zone used for entry dates and timestamps
+                if let Err(e) = crate::utils::JournalTimeZone::from_setting(&self.settings_input) {
+                    self.message = Some(e.to_string());
+                    self.message_type = MessageType::Error;
+                    return Ok(());
+                }
+                self.config.time_zone = self.settings_input.trim().to_string();
+            }
+            4 => {
+                // Change the model used for LLM formatting
+                self.config.llm.model = self.settings_input.trim().to_string();
+            }
+            5 => {
+                // Ask for mood and energy before saving a new entry
+                self.config.mood_check_in = match self.settings_input.trim() {
+                    "on" | "yes" | "true" => true,
+                    "off" | "no" | "false" => false,
+                    other => {
+                        self.message = Some(format!("Type 'on' or 'off', not '{}'", other));
+                        self.message_type = MessageType::Error;
+                        return Ok(());
+                    }
+                };
+            }
             _ => {}
         }
 
@@ -345,13 +1617,22 @@ impl App {
     }
 
     fn reset_input_state(&mut self) {
-        self.current_input.clear();
-        self.cursor_position = 0;
+        self.editor.clear();
+        self.entry_day = None;
+        self.entry_mood = None;
+        self.entry_energy = None;
+        self.check_in = None;
         self.input_mode = InputMode::Normal;
         self.processing = false;
     }
 
     fn on_tick(&mut self) {
-        // Handle any periodic updates here
+        if self.processing || self.pending_job.is_some() {
+            self.spinner_frame = self.spinner_frame.wrapping_add(1);
+        }
     }
 }
+
+fn add_days(day: Date, days: i64) -> Date {
+    day.checked_add(time::Duration::days(days)).unwrap_or(day)
+}
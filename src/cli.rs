@@ -0,0 +1,565 @@
+use crate::commands::OutputFormat;
+use std::collections::BTreeMap;
+
+// A command line invocation, parsed from the arguments after the program name
+#[derive(Debug, PartialEq)]
+pub enum Command {
+    Tui,
+    Help,
+    // The interactive -aj flow
+    AddJournal {
+        date: Option<String>,
+    },
+    Add {
+        text: Option<String>,
+        file: Option<String>,
+        date: Option<String>,
+        prompt: Option<String>,
+        raw: bool,
+        mood: Option<String>,
+        energy: Option<String>,
+    },
+    List {
+        from: Option<String>,
+        to: Option<String>,
+        limit: Option<usize>,
+        tags: Vec<String>,
+        // Entries need any of the tags instead of all of them
+        any_tag: bool,
+        output: OutputFormat,
+    },
+    Show {
+        date: String,
+        id: Option<String>,
+        output: OutputFormat,
+    },
+    Search {
+        query: String,
+        limit: Option<usize>,
+        output: OutputFormat,
+    },
+    Edit {
+        date: Option<String>,
+        entry_date: Option<String>,
+    },
+    Delete {
+        date: String,
+        id: Option<String>,
+        all: bool,
+    },
+    Export {
+        format: Option<String>,
+        output: Option<String>,
+    },
+    Stats {
+        output: OutputFormat,
+    },
+    ConfigGet {
+        key: Option<String>,
+    },
+    ConfigSet {
+        key: String,
+        value: String,
+    },
+    ProcessPending,
+    MigrateFormat {
+        to: Option<String>,
+        from: Option<String>,
+    },
+    Encrypt,
+    Decrypt,
+    ChangePassphrase,
+    Backup {
+        output: Option<String>,
+    },
+    Restore {
+        archive: Option<String>,
+    },
+}
+
+impl Command {
+    // Whether the command reads or writes entries, so an encrypted journal has
+    // to be unlocked first. The encryption commands ask for what they need,
+    // and backups copy the files as they are.
+    pub fn reads_journal(&self) -> bool {
+        !matches!(
+            self,
+            Command::Tui
+                | Command::Help
+                | Command::ConfigGet { .. }
+                | Command::ConfigSet { .. }
+                | Command::Encrypt
+                | Command::Decrypt
+                | Command::ChangePassphrase
+                | Command::Backup { .. }
+                | Command::Restore { .. }
+        )
+    }
+}
+
+// Options of a command: the long name, an optional short alias, and whether it
+// takes a value
+type OptionSpec = (&'static str, Option<&'static str>, bool);
+
+const DATE: OptionSpec = ("--date", Some("-d"), true);
+const ID: OptionSpec = ("--id", None, true);
+const LIMIT: OptionSpec = ("--limit", Some("-n"), true);
+const OUTPUT_FORMAT: OptionSpec = ("--output-format", None, true);
+const JSON: OptionSpec = ("--json", None, false);
+const NDJSON: OptionSpec = ("--ndjson", None, false);
+const CSV: OptionSpec = ("--csv", None, false);
+
+pub fn parse(args: &[String]) -> Result<Command, String> {
+    let Some(name) = args.first() else {
+        return Ok(Command::Tui);
+    };
+    let rest = &args[1..];
+
+    let command = match name.as_str() {
+        "-t" | "--tui" | "tui" => Command::Tui,
+        "-h" | "--help" | "help" => Command::Help,
+        "-aj" | "--add-journal" => {
+            let mut parsed = Parsed::new("-aj", rest, &[DATE], false)?;
+            parsed.no_positional()?;
+            Command::AddJournal {
+                date: parsed.value("--date"),
+            }
+        }
+        "add" => {
+            let mut parsed = Parsed::new(
+                name,
+                rest,
+                &[
+                    DATE,
+                    ("--file", Some("-f"), true),
+                    ("--prompt", Some("-p"), true),
+                    ("--raw", None, false),
+                    ("--mood", None, true),
+                    ("--energy", None, true),
+                ],
+                false,
+            )?;
+            let text = (!parsed.positional.is_empty()).then(|| parsed.positional.join(" "));
+            Command::Add {
+                text,
+                file: parsed.value("--file"),
+                date: parsed.value("--date"),
+                prompt: parsed.value("--prompt"),
+                raw: parsed.flag("--raw"),
+                mood: parsed.value("--mood"),
+                energy: parsed.value("--energy"),
+            }
+        }
+        "list" | "ls" => {
+            let mut parsed = Parsed::new(
+                name,
+                rest,
+                &[
+                    ("--from", None, true),
+                    ("--to", None, true),
+                    LIMIT,
+                    ("--tag", None, true),
+                    ("--any-tag", None, false),
+                    OUTPUT_FORMAT,
+                    JSON,
+                    NDJSON,
+                    CSV,
+                ],
+                false,
+            )?;
+            parsed.no_positional()?;
+            Command::List {
+                from: parsed.value("--from"),
+                to: parsed.value("--to"),
+                limit: parsed.number("--limit")?,
+                tags: parsed.list("--tag"),
+                any_tag: parsed.flag("--any-tag"),
+                output: parsed.output_format()?,
+            }
+        }
+        "show" => {
+            let mut parsed =
+                Parsed::new(name, rest, &[ID, OUTPUT_FORMAT, JSON, NDJSON, CSV], false)?;
+            Command::Show {
+                date: parsed.single_positional("DATE")?,
+                id: parsed.value("--id"),
+                output: parsed.output_format()?,
+            }
+        }
+        // Unknown dashed words are query terms, so `search rust -python` works
+        "search" => {
+            let mut parsed =
+                Parsed::new(name, rest, &[LIMIT, OUTPUT_FORMAT, JSON, NDJSON, CSV], true)?;
+            if parsed.positional.is_empty() {
+                return Err("search needs a query".to_string());
+            }
+            Command::Search {
+                query: parsed.positional.join(" "),
+                limit: parsed.number("--limit")?,
+                output: parsed.output_format()?,
+            }
+        }
+        "edit" | "-e" | "--editor" => {
+            let mut parsed = Parsed::new(name, rest, &[DATE], false)?;
+            let date = parsed.optional_positional("DATE")?;
+            Command::Edit {
+                date,
+                entry_date: parsed.value("--date"),
+            }
+        }
+        "delete" | "rm" => {
+            let mut parsed = Parsed::new(name, rest, &[ID, ("--all", None, false)], false)?;
+            Command::Delete {
+                date: parsed.single_positional("DATE")?,
+                id: parsed.value("--id"),
+                all: parsed.flag("--all"),
+            }
+        }
+        "export" => {
+            let mut parsed = Parsed::new(
+                name,
+                rest,
+                &[("--format", None, true), ("--output", Some("-o"), true)],
+                false,
+            )?;
+            parsed.no_positional()?;
+            Command::Export {
+                format: parsed.value("--format"),
+                output: parsed.value("--output"),
+            }
+        }
+        "stats" => {
+            let mut parsed = Parsed::new(name, rest, &[OUTPUT_FORMAT, JSON, NDJSON], false)?;
+            parsed.no_positional()?;
+            let output = parsed.output_format()?;
+            if output == OutputFormat::Csv {
+                return Err("stats can't be printed as CSV".to_string());
+            }
+            Command::Stats { output }
+        }
+        "config" => {
+            let mut parsed = Parsed::new(name, rest, &[], false)?;
+            let mut positional = parsed.positional.drain(..);
+            match (
+                positional.next().as_deref(),
+                positional.next(),
+                positional.next(),
+            ) {
+                (Some("get"), key, None) => Command::ConfigGet { key },
+                (Some("set"), Some(key), Some(value)) if positional.next().is_none() => {
+                    Command::ConfigSet { key, value }
+                }
+                _ => return Err("usage: config get [KEY] | config set KEY VALUE".to_string()),
+            }
+        }
+        "--process-pending" | "process-pending" => {
+            Parsed::new(name, rest, &[], false)?.no_positional()?;
+            Command::ProcessPending
+        }
+        "migrate-format" => {
+            let mut parsed = Parsed::new(name, rest, &[("--from", None, true)], false)?;
+            Command::MigrateFormat {
+                to: parsed.optional_positional("FORMAT")?,
+                from: parsed.value("--from"),
+            }
+        }
+        "encrypt" => {
+            Parsed::new(name, rest, &[], false)?.no_positional()?;
+            Command::Encrypt
+        }
+        "decrypt" => {
+            Parsed::new(name, rest, &[], false)?.no_positional()?;
+            Command::Decrypt
+        }
+        "change-passphrase" => {
+            Parsed::new(name, rest, &[], false)?.no_positional()?;
+            Command::ChangePassphrase
+        }
+        "backup" => {
+            let mut parsed = Parsed::new(name, rest, &[("--output", Some("-o"), true)], false)?;
+            parsed.no_positional()?;
+            Command::Backup {
+                output: parsed.value("--output"),
+            }
+        }
+        "restore" => {
+            let mut parsed = Parsed::new(name, rest, &[], false)?;
+            Command::Restore {
+                archive: parsed.optional_positional("ARCHIVE")?,
+            }
+        }
+        other => return Err(format!("unknown command '{}'", other)),
+    };
+
+    Ok(command)
+}
+
+struct Parsed {
+    command: String,
+    positional: Vec<String>,
+    // Every value given for each option, in order
+    values: BTreeMap<&'static str, Vec<String>>,
+}
+
+impl Parsed {
+    // Splits arguments into positionals and the given options. `--name=value`
+    // is accepted, a lone `-` is a positional and `--` ends the options.
+    fn new(
+        command: &str,
+        args: &[String],
+        specs: &[OptionSpec],
+        dashed_positionals: bool,
+    ) -> Result<Self, String> {
+        let mut parsed = Self {
+            command: command.to_string(),
+            positional: Vec::new(),
+            values: BTreeMap::new(),
+        };
+
+        let mut args = args.iter();
+        while let Some(arg) = args.next() {
+            if arg == "--" {
+                parsed.positional.extend(args.by_ref().cloned());
+                break;
+            }
+            if !arg.starts_with('-') || arg == "-" {
+                parsed.positional.push(arg.clone());
+                continue;
+            }
+
+            let (name, inline) = match arg.split_once('=') {
+                Some((name, value)) if arg.starts_with("--") => (name, Some(value.to_string())),
+                _ => (arg.as_str(), None),
+            };
+            let Some(&(long, _, takes_value)) = specs
+                .iter()
+                .find(|(long, short, _)| *long == name || *short == Some(name))
+            else {
+                if dashed_positionals {
+                    parsed.positional.push(arg.clone());
+                    continue;
+                }
+                return Err(format!("unknown option '{}' for {}", arg, command));
+            };
+
+            let value = if takes_value {
+                match inline.or_else(|| args.next().cloned()) {
+                    Some(value) => value,
+                    None => return Err(format!("{} needs a value", long)),
+                }
+            } else if inline.is_some() {
+                return Err(format!("{} doesn't take a value", long));
+            } else {
+                String::new()
+            };
+            parsed.values.entry(long).or_default().push(value);
+        }
+
+        Ok(parsed)
+    }
+
+    // The last value given for an option
+    fn value(&mut self, name: &str) -> Option<String> {
+        self.values.remove(name)?.pop()
+    }
+
+    // Values of a repeatable option, which may also be comma-separated
+    fn list(&mut self, name: &str) -> Vec<String> {
+        self.values
+            .remove(name)
+            .unwrap_or_default()
+            .iter()
+            .flat_map(|value| value.split(','))
+            .map(str::trim)
+            .filter(|value| !value.is_empty())
+            .map(str::to_string)
+            .collect()
+    }
+
+    fn flag(&mut self, name: &str) -> bool {
+        self.values.remove(name).is_some()
+    }
+
+    fn number(&mut self, name: &str) -> Result<Option<usize>, String> {
+        self.value(name)
+            .map(|value| {
+                value
+                    .parse()
+                    .map_err(|_| format!("{} needs a number, got '{}'", name, value))
+            })
+            .transpose()
+    }
+
+    // `--output-format FORMAT`, or one of its shorthands `--json`, `--ndjson`
+    // and `--csv`
+    fn output_format(&mut self) -> Result<OutputFormat, String> {
+        let mut formats = Vec::new();
+        if let Some(name) = self.value("--output-format") {
+            formats.push(
+                OutputFormat::from_name(&name)
+                    .ok_or_else(|| format!("unknown output format '{}'", name))?,
+            );
+        }
+        for (flag, format) in [
+            ("--json", OutputFormat::Json),
+            ("--ndjson", OutputFormat::Ndjson),
+            ("--csv", OutputFormat::Csv),
+        ] {
+            if self.flag(flag) {
+                formats.push(format);
+            }
+        }
+
+        match formats.as_slice() {
+            [] => Ok(OutputFormat::Text),
+            [format] => Ok(*format),
+            _ => Err("only one output format can be given".to_string()),
+        }
+    }
+
+    fn no_positional(&mut self) -> Result<(), String> {
+        match self.positional.first() {
+            Some(arg) => Err(format!(
+                "unexpected argument '{}' for {}",
+                arg, self.command
+            )),
+            None => Ok(()),
+        }
+    }
+
+    fn optional_positional(&mut self, name: &str) -> Result<Option<String>, String> {
+        if self.positional.len() > 1 {
+            return Err(format!("{} takes a single {}", self.command, name));
+        }
+        Ok(self.positional.pop())
+    }
+
+    fn single_positional(&mut self, name: &str) -> Result<String, String> {
+        self.optional_positional(name)?
+            .ok_or_else(|| format!("{} needs a {}", self.command, name))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse_line(line: &str) -> Result<Command, String> {
+        let args: Vec<String> = line.split_whitespace().map(str::to_string).collect();
+        parse(&args)
+    }
+
+    #[test]
+    fn options_take_values_separately_or_inline() {
+        assert_eq!(
+            parse_line("list --from 2024-05-01 -n 5 --tag=work --tag travel,home --any-tag --json"),
+            Ok(Command::List {
+                from: Some("2024-05-01".to_string()),
+                to: None,
+                limit: Some(5),
+                tags: vec!["work".to_string(), "travel".to_string(), "home".to_string()],
+                any_tag: true,
+                output: OutputFormat::Json,
+            })
+        );
+        assert_eq!(
+            parse_line("show 2024-05-04 --id=abc --output-format=csv"),
+            Ok(Command::Show {
+                date: "2024-05-04".to_string(),
+                id: Some("abc".to_string()),
+                output: OutputFormat::Csv,
+            })
+        );
+        // The last value of a repeated option wins
+        assert_eq!(
+            parse_line("backup -o first.tar.gz --output=second.tar.gz"),
+            Ok(Command::Backup {
+                output: Some("second.tar.gz".to_string())
+            })
+        );
+    }
+
+    #[test]
+    fn double_dash_ends_the_options() {
+        assert_eq!(
+            parse_line("add --raw -- --mood 5 -"),
+            Ok(Command::Add {
+                text: Some("--mood 5 -".to_string()),
+                file: None,
+                date: None,
+                prompt: None,
+                raw: true,
+                mood: None,
+                energy: None,
+            })
+        );
+        assert_eq!(
+            parse_line("add -f -"),
+            Ok(Command::Add {
+                text: None,
+                file: Some("-".to_string()),
+                date: None,
+                prompt: None,
+                raw: false,
+                mood: None,
+                energy: None,
+            })
+        );
+    }
+
+    // main exits with status 2 for all of these
+    #[test]
+    fn usage_errors_are_reported() {
+        let errors = [
+            ("list --bogus", "unknown option '--bogus' for list"),
+            ("list --limit", "--limit needs a value"),
+            ("list --limit five", "--limit needs a number, got 'five'"),
+            ("list --json=yes", "--json doesn't take a value"),
+            ("list --json --csv", "only one output format can be given"),
+            ("list extra", "unexpected argument 'extra' for list"),
+            ("show", "show needs a DATE"),
+            ("show 2024-05-04 2024-05-05", "show takes a single DATE"),
+            ("stats --csv", "unknown option '--csv' for stats"),
+            (
+                "config set date_format",
+                "usage: config get [KEY] | config set KEY VALUE",
+            ),
+            ("search --json", "search needs a query"),
+            ("frobnicate", "unknown command 'frobnicate'"),
+        ];
+        for (line, error) in errors {
+            assert_eq!(parse_line(line), Err(error.to_string()), "{}", line);
+        }
+    }
+
+    #[test]
+    fn search_terms_may_start_with_a_dash() {
+        assert_eq!(
+            parse_line("search rust -python --limit 3 -boat"),
+            Ok(Command::Search {
+                query: "rust -python -boat".to_string(),
+                limit: Some(3),
+                output: OutputFormat::Text,
+            })
+        );
+        assert_eq!(
+            parse_line("search -- --limit"),
+            Ok(Command::Search {
+                query: "--limit".to_string(),
+                limit: None,
+                output: OutputFormat::Text,
+            })
+        );
+    }
+
+    #[test]
+    fn no_arguments_start_the_tui() {
+        assert_eq!(parse(&[]), Ok(Command::Tui));
+        assert_eq!(
+            parse_line("-aj --date yesterday"),
+            Ok(Command::AddJournal {
+                date: Some("yesterday".to_string())
+            })
+        );
+    }
+}
@@ -0,0 +1,44 @@
+use crate::config::Config;
+use crate::search::SearchIndex;
+use crate::storage;
+use anyhow::{anyhow, Result};
+use colored::Colorize;
+use std::path::{Path, PathBuf};
+
+// Archives the journal directory. Encrypted files are archived as they are,
+// so the journal doesn't have to be unlocked.
+pub fn exe_backup(output: Option<&str>) -> Result<()> {
+    let config = Config::load()?;
+    let path = storage::create_archive(&config, output.map(Path::new))?;
+    println!(
+        "{}",
+        format!("Backed up the journal to {}", path.display()).green()
+    );
+    Ok(())
+}
+
+// Restores an archive, or the newest one in the backup directory
+pub fn exe_restore(archive: Option<&str>) -> Result<()> {
+    let config = Config::load()?;
+    let archive = match archive {
+        Some(archive) => PathBuf::from(archive),
+        None => storage::latest_archive(&config)?.ok_or_else(|| {
+            anyhow!(
+                "No backups in {}; give the archive to restore",
+                config.backup_directory().display()
+            )
+        })?,
+    };
+
+    let report = storage::restore_archive(&config, &archive)?;
+    // Rebuilt from the restored files on the next search
+    SearchIndex::delete(&config)?;
+    println!(
+        "{}",
+        format!("Restored {} files from {}", report.files, archive.display()).green()
+    );
+    if let Some(previous) = report.previous {
+        println!("The journal as it was before is in {}", previous.display());
+    }
+    Ok(())
+}
@@ -0,0 +1,110 @@
+use crate::config::Config;
+use crate::search::SearchIndex;
+use crate::storage::{self, PASSPHRASE_VARIABLE};
+use crate::utils;
+use anyhow::{anyhow, bail, Result};
+use colored::Colorize;
+use std::env;
+use std::io::{self, IsTerminal};
+
+// Read for the new passphrase by change-passphrase instead of asking
+const NEW_PASSPHRASE_VARIABLE: &str = "RUSTY_DIARY_NEW_PASSPHRASE";
+
+// Unlocks an encrypted journal before a command reads it, with the passphrase
+// from RUSTY_DIARY_PASSPHRASE or typed at the terminal
+pub fn unlock_journal() -> Result<()> {
+    unlock(&Config::load()?)
+}
+
+fn unlock(config: &Config) -> Result<()> {
+    if !storage::is_encrypted(config) || storage::unlock_from_env(config)? {
+        return Ok(());
+    }
+    if !io::stdin().is_terminal() {
+        bail!(
+            "The journal is encrypted. Set {} to unlock it without a terminal",
+            PASSPHRASE_VARIABLE
+        );
+    }
+    let passphrase = utils::get_passphrase("Passphrase: ")?;
+    storage::unlock(config, &passphrase)
+}
+
+// A new passphrase from the environment variable, or typed twice
+fn new_passphrase(variable: &str) -> Result<String> {
+    if let Ok(passphrase) = env::var(variable) {
+        if passphrase.is_empty() {
+            bail!("{} is empty", variable);
+        }
+        return Ok(passphrase);
+    }
+    if !io::stdin().is_terminal() {
+        bail!(
+            "Set {} to give the new passphrase without a terminal",
+            variable
+        );
+    }
+
+    let passphrase = utils::get_passphrase("New passphrase: ")?;
+    if passphrase.is_empty() {
+        bail!("The passphrase is empty");
+    }
+    if utils::get_passphrase("Repeat the passphrase: ")? != passphrase {
+        bail!("The passphrases don't match");
+    }
+    Ok(passphrase)
+}
+
+// Encrypts a plain journal in place, or finishes an interrupted run
+pub fn exe_encrypt() -> Result<()> {
+    let config = Config::load()?;
+    let passphrase = if storage::is_encrypted(&config) {
+        unlock(&config)?;
+        None
+    } else {
+        Some(new_passphrase(PASSPHRASE_VARIABLE)?)
+    };
+
+    let count = storage::encrypt_journal(&config, passphrase.as_deref())?;
+    SearchIndex::delete(&config)?;
+    println!("{}", format!("Encrypted {} files.", count).green());
+    if passphrase.is_some() {
+        println!(
+            "{}",
+            "Keep the passphrase safe: the entries can't be read without it.".yellow()
+        );
+    }
+    Ok(())
+}
+
+// Decrypts the journal in place
+pub fn exe_decrypt() -> Result<()> {
+    let config = Config::load()?;
+    if !storage::is_encrypted(&config) {
+        bail!("The journal isn't encrypted");
+    }
+    unlock(&config)?;
+
+    let count = storage::decrypt_journal(&config)?;
+    SearchIndex::delete(&config)?;
+    println!("{}", format!("Decrypted {} files.", count).green());
+    Ok(())
+}
+
+// Re-encrypts the journal under a new passphrase
+pub fn exe_change_passphrase() -> Result<()> {
+    let config = Config::load()?;
+    if !storage::is_encrypted(&config) {
+        bail!("The journal isn't encrypted; run `rusty_diary encrypt` first");
+    }
+    unlock(&config).map_err(|e| anyhow!("Current passphrase: {}", e))?;
+
+    let passphrase = new_passphrase(NEW_PASSPHRASE_VARIABLE)?;
+    let count = storage::change_passphrase(&config, &passphrase)?;
+    SearchIndex::delete(&config)?;
+    println!(
+        "{}",
+        format!("Changed the passphrase and re-encrypted {} files.", count).green()
+    );
+    Ok(())
+}
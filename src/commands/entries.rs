@@ -0,0 +1,438 @@
+use super::load_journal_entries_with_config;
+use super::output::{print_records, EntryRecord, OutputFormat, ENTRY_COLUMNS};
+use crate::config::{Config, FileFormat};
+use crate::search::{self, SearchHit, SearchIndex, SearchQuery};
+use crate::stats::{JournalStats, Streak};
+use crate::storage::{self, DayFile, JournalEntry, TagFilter};
+use crate::utils;
+use anyhow::{anyhow, bail, Result};
+use colored::Colorize;
+use std::collections::HashMap;
+use std::io::{self, Write};
+use std::{fmt, fs};
+use time::Date;
+
+// Characters of an entry's title or first line shown by `list`
+const PREVIEW_LENGTH: usize = 60;
+
+// Returned when a command found no entries, so scripts can tell an empty
+// result from a failure
+#[derive(Debug)]
+pub struct NoMatch(pub String);
+
+impl fmt::Display for NoMatch {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+impl std::error::Error for NoMatch {}
+
+// Prints one line per entry, newest first, optionally limited to a range of
+// days and to entries with the given tags
+pub fn exe_list(
+    from: Option<&str>,
+    to: Option<&str>,
+    limit: Option<usize>,
+    tags: &TagFilter,
+    output: OutputFormat,
+) -> Result<()> {
+    let config = Config::load()?;
+    let from = from.map(|date| parse_day(date, &config)).transpose()?;
+    let to = to.map(|date| parse_day(date, &config)).transpose()?;
+
+    let entries = load_entries(&config)?;
+    let entries: Vec<&JournalEntry> = entries
+        .iter()
+        .filter(|entry| from.is_none_or(|from| entry.day >= from))
+        .filter(|entry| to.is_none_or(|to| entry.day <= to))
+        .filter(|entry| tags.matches(entry))
+        .take(limit.unwrap_or(usize::MAX))
+        .collect();
+
+    let mut out = io::stdout().lock();
+    if output != OutputFormat::Text {
+        let records: Vec<EntryRecord> = entries.into_iter().map(EntryRecord::new).collect();
+        return print_records(&mut out, &records, output, &ENTRY_COLUMNS);
+    }
+
+    for entry in entries {
+        let preview: String = entry
+            .metadata
+            .title
+            .as_deref()
+            .or_else(|| {
+                entry
+                    .body
+                    .lines()
+                    .map(str::trim)
+                    .find(|line| !line.is_empty())
+            })
+            .unwrap_or_default()
+            .chars()
+            .take(PREVIEW_LENGTH)
+            .collect();
+        let labels: Vec<String> = entry.labels().collect();
+        writeln!(
+            out,
+            "{} {}  {}  {}  {}",
+            entry.date.cyan(),
+            entry.time_label().unwrap_or_else(|| "--:--".to_string()),
+            format_badge(entry).dimmed(),
+            preview,
+            labels.join(" ").dimmed()
+        )?;
+    }
+    Ok(())
+}
+
+// Prints the entries of a day (or only the one with `id`) as written
+pub fn exe_show(date: &str, id: Option<&str>, output: OutputFormat) -> Result<()> {
+    let config = Config::load()?;
+    let day = parse_day(date, &config)?;
+    let entries = select_entries(storage::read_day(&config, day)?, id, date);
+
+    let mut out = io::stdout().lock();
+    if output != OutputFormat::Text {
+        let records: Vec<EntryRecord> = entries
+            .iter()
+            .flatten()
+            .map(|entry| EntryRecord {
+                body: Some(entry.body.clone()),
+                ..EntryRecord::new(entry)
+            })
+            .collect();
+        // Nothing found still prints an empty result, so the output always parses
+        print_records(
+            &mut out,
+            &records,
+            output,
+            &[&ENTRY_COLUMNS[..], &["body"]].concat(),
+        )?;
+        return entries.map(|_| ());
+    }
+
+    for (index, entry) in entries?.iter().enumerate() {
+        if index > 0 {
+            writeln!(out)?;
+        }
+        print_entry_header(&mut out, entry)?;
+        if let Some(title) = &entry.metadata.title {
+            writeln!(out, "{}", title.bold())?;
+        }
+        writeln!(out, "{}", entry.body)?;
+    }
+    Ok(())
+}
+
+// Ranked search over the index, falling back to scanning every entry
+pub fn exe_search(query: &str, limit: Option<usize>, output: OutputFormat) -> Result<()> {
+    let config = Config::load()?;
+    let query = SearchQuery::parse(query);
+    if query.is_empty() {
+        bail!("The search query is empty");
+    }
+
+    let limit = limit.unwrap_or(usize::MAX);
+    // Without the index, every match scores the same
+    let hits: Vec<(JournalEntry, f64, String)> =
+        match SearchIndex::open(&config).and_then(|index| index.search(&query, &config)) {
+            Ok(hits) => read_hits(&config, hits, limit)?,
+            Err(_) => load_entries(&config)?
+                .into_iter()
+                .filter(|entry| query.matches(&entry.body))
+                .take(limit)
+                .map(|entry| {
+                    let snippet = entry.body.clone();
+                    (entry, 0.0, snippet)
+                })
+                .collect(),
+        };
+    let hits: Vec<(JournalEntry, f64, String)> = hits
+        .into_iter()
+        .map(|(entry, score, snippet)| {
+            let snippet = snippet.split_whitespace().collect::<Vec<_>>().join(" ");
+            (entry, score, snippet)
+        })
+        .collect();
+
+    let mut out = io::stdout().lock();
+    if output != OutputFormat::Text {
+        let records: Vec<EntryRecord> = hits
+            .iter()
+            .map(|(entry, score, snippet)| EntryRecord {
+                score: Some(*score),
+                snippet: Some(snippet.clone()),
+                ..EntryRecord::new(entry)
+            })
+            .collect();
+        print_records(
+            &mut out,
+            &records,
+            output,
+            &[&ENTRY_COLUMNS[..], &["score", "snippet"]].concat(),
+        )?;
+    }
+    if hits.is_empty() {
+        return Err(NoMatch("No entries match the query".to_string()).into());
+    }
+    if output != OutputFormat::Text {
+        return Ok(());
+    }
+
+    for (entry, _, snippet) in hits {
+        writeln!(
+            out,
+            "{} {}  {}",
+            entry.date.cyan(),
+            entry.time_label().unwrap_or_else(|| "--:--".to_string()),
+            snippet
+        )?;
+    }
+    Ok(())
+}
+
+// The entries of the first `limit` index hits. Only the day files holding them
+// are read.
+fn read_hits(
+    config: &Config,
+    hits: Vec<SearchHit>,
+    limit: usize,
+) -> Result<Vec<(JournalEntry, f64, String)>> {
+    let mut day_files: HashMap<String, DayFile> = storage::list_day_files(config)?
+        .into_iter()
+        .filter_map(|day_file| {
+            let name = day_file.path.file_name()?.to_string_lossy().to_string();
+            Some((name, day_file))
+        })
+        .collect();
+    let mut read: HashMap<String, Vec<JournalEntry>> = HashMap::new();
+
+    let mut found = Vec::new();
+    for hit in hits {
+        if found.len() >= limit {
+            break;
+        }
+        if !read.contains_key(&hit.filename) {
+            let entries = match day_files.remove(&hit.filename) {
+                Some(day_file) => day_file
+                    .read()
+                    .map_err(|e| anyhow!("Failed to read {}: {}", hit.filename, e))?,
+                None => Vec::new(),
+            };
+            read.insert(hit.filename.clone(), entries);
+        }
+        if let Some(entry) = read[&hit.filename]
+            .iter()
+            .find(|entry| entry.id == hit.entry_id)
+        {
+            found.push((entry.clone(), hit.score, hit.snippet));
+        }
+    }
+    Ok(found)
+}
+
+// Moves the entries of a day to the trash. A day with several entries needs
+// `id` or `all` so a script can't delete more than it meant to.
+pub fn exe_delete(date: &str, id: Option<&str>, all: bool) -> Result<()> {
+    let config = Config::load()?;
+    let day = parse_day(date, &config)?;
+    let entries = select_entries(storage::read_day(&config, day)?, id, date)?;
+
+    if entries.len() > 1 && !all {
+        let ids: Vec<String> = entries
+            .iter()
+            .map(|entry| format!("  {} {}", entry.id, entry.time_label().unwrap_or_default()))
+            .collect();
+        bail!(
+            "{} has {} entries; pick one with --id or use --all:\n{}",
+            date,
+            entries.len(),
+            ids.join("\n")
+        );
+    }
+
+    for entry in &entries {
+        storage::trash_entry(&config, entry)?;
+        let _ = search::update_index(&config, &storage::entry_path(&config, entry));
+    }
+    println!(
+        "{}",
+        format!(
+            "Moved {} entries to the trash. Restore them from View Journals with 't'.",
+            entries.len()
+        )
+        .green()
+    );
+    Ok(())
+}
+
+// Writes every entry, oldest first, as one document in the given format
+// (the configured one by default) to a file or stdout
+pub fn exe_export(format: Option<&str>, output: Option<&str>) -> Result<()> {
+    let config = Config::load()?;
+    let format = match format {
+        Some(name) => {
+            FileFormat::from_name(name).ok_or_else(|| anyhow!("Unknown file format: {}", name))?
+        }
+        None => config.file_format.clone(),
+    };
+
+    let mut entries = load_entries(&config)?;
+    entries.reverse();
+    let document = export_document(&entries, &format)?;
+
+    match output {
+        Some(path) if path != "-" => {
+            fs::write(path, document).map_err(|e| anyhow!("Failed to write {}: {}", path, e))?;
+            eprintln!(
+                "{}",
+                format!("Exported {} entries to {}", entries.len(), path).green()
+            );
+        }
+        _ => write!(io::stdout().lock(), "{}", document)?,
+    }
+    Ok(())
+}
+
+// Writing statistics of the whole journal
+pub fn exe_stats(output: OutputFormat) -> Result<()> {
+    let config = Config::load()?;
+    let entries = load_entries(&config)?;
+    let today = config.journal_time_zone()?.now().date();
+    let stats = JournalStats::new(&entries, today);
+
+    let mut out = io::stdout().lock();
+    match output {
+        OutputFormat::Json => writeln!(out, "{}", serde_json::to_string_pretty(&stats)?)?,
+        OutputFormat::Ndjson => writeln!(out, "{}", serde_json::to_string(&stats)?)?,
+        _ => print_stats(&mut out, &config, &stats)?,
+    }
+    Ok(())
+}
+
+fn print_stats(out: &mut impl Write, config: &Config, stats: &JournalStats) -> Result<()> {
+    let date_format = config.date_formatter()?;
+    let date = |day: Option<Date>| -> Result<String> {
+        day.map_or(Ok("-".to_string()), |day| date_format.format(day))
+    };
+    let streak = |streak: &Streak| -> Result<String> {
+        Ok(match streak.days {
+            0 => "none".to_string(),
+            1 => format!("1 day ({})", date(streak.start)?),
+            days => format!(
+                "{} days ({} to {})",
+                days,
+                date(streak.start)?,
+                date(streak.end)?
+            ),
+        })
+    };
+
+    writeln!(out, "Entries:          {}", stats.entries)?;
+    writeln!(out, "Days written:     {}", stats.days_written)?;
+    writeln!(out, "Words:            {}", stats.words)?;
+    writeln!(out, "Words/entry:      {:.0}", stats.average_words)?;
+    writeln!(
+        out,
+        "Words/day:        {:.0} ({:.0} a week, {:.0} a month)",
+        stats.words_per_day, stats.words_per_week, stats.words_per_month
+    )?;
+    writeln!(out, "Current streak:   {}", streak(&stats.current_streak)?)?;
+    writeln!(out, "Longest streak:   {}", streak(&stats.longest_streak)?)?;
+    if let Some(hour) = stats.most_active_hour {
+        writeln!(
+            out,
+            "Most active hour: {:02}:00 ({} entries)",
+            hour, stats.hours[hour as usize]
+        )?;
+    }
+    writeln!(out, "First day:        {}", date(stats.first_day)?)?;
+    writeln!(out, "Last day:         {}", date(stats.last_day)?)?;
+    if !stats.top_words.is_empty() {
+        let words: Vec<String> = stats
+            .top_words
+            .iter()
+            .map(|word| format!("{} ({})", word.word, word.count))
+            .collect();
+        writeln!(out, "Top words:        {}", words.join(", "))?;
+    }
+    Ok(())
+}
+
+fn load_entries(config: &Config) -> Result<Vec<JournalEntry>> {
+    load_journal_entries_with_config(config)
+        .map_err(|e| anyhow!("Failed to read the journal: {}", e))
+}
+
+// A day given on the command line; unlike new entries, it may be in the future
+fn parse_day(input: &str, config: &Config) -> Result<Date> {
+    let today = config.journal_time_zone()?.now().date();
+    utils::parse_user_date(input, today, &config.date_formatter()?)
+}
+
+// The day's entries, or only the one with `id`
+fn select_entries(
+    entries: Vec<JournalEntry>,
+    id: Option<&str>,
+    date: &str,
+) -> Result<Vec<JournalEntry>> {
+    if entries.is_empty() {
+        return Err(NoMatch(format!("No entries for {}", date)).into());
+    }
+    match id {
+        Some(id) => {
+            let entries: Vec<JournalEntry> =
+                entries.into_iter().filter(|entry| entry.id == id).collect();
+            if entries.is_empty() {
+                return Err(NoMatch(format!("No entry with id {} on {}", id, date)).into());
+            }
+            Ok(entries)
+        }
+        None => Ok(entries),
+    }
+}
+
+fn print_entry_header(out: &mut impl Write, entry: &JournalEntry) -> io::Result<()> {
+    writeln!(
+        out,
+        "{} {}  {}  {}",
+        entry.date.cyan().bold(),
+        entry.time_label().unwrap_or_else(|| "--:--".to_string()),
+        format_badge(entry).dimmed(),
+        entry.id.dimmed()
+    )
+}
+
+fn format_badge(entry: &JournalEntry) -> String {
+    entry
+        .format()
+        .map_or("?", |format| format.extension())
+        .to_string()
+}
+
+fn export_document(entries: &[JournalEntry], format: &FileFormat) -> Result<String> {
+    if *format == FileFormat::Json {
+        return storage::render_day_file(format, entries);
+    }
+
+    let mut document = String::new();
+    for entry in entries {
+        let time = entry
+            .time_label()
+            .map(|time| format!(" {}", time))
+            .unwrap_or_default();
+        let heading = format!("{}{}", entry.date, time);
+        match format {
+            FileFormat::Markdown => document.push_str(&format!("## {}\n\n", heading)),
+            _ => document.push_str(&format!(
+                "{}\n{}\n\n",
+                heading,
+                "=".repeat(heading.chars().count())
+            )),
+        }
+        document.push_str(entry.body.trim());
+        document.push_str("\n\n");
+    }
+    Ok(document)
+}
@@ -13,16 +13,54 @@ pub fn exe_help() {
     println!("{}", "Commands:".blue().bold());
     println!("  (no args)             Start interactive TUI mode (default)");
     println!("  -t,  --tui            Start interactive TUI mode explicitly");
-    println!("  -aj, --add-journal    Add a journal entry (command line mode)");
+    println!("  add [TEXT...]         Add an entry from the arguments, piped stdin or --file PATH");
+    println!("                        (--date DATE, --prompt NAME, --raw to skip the LLM,");
+    println!("                        --mood 1-5, --energy 1-5)");
+    println!("  list                  List entries, newest first (--from DATE, --to DATE, -n N,");
+    println!("                        --tag TAG, --any-tag)");
+    println!("  show DATE             Print the entries of a day (--id ID)");
+    println!("  search QUERY          Search entries (-n N)");
+    println!("                        list, show and search take --json, --ndjson or --csv");
+    println!(
+        "  edit [DATE]           Write an entry in your editor, or edit the day file for DATE"
+    );
+    println!("  delete DATE           Move a day's entry to the trash (--id ID or --all)");
+    println!("  export                Print every entry (--format md|txt|json, -o PATH)");
+    println!("  stats                 Show counts, streaks and top words (--json, --ndjson)");
+    println!("  config get [KEY]      Print a setting (e.g. llm.model) or the whole config");
+    println!("  config set KEY VALUE  Change a setting");
+    println!("  migrate-format <FORMAT> [--from <FORMAT>]");
+    println!(
+        "                        Convert day files to md, txt or json and make it the default"
+    );
+    println!("  process-pending       Format entries queued while the LLM was unavailable");
+    println!("  encrypt               Encrypt the journal in place with a new passphrase");
+    println!("  decrypt               Decrypt the journal in place");
+    println!("  change-passphrase     Re-encrypt the journal under a new passphrase");
+    println!("  backup                Archive the journal directory (-o PATH)");
+    println!("  restore [ARCHIVE]     Restore an archive, by default the newest backup");
+    println!("  -aj, --add-journal    Add a journal entry interactively");
+    println!("  -e,  --editor [DATE]  Same as edit");
     println!("  -h,  --help           Show this help message");
     println!();
+    println!("  DATE is the configured date format, YYYY-MM-DD, today, yesterday, \"3 days ago\"");
+    println!("  or a weekday like \"last friday\". --date files a new entry under that day.");
+    println!("  An encrypted journal asks for its passphrase, or reads RUSTY_DIARY_PASSPHRASE.");
+    println!();
+    println!("{}", "Exit status:".blue().bold());
+    println!("  0  success");
+    println!("  1  the command failed");
+    println!("  2  invalid arguments");
+    println!("  3  nothing matched (show, search, delete)");
+    println!();
     println!("{}", "Features:".blue().bold());
     println!("  • Beautiful terminal user interface (TUI)");
     println!("  • Multiple file formats: Markdown, Plain Text, JSON");
     println!("  • Configurable journal directory");
-    println!("  • LLM processing with Ollama for markdown formatting");
+    println!("  • LLM processing with Ollama or OpenAI-compatible servers for markdown formatting");
     println!("  • Date-based file organization");
     println!("  • Browse and view existing journal entries");
+    println!("  • #tags and @mentions, with filtering in the TUI and with --tag");
     println!();
     println!("{}", "TUI Navigation:".blue().bold());
     println!("  • Use ↑↓ arrow keys to navigate menus");
@@ -30,7 +68,15 @@ pub fn exe_help() {
     println!("  • Press 'q' or Esc to go back/quit");
     println!("  • Press 'e' to edit in Add Journal screen");
     println!("  • Press 's' to save journal entries");
+    println!("  • Press 'E' to write or edit entries in your external editor");
     println!("  • Press 'r' to refresh journal list");
+    println!("  • Press 'e', 'd' or 'm' in the journal list to edit, delete or re-date an entry");
+    println!("  • Press 't' in the journal list to restore deleted entries from the trash");
+    println!("  • Press 'T' in the journal list to filter by #tags and @mentions");
+    println!("  • Press '/' to search journal entries, 'n'/'N' to jump between matches");
+    println!("  • Open the Calendar to browse days by month or as a year heatmap");
+    println!("  • Press 'm' in Add Journal to rate mood and energy; see them in Insights");
+    println!("  • Open Statistics for streaks, words per month and your most used words");
     println!();
     println!("{}", "Configuration:".blue().bold());
     println!("  Configuration file: ~/.config/rusty_diary/config.toml");
@@ -38,8 +84,14 @@ pub fn exe_help() {
     println!("  Use the Settings screen in TUI to customize your setup");
     println!();
     println!("{}", "Examples:".blue().bold());
-    println!("  rusty_diary                    # Start TUI mode");
-    println!("  rusty_diary --tui              # Start TUI mode explicitly");
-    println!("  rusty_diary -aj                # Quick add journal entry");
-    println!("  rusty_diary --help             # Show this help");
+    println!("  rusty_diary                          # Start TUI mode");
+    println!("  rusty_diary add \"Went for a run\"     # Add an entry");
+    println!("  echo \"...\" | rusty_diary add         # Add an entry from a pipe");
+    println!("  rusty_diary add --mood 4 \"Good day\"  # Add an entry with a mood");
+    println!("  rusty_diary list -n 10               # Show the latest entries");
+    println!("  rusty_diary show yesterday           # Print yesterday's entries");
+    println!("  rusty_diary search \"rust -python\"    # Search entries");
+    println!("  rusty_diary list --json | jq .       # List entries as JSON");
+    println!("  rusty_diary config set llm.model m   # Change a setting");
+    println!("  rusty_diary --help                   # Show this help");
 }
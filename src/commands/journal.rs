@@ -1,351 +1,558 @@
-use crate::config::{Config, FileFormat};
-use crate::utils::{self, get_string};
+use crate::config::{Config, FileFormat, LlmFallback};
+use crate::llm;
+use crate::search;
+use crate::storage::{self, JournalEntry};
+use crate::utils::{self, format_offset, format_timestamp, get_string};
 use colored::Colorize;
-use std::fs::OpenOptions;
-use std::io::Write;
-use std::process::{Command, Stdio};
+use std::fs;
+use std::io::{self, IsTerminal, Read};
+use std::ops::ControlFlow;
+use std::path::Path;
+use time::Date;
+
+// Adds an entry for today, or for the day given with --date
+pub fn exe_add_journal(date: Option<&str>) -> anyhow::Result<()> {
+    let config = Config::load().map_err(|e| anyhow::anyhow!("Failed to load config: {}", e))?;
+    let (day, date) =
+        entry_day_and_date(date, &config).map_err(|e| anyhow::anyhow!("Invalid date: {}", e))?;
 
-pub fn exe_add_journal() {
-    let config = match Config::load() {
-        Ok(config) => config,
-        Err(e) => {
-            eprintln!("{} {}", "Failed to load config:".red(), e);
-            return;
+    let file_path = config.get_journal_file_path(&date);
+    let filename = file_path.file_name().unwrap().to_string_lossy();
+
+    // Existing entries for the day are kept when appending and dropped when overwriting
+    let mut entries = Vec::new();
+    let mut appending = false;
+    if file_path.exists() {
+        println!("{} {}", "A journal file already exists:".yellow(), filename);
+        let choice = get_string("Do you want to append to an existing file? (y/n): ");
+        if matches!(choice.as_str(), "y" | "Y") {
+            entries = read_day_entries(&file_path, &date, &config)
+                .map_err(|e| anyhow::anyhow!("Failed to read existing entries: {}", e))?;
+            appending = true;
         }
+    }
+
+    print!("{esc}[2J{esc}[1;1H", esc = 27 as char);
+    let user_entry = if file_path.exists() && !appending {
+        utils::get_string(
+            format!(
+                "{} You are overwriting the existing file!\nEnter your journal entry for {}: ",
+                "WARNING:".yellow(),
+                filename
+            )
+            .as_str(),
+        )
+    } else {
+        utils::get_string(format!("Enter your journal entry for {}: ", filename).as_str())
     };
 
-    let utc_date_time = time::UtcDateTime::now();
-    let date = format!(
-        "{}-{:02}-{:02}",
-        utc_date_time.year(),
-        utc_date_time.month() as u8,
-        utc_date_time.day()
-    );
+    let mut entry = new_raw_entry(&user_entry, Some(day), &config)
+        .map_err(|e| anyhow::anyhow!("Failed to create entry: {}", e))?;
 
-    let file_path = config.get_journal_file_path(&date);
-    let filename = file_path.file_name().unwrap().to_string_lossy();
+    if config.mood_check_in {
+        entry.metadata.mood = ask_rating("Mood", &storage::MOOD_EMOJI);
+        entry.metadata.energy = ask_rating("Energy", &storage::ENERGY_EMOJI);
+    }
 
-    // Ensure directory exists
-    if let Some(parent) = file_path.parent() {
-        if let Err(e) = std::fs::create_dir_all(parent) {
-            eprintln!("{} {}", "Failed to create directory:".red(), e);
-            return;
-        }
+    format_in_terminal(&mut entry, &config)?;
+    entry.update_tags(None);
+    entries.push(entry);
+
+    storage::write_day_file(&config, &file_path, &config.file_format, &entries)
+        .map_err(|e| anyhow::anyhow!("Failed to write to file: {}", e))?;
+    if appending {
+        println!("{}", "Journal entry appended!".green());
+    } else {
+        println!("{}", "Journal entry saved!".green());
     }
 
-    let mut file = match OpenOptions::new()
-        .create_new(true)
-        .write(true)
-        .open(&file_path)
-    {
-        Ok(file) => file,
-        Err(e) => {
-            eprintln!("{} {}", "Failed to open file:".red(), e);
-            let choice = get_string("Do you want to append to an existing file? (y/n): ");
-            match choice.as_str() {
-                "y" | "Y" => append_to_existing_file(&file_path, &config),
-                _ => write_to_existing_file(&file_path, &config),
+    if let Err(e) = search::update_index(&config, &file_path) {
+        eprintln!("{} {}", "Failed to update search index:".yellow(), e);
+    }
+    Ok(())
+}
+
+// Adds an entry without asking anything: the text comes from the arguments, a
+// file (`-` for stdin) or stdin until end of input. Markdown entries are
+// formatted by the LLM unless `raw` is set.
+pub fn exe_add(
+    text: Option<String>,
+    file: Option<&str>,
+    date: Option<&str>,
+    prompt: Option<&str>,
+    raw: bool,
+    mood: Option<&str>,
+    energy: Option<&str>,
+) -> anyhow::Result<()> {
+    let config = Config::load()?;
+    let day = date
+        .map(|date| parse_entry_date(date, &config))
+        .transpose()?;
+    let mood = mood
+        .map(|mood| parse_rating_option("--mood", mood, &storage::MOOD_EMOJI))
+        .transpose()?;
+    let energy = energy
+        .map(|energy| parse_rating_option("--energy", energy, &storage::ENERGY_EMOJI))
+        .transpose()?;
+
+    let text = match (text, file) {
+        (Some(_), Some(_)) => anyhow::bail!("Give the entry as arguments or with --file, not both"),
+        (Some(text), None) => text,
+        (None, Some("-")) => read_stdin()?,
+        (None, Some(path)) => fs::read_to_string(path)
+            .map_err(|e| anyhow::anyhow!("Failed to read {}: {}", path, e))?,
+        (None, None) => {
+            if io::stdin().is_terminal() {
+                eprintln!(
+                    "{}",
+                    "Enter your journal entry, then press Ctrl+D on an empty line:".cyan()
+                );
             }
-            return;
+            read_stdin()?
         }
     };
+    if text.trim().is_empty() {
+        anyhow::bail!("The entry is empty, nothing was saved");
+    }
 
-    print!("{esc}[2J{esc}[1;1H", esc = 27 as char);
-    let user_entry =
-        utils::get_string(format!("Enter your journal entry for {}: ", filename).as_str());
-
-    let processed_entry = match config.file_format {
-        FileFormat::Markdown => {
-            println!("{}", "Processing with the LLM...".yellow());
-            match process_with_ollama(&user_entry) {
-                Ok(content) => content,
-                Err(e) => {
-                    eprintln!("{} {}", "Failed to process entry with Ollama:".red(), e);
-                    return;
+    let mut entry =
+        new_raw_entry(text.trim(), day, &config).map_err(|e| anyhow::anyhow!("{}", e))?;
+    entry.metadata.mood = mood.map(|mood| mood.to_string());
+    entry.metadata.energy = energy.map(|energy| energy.to_string());
+
+    if !raw && config.file_format == FileFormat::Markdown {
+        // Progress goes to stderr so scripts can read the saved path from stdout
+        eprintln!("{}", "Processing with the LLM...".yellow());
+        let (prompt, text) = build_prompt(&entry, prompt, &config)?;
+        match process_with_llm(&text, &config) {
+            Ok((output, model)) => apply_llm_output(&mut entry, &output, &prompt, &model, &config)?,
+            Err(e) => {
+                eprintln!("{} {}", "Failed to process entry with the LLM:".yellow(), e);
+                llm_fallback(&mut entry, &prompt, e, &config)
+                    .map_err(|e| anyhow::anyhow!("The entry was not saved: {}", e))?;
+                if entry.metadata.pending_formatting {
+                    eprintln!(
+                        "{}",
+                        "Saving the entry as written; run --process-pending to format it later."
+                            .yellow()
+                    );
                 }
             }
         }
-        FileFormat::PlainText => user_entry,
-        FileFormat::Json => {
-            match serde_json::to_string_pretty(&serde_json::json!({
-                "date": date,
-                "content": user_entry,
-                "created_at": time::UtcDateTime::now().to_string()
-            })) {
-                Ok(json) => json,
-                Err(e) => {
-                    eprintln!("{} {}", "Failed to create JSON:".red(), e);
-                    return;
-                }
-            }
-        }
-    };
-
-    match write!(file, "{}", processed_entry) {
-        Ok(_) => println!("{}", "Journal entry saved!".green()),
-        Err(e) => eprintln!("{} {}", "Failed to write to file:".red(), e),
     }
+
+    let path = storage::insert_entry(&config, entry)?;
+    let _ = search::update_index(&config, &path);
+    println!("{}", path.display());
+    Ok(())
 }
 
-fn append_to_existing_file(file_path: &std::path::Path, config: &Config) {
-    let mut file = match OpenOptions::new().append(true).open(file_path) {
-        Ok(file) => file,
-        Err(e) => {
-            eprintln!("{} {}", "Failed to open file:".red(), e);
-            return;
-        }
-    };
+fn parse_rating_option(name: &str, value: &str, scale: &[&str; 5]) -> anyhow::Result<u8> {
+    storage::parse_rating(value, scale).ok_or_else(|| {
+        anyhow::anyhow!(
+            "{} takes 1 to 5 or one of {}, got '{}'",
+            name,
+            scale.join(" "),
+            value
+        )
+    })
+}
 
-    let filename = file_path.file_name().unwrap().to_string_lossy();
-    print!("{esc}[2J{esc}[1;1H", esc = 27 as char);
-    let user_entry =
-        utils::get_string(format!("Enter your journal entry for {}: ", filename).as_str());
-
-    let processed_entry = match config.file_format {
-        FileFormat::Markdown => {
-            println!("{}", "Processing with the LLM...".yellow());
-            match process_with_ollama(&user_entry) {
-                Ok(content) => content,
-                Err(e) => {
-                    eprintln!("{} {}", "Failed to process entry with Ollama:".red(), e);
-                    return;
-                }
+// Asks for a rating when `mood_check_in` is set; anything but 1-5 or an emoji
+// of the scale skips it
+fn ask_rating(question: &str, scale: &[&str; 5]) -> Option<String> {
+    let answer = get_string(&format!(
+        "{} (1-5, {}; Enter to skip): ",
+        question,
+        scale.join(" ")
+    ));
+    storage::parse_rating(&answer, scale).map(|rating| rating.to_string())
+}
+
+fn read_stdin() -> anyhow::Result<String> {
+    let mut text = String::new();
+    io::stdin()
+        .read_to_string(&mut text)
+        .map_err(|e| anyhow::anyhow!("Failed to read stdin: {}", e))?;
+    Ok(text)
+}
+
+// Runs a Markdown entry through the LLM and lets the user choose between the
+// formatted and raw text. Fails when the entry shouldn't be saved.
+fn format_in_terminal(entry: &mut JournalEntry, config: &Config) -> anyhow::Result<()> {
+    if config.file_format != FileFormat::Markdown {
+        return Ok(());
+    }
+
+    println!("{}", "Processing with the LLM...".yellow());
+    let (prompt, text) = build_prompt(entry, None, config)
+        .map_err(|e| anyhow::anyhow!("Failed to prepare the prompt: {}", e))?;
+
+    match process_with_llm(&text, config) {
+        Ok((output, model)) => {
+            println!("\n{}\n{}\n", "Formatted entry:".cyan(), output);
+            let choice = get_string("Save the formatted entry? (y = formatted, n = as written): ");
+            if matches!(choice.as_str(), "y" | "Y") {
+                apply_llm_output(entry, &output, &prompt, &model, config)
+                    .map_err(|e| anyhow::anyhow!("Failed to create entry: {}", e))?;
             }
         }
-        FileFormat::PlainText => user_entry,
-        FileFormat::Json => {
-            let utc_date_time = time::UtcDateTime::now();
-            let date = format!(
-                "{}-{:02}-{:02}",
-                utc_date_time.year(),
-                utc_date_time.month() as u8,
-                utc_date_time.day()
-            );
-            match serde_json::to_string_pretty(&serde_json::json!({
-                "date": date,
-                "content": user_entry,
-                "created_at": time::UtcDateTime::now().to_string()
-            })) {
-                Ok(json) => json,
-                Err(e) => {
-                    eprintln!("{} {}", "Failed to create JSON:".red(), e);
-                    return;
-                }
+        Err(e) => {
+            eprintln!("{} {}", "Failed to process entry with the LLM:".red(), e);
+            llm_fallback(entry, &prompt, e, config)
+                .map_err(|e| anyhow::anyhow!("The entry was not saved: {}", e))?;
+            if entry.metadata.pending_formatting {
+                println!(
+                    "{}",
+                    "Saving the entry as written; run --process-pending to format it later."
+                        .yellow()
+                );
+            } else {
+                println!("{}", "Saving the entry as written.".yellow());
             }
         }
+    }
+    Ok(())
+}
+
+// Composes a new entry (for `entry_date` when given) in the external editor,
+// or opens the existing day file for `date` in it
+pub fn exe_editor(date: Option<&str>, entry_date: Option<&str>) -> anyhow::Result<()> {
+    let config = Config::load().map_err(|e| anyhow::anyhow!("Failed to load config: {}", e))?;
+
+    let Some(date) = date else {
+        let day = entry_date
+            .map(|date| parse_entry_date(date, &config))
+            .transpose()
+            .map_err(|e| anyhow::anyhow!("Invalid date: {}", e))?;
+
+        let text = utils::edit_text(
+            config.editor_command.as_deref(),
+            "",
+            config.file_format.extension(),
+        )
+        .map_err(|e| anyhow::anyhow!("Failed to edit entry: {}", e))?;
+        if text.trim().is_empty() {
+            println!("{}", "The entry is empty, nothing was saved.".yellow());
+            return Ok(());
+        }
+
+        let mut entry = new_raw_entry(&text, day, &config)
+            .map_err(|e| anyhow::anyhow!("Failed to create entry: {}", e))?;
+        format_in_terminal(&mut entry, &config)?;
+        save_entry(entry, &config)
+            .map_err(|e| anyhow::anyhow!("Failed to write to file: {}", e))?;
+        println!("{}", "Journal entry saved!".green());
+        return Ok(());
     };
 
-    match write!(file, "\n{}", processed_entry) {
-        Ok(_) => println!("{}", "Journal entry appended!".green()),
-        Err(e) => eprintln!("{} {}", "Failed to write to file:".red(), e),
+    let (_, date) = entry_day_and_date(Some(date), &config)
+        .map_err(|e| anyhow::anyhow!("Invalid date: {}", e))?;
+    let file_path = config.get_journal_file_path(&date);
+    if !file_path.exists() {
+        anyhow::bail!("No journal file for {}", file_path.display());
     }
+
+    edit_day_file(&config, &file_path)
+        .map_err(|e| anyhow::anyhow!("Failed to edit entry: {}", e))?;
+    let _ = search::update_index(&config, &file_path);
+    println!("{}", "Journal file saved!".green());
+    Ok(())
 }
 
-fn write_to_existing_file(file_path: &std::path::Path, config: &Config) {
-    let mut file = match OpenOptions::new()
-        .create(true)
-        .write(true)
-        .truncate(true)
-        .open(file_path)
-    {
-        Ok(file) => file,
-        Err(e) => {
-            println!("{} {}", "Error:".red(), e);
-            return;
+// Opens a day file in the external editor. An encrypted file is edited as a
+// decrypted temporary copy, which is deleted afterwards.
+pub fn edit_day_file(config: &Config, path: &Path) -> anyhow::Result<()> {
+    storage::snapshot_file(config, path)?;
+    if !storage::is_encrypted_path(path) {
+        return utils::open_in_editor(config.editor_command.as_deref(), path);
+    }
+
+    let text = storage::read_file(path)?;
+    let extension = storage::parse_file_name(path).map_or("txt", |(_, format)| format.extension());
+    let edited = utils::edit_text(config.editor_command.as_deref(), &text, extension)?;
+    if edited != text.trim_end_matches(['\n', '\r']) {
+        storage::write_file(path, &format!("{}\n", edited))?;
+    }
+    Ok(())
+}
+
+// Sends a rendered prompt to the configured LLM, returning the formatted text
+// and the model used
+fn process_with_llm(prompt: &str, config: &Config) -> anyhow::Result<(String, String)> {
+    let backend = llm::backend_from_config(&config.llm);
+    let output = backend.generate(prompt, &mut |_| ControlFlow::Continue(()))?;
+    Ok((output.trim().to_string(), backend.model().to_string()))
+}
+
+// Renders the named prompt template (the configured default when `None`) for
+// an entry, returning the template name and the prompt text
+pub fn build_prompt(
+    entry: &JournalEntry,
+    prompt: Option<&str>,
+    config: &Config,
+) -> anyhow::Result<(String, String)> {
+    let template = llm::find_prompt(config, prompt.unwrap_or(&config.llm.default_prompt))?;
+    let previous_entry = previous_entry(entry, config);
+    // The text's tags may not have been extracted into the metadata yet
+    let mut tags = entry.metadata.tags.clone();
+    tags.extend(storage::extract_tags(&entry.body).tags);
+    tags.sort();
+    tags.dedup();
+    let prompt = template.render(&llm::PromptContext {
+        entry: &entry.body,
+        date: &entry.date,
+        previous_entry: previous_entry.as_ref().map(|entry| entry.body.as_str()),
+        tags: &tags,
+    });
+    Ok((template.name, prompt))
+}
+
+// Replaces an entry's text with the LLM's output and records how it was made
+pub fn apply_llm_output(
+    entry: &mut JournalEntry,
+    output: &str,
+    prompt: &str,
+    model: &str,
+    config: &Config,
+) -> anyhow::Result<()> {
+    // Tags written in the raw text are kept even if the LLM drops them
+    entry.update_tags(None);
+    entry.body = output.trim().to_string();
+    entry.metadata.prompt = Some(prompt.to_string());
+    entry.metadata.llm_model = Some(model.to_string());
+    entry.metadata.processed_at = Some(format_timestamp(config.journal_time_zone()?.now()));
+    Ok(())
+}
+
+// Applies the configured fallback to an entry the LLM couldn't format: it is
+// kept as written, queued for formatting, or the error is passed back
+pub fn llm_fallback(
+    entry: &mut JournalEntry,
+    prompt: &str,
+    error: anyhow::Error,
+    config: &Config,
+) -> anyhow::Result<()> {
+    match config.llm.fallback {
+        LlmFallback::SaveRaw => Ok(()),
+        LlmFallback::Queue => {
+            entry.metadata.pending_formatting = true;
+            entry.metadata.prompt = Some(prompt.to_string());
+            Ok(())
         }
-    };
+        LlmFallback::Fail => Err(error),
+    }
+}
 
-    let filename = file_path.file_name().unwrap().to_string_lossy();
-    print!("{esc}[2J{esc}[1;1H", esc = 27 as char);
-    let user_entry = utils::get_string(
-        format!(
-            "{} You are overwriting the existing file!\nEnter your journal entry for {}: ",
-            "WARNING:".yellow(),
-            filename
-        )
-        .as_str(),
-    );
-
-    let processed_entry = match config.file_format {
-        FileFormat::Markdown => {
-            println!("{}", "Processing with the LLM...".yellow());
-            match process_with_ollama(&user_entry) {
-                Ok(content) => content,
-                Err(e) => {
-                    println!("{} {}", "Error processing with LLM:".red(), e);
-                    return;
-                }
+// The entry written just before the given one, used as context for prompts
+fn previous_entry(entry: &JournalEntry, config: &Config) -> Option<JournalEntry> {
+    let mut day_files = storage::list_day_files(config).ok()?;
+    day_files.retain(|day_file| day_file.day <= entry.day);
+    day_files.sort_by_key(|day_file| std::cmp::Reverse(day_file.day));
+
+    let position = (entry.day, entry.timestamp());
+    day_files.iter().find_map(|day_file| {
+        day_file
+            .read()
+            .ok()?
+            .into_iter()
+            .filter(|other| other.id != entry.id && (other.day, other.timestamp()) < position)
+            .max_by_key(|other| other.timestamp())
+    })
+}
+
+#[derive(Default)]
+pub struct PendingReport {
+    pub formatted: usize,
+    pub remaining: usize,
+    pub error: Option<String>,
+}
+
+// Runs entries queued while the LLM was unavailable through it, oldest first.
+// Processing stops at the first failure; the rest stay queued.
+pub fn process_pending(config: &Config) -> anyhow::Result<PendingReport> {
+    let mut report = PendingReport::default();
+    let backend = llm::backend_from_config(&config.llm);
+
+    let mut day_files = storage::list_day_files(config)?;
+    day_files.sort_by_key(|day_file| day_file.day);
+
+    for day_file in day_files {
+        let mut entries = day_file.read()?;
+        let mut changed = false;
+
+        for entry in entries
+            .iter_mut()
+            .filter(|entry| entry.metadata.pending_formatting)
+        {
+            if report.error.is_some() {
+                report.remaining += 1;
+                continue;
             }
-        }
-        FileFormat::PlainText => user_entry,
-        FileFormat::Json => {
-            let utc_date_time = time::UtcDateTime::now();
-            let date = format!(
-                "{}-{:02}-{:02}",
-                utc_date_time.year(),
-                utc_date_time.month() as u8,
-                utc_date_time.day()
+
+            let result = build_prompt(entry, entry.metadata.prompt.as_deref(), config).and_then(
+                |(prompt, text)| {
+                    let output = backend.generate(&text, &mut |_| ControlFlow::Continue(()))?;
+                    Ok((prompt, output))
+                },
             );
-            match serde_json::to_string_pretty(&serde_json::json!({
-                "date": date,
-                "content": user_entry,
-                "created_at": time::UtcDateTime::now().to_string()
-            })) {
-                Ok(json) => json,
+
+            match result {
+                Ok((prompt, output)) => {
+                    apply_llm_output(entry, &output, &prompt, backend.model(), config)?;
+                    entry.metadata.pending_formatting = false;
+                    report.formatted += 1;
+                    changed = true;
+                }
                 Err(e) => {
-                    eprintln!("{} {}", "Failed to create JSON:".red(), e);
-                    return;
+                    report.error = Some(e.to_string());
+                    report.remaining += 1;
                 }
             }
         }
-    };
 
-    match write!(file, "{}", processed_entry) {
-        Ok(_) => println!("{}", "Journal entry saved!".green()),
-        Err(e) => eprintln!("{} {}", "Failed to write to file:".red(), e),
+        if changed {
+            storage::write_day_file(config, &day_file.path, &day_file.format, &entries)?;
+            let _ = search::update_index(config, &day_file.path);
+        }
     }
-}
 
-fn process_with_ollama(entry: &str) -> Result<String, std::io::Error> {
-    let model_prompt = "Please just structure and organize the following journal entry into a beautiful and organized markdown format. You should only respond with the markdown result, do not add any additional details, you have to deal with the entry.";
-    let mut child = Command::new("ollama")
-        .args(["run", "llama3.1:8b", model_prompt])
-        .stdin(Stdio::piped())
-        .stdout(Stdio::piped())
-        .spawn()?;
-
-    if let Some(mut stdin) = child.stdin.take() {
-        stdin.write_all(entry.as_bytes())?;
-    }
+    Ok(report)
+}
 
-    let output = child.wait_with_output()?;
+pub fn exe_process_pending() -> anyhow::Result<()> {
+    let config = Config::load().map_err(|e| anyhow::anyhow!("Failed to load config: {}", e))?;
 
-    if output.status.success() {
-        Ok(String::from_utf8_lossy(&output.stdout).to_string())
+    println!("{}", "Formatting pending entries with the LLM...".yellow());
+    let report = process_pending(&config)
+        .map_err(|e| anyhow::anyhow!("Failed to process pending entries: {}", e))?;
+    if report.formatted == 0 && report.remaining == 0 {
+        println!("{}", "No entries are waiting for formatting.".green());
     } else {
-        let err = String::from_utf8_lossy(&output.stderr);
-        eprintln!("{} {}", "Ollama Error:".red(), err);
-        Err(std::io::Error::other("Ollama execution failed"))
+        println!(
+            "{}",
+            format!("Formatted {} pending entries.", report.formatted).green()
+        );
+    }
+    if let Some(error) = report.error {
+        anyhow::bail!(
+            "Failed to reach the LLM: {} ({} entries still pending)",
+            error,
+            report.remaining
+        );
     }
+    Ok(())
 }
 
 pub fn save_journal_entry_with_config(
     entry: &str,
+    prompt: Option<&str>,
     config: &Config,
 ) -> Result<(), Box<dyn std::error::Error>> {
-    let utc_date_time = time::UtcDateTime::now();
-    let date = format!(
-        "{}-{:02}-{:02}",
-        utc_date_time.year(),
-        utc_date_time.month() as u8,
-        utc_date_time.day()
-    );
-
-    let file_path = config.get_journal_file_path(&date);
-
-    // Ensure the journal directory exists
-    if let Some(parent) = file_path.parent() {
-        std::fs::create_dir_all(parent)?;
-    }
-
-    let processed_entry = match config.file_format {
-        FileFormat::Markdown => process_with_ollama(entry)?,
-        FileFormat::PlainText => entry.to_string(),
-        FileFormat::Json => serde_json::to_string_pretty(&serde_json::json!({
-            "date": date,
-            "content": entry,
-            "processed_at": time::UtcDateTime::now().to_string()
-        }))?,
-    };
+    save_entry(new_entry(entry, prompt, config)?, config)
+}
 
-    let mut file = match std::fs::OpenOptions::new()
-        .create_new(true)
-        .write(true)
-        .open(&file_path)
-    {
-        Ok(file) => file,
-        Err(_) => {
-            // If file exists, append to it with a separator
-            let mut append_file = std::fs::OpenOptions::new().append(true).open(&file_path)?;
-            writeln!(append_file, "\n---\n")?;
-            append_file
-        }
-    };
+// Adds a finished entry to its day file
+pub fn save_entry(entry: JournalEntry, config: &Config) -> Result<(), Box<dyn std::error::Error>> {
+    let file_path = storage::insert_entry(config, entry)?;
 
-    writeln!(file, "{}", processed_entry)?;
+    // A stale index is rebuilt from file modification times on the next search
+    let _ = search::update_index(config, &file_path);
     Ok(())
 }
 
 pub fn load_journal_entries_with_config(
     config: &Config,
-) -> Result<Vec<crate::app::JournalEntry>, Box<dyn std::error::Error>> {
+) -> Result<Vec<JournalEntry>, Box<dyn std::error::Error>> {
     let mut entries = Vec::new();
 
-    if !config.journal_directory.exists() {
-        return Ok(entries);
+    for day_file in storage::list_day_files(config)? {
+        entries.extend(day_file.read()?);
     }
 
-    let extension = config.file_format.extension();
-
-    for entry in std::fs::read_dir(&config.journal_directory)? {
-        let entry = entry?;
-        let path = entry.path();
-
-        if let Some(file_extension) = path.extension() {
-            if file_extension == extension {
-                if let Some(filename_str) = path.file_name().and_then(|n| n.to_str()) {
-                    if let Some(stem) = path.file_stem().and_then(|s| s.to_str()) {
-                        // Check if filename matches date format (YYYY-MM-DD)
-                        if stem.len() >= 10
-                            && stem.chars().nth(4) == Some('-')
-                            && stem.chars().nth(7) == Some('-')
-                        {
-                            let content = std::fs::read_to_string(&path)?;
-
-                            // Process content based on file format
-                            let processed_content = match config.file_format {
-                                FileFormat::Json => {
-                                    // For JSON files, try to extract the content field
-                                    if let Ok(json_value) =
-                                        serde_json::from_str::<serde_json::Value>(&content)
-                                    {
-                                        if let Some(content_field) = json_value.get("content") {
-                                            content_field.as_str().unwrap_or(&content).to_string()
-                                        } else {
-                                            content
-                                        }
-                                    } else {
-                                        content
-                                    }
-                                }
-                                _ => content,
-                            };
-
-                            entries.push(crate::app::JournalEntry {
-                                date: stem.to_string(),
-                                content: processed_content,
-                                filename: filename_str.to_string(),
-                            });
-                        }
-                    }
-                }
-            }
+    // Sort entries by date and time (newest first)
+    entries.sort_by_key(|entry| std::cmp::Reverse((entry.day, entry.timestamp())));
+
+    Ok(entries)
+}
+
+fn read_day_entries(
+    file_path: &std::path::Path,
+    date: &str,
+    config: &Config,
+) -> anyhow::Result<Vec<JournalEntry>> {
+    let day = config
+        .date_formatter()?
+        .parse(date)
+        .ok_or_else(|| anyhow::anyhow!("'{}' doesn't match the date format", date))?;
+    storage::read_day_file(file_path, &config.file_format, date, day)
+}
+
+// Builds a new entry for today, running Markdown entries through the LLM
+fn new_entry(
+    text: &str,
+    prompt: Option<&str>,
+    config: &Config,
+) -> Result<JournalEntry, Box<dyn std::error::Error>> {
+    let mut entry = new_raw_entry(text, None, config)?;
+    if config.file_format == FileFormat::Markdown {
+        let (prompt, text) = build_prompt(&entry, prompt, config)?;
+        match process_with_llm(&text, config) {
+            Ok((output, model)) => apply_llm_output(&mut entry, &output, &prompt, &model, config)?,
+            Err(e) => llm_fallback(&mut entry, &prompt, e, config)?,
         }
     }
+    Ok(entry)
+}
 
-    // Sort entries by date (newest first)
-    entries.sort_by(|a, b| b.date.cmp(&a.date));
+// A new entry as written, filed under `day` or today when it's None. The
+// timestamp is the time of writing in the configured time zone, recorded with
+// its offset and name so the writer's local time can be recovered later.
+pub fn new_raw_entry(
+    text: &str,
+    day: Option<Date>,
+    config: &Config,
+) -> Result<JournalEntry, Box<dyn std::error::Error>> {
+    let time_zone = config.journal_time_zone()?;
+    let now = time_zone.now();
+    let day = day.unwrap_or(now.date());
+    let date = config.date_formatter()?.format(day)?;
+
+    let mut entry = JournalEntry::new(&date, day, now, text);
+    entry.metadata.utc_offset = Some(format_offset(now.offset()));
+    entry.metadata.time_zone = Some(time_zone.name());
+    Ok(entry)
+}
 
-    Ok(entries)
+// Day an entry is filed under, from a date typed by the user (see
+// `utils::parse_user_date`). Entries can't be written for future days.
+pub fn parse_entry_date(input: &str, config: &Config) -> anyhow::Result<Date> {
+    let today = config.journal_time_zone()?.now().date();
+    let day = utils::parse_user_date(input, today, &config.date_formatter()?)?;
+    if day > today {
+        return Err(anyhow::anyhow!("{} is in the future", input.trim()));
+    }
+    Ok(day)
+}
+
+// The day for an optional user-typed date (today when None) and its file name stem
+fn entry_day_and_date(input: Option<&str>, config: &Config) -> anyhow::Result<(Date, String)> {
+    let day = match input {
+        Some(input) => parse_entry_date(input, config)?,
+        None => config.journal_time_zone()?.now().date(),
+    };
+    Ok((day, config.date_formatter()?.format(day)?))
 }
 
 // Keep the original functions for backward compatibility
+#[allow(dead_code)]
 pub fn save_journal_entry(entry: &str) -> Result<(), Box<dyn std::error::Error>> {
     let config = Config::load()?;
-    save_journal_entry_with_config(entry, &config)
+    save_journal_entry_with_config(entry, None, &config)
 }
 
-pub fn load_journal_entries() -> Result<Vec<crate::app::JournalEntry>, Box<dyn std::error::Error>> {
+#[allow(dead_code)]
+pub fn load_journal_entries() -> Result<Vec<JournalEntry>, Box<dyn std::error::Error>> {
     let config = Config::load()?;
     load_journal_entries_with_config(&config)
 }
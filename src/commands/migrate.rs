@@ -0,0 +1,67 @@
+use crate::config::{Config, FileFormat};
+use crate::{search, storage};
+use colored::Colorize;
+
+// Converts every day file (or only those in `from`) to another format and
+// makes it the configured one
+pub fn exe_migrate_format(to: Option<&str>, from: Option<&str>) -> anyhow::Result<()> {
+    let Some(to) = to.and_then(FileFormat::from_name) else {
+        anyhow::bail!("Usage: rusty_diary migrate-format <md|txt|json> [--from <md|txt|json>]");
+    };
+    let from = from
+        .map(|name| {
+            FileFormat::from_name(name)
+                .ok_or_else(|| anyhow::anyhow!("Unknown file format: {}", name))
+        })
+        .transpose()?;
+
+    let mut config = Config::load().map_err(|e| anyhow::anyhow!("Failed to load config: {}", e))?;
+    let day_files = storage::list_day_files(&config)
+        .map_err(|e| anyhow::anyhow!("Failed to read the journal: {}", e))?;
+
+    let mut converted = 0;
+    let mut failed = 0;
+    for day_file in day_files.iter().filter(|day_file| {
+        day_file.format != to && from.as_ref().is_none_or(|from| day_file.format == *from)
+    }) {
+        match storage::convert_day_file(&config, day_file, &to) {
+            Ok(path) => {
+                let _ = search::update_index(&config, &path);
+                println!("  {} → {}", day_file.path.display(), path.display());
+                converted += 1;
+            }
+            Err(e) => {
+                eprintln!(
+                    "{} {}: {}",
+                    "Failed to convert".red(),
+                    day_file.path.display(),
+                    e
+                );
+                failed += 1;
+            }
+        }
+    }
+
+    if failed > 0 {
+        anyhow::bail!(
+            "Converted {} day files, {} failed and were left unchanged",
+            converted,
+            failed
+        );
+    }
+
+    config.file_format = to.clone();
+    config
+        .save()
+        .map_err(|e| anyhow::anyhow!("Failed to save config: {}", e))?;
+    println!(
+        "{}",
+        format!(
+            "Converted {} day files. New entries are saved as {}.",
+            converted,
+            to.display_name()
+        )
+        .green()
+    );
+    Ok(())
+}
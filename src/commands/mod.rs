@@ -1,5 +1,17 @@
+mod backup;
+mod encryption;
+mod entries;
 mod help;
 mod journal;
+mod migrate;
+mod output;
+mod settings;
 
+pub use backup::*;
+pub use encryption::*;
+pub use entries::*;
 pub use help::exe_help;
 pub use journal::*;
+pub use migrate::exe_migrate_format;
+pub use output::OutputFormat;
+pub use settings::*;
@@ -0,0 +1,308 @@
+use crate::storage::JournalEntry;
+use anyhow::Result;
+use serde::Serialize;
+use std::fmt;
+use std::io::Write;
+
+// How `list`, `show` and `search` print their results
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub enum OutputFormat {
+    // Colored lines for people
+    #[default]
+    Text,
+    // One JSON array
+    Json,
+    // One JSON object per line
+    Ndjson,
+    // A header row, then one row per entry
+    Csv,
+}
+
+impl OutputFormat {
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.trim().to_lowercase().as_str() {
+            "text" => Some(OutputFormat::Text),
+            "json" => Some(OutputFormat::Json),
+            "ndjson" | "jsonl" => Some(OutputFormat::Ndjson),
+            "csv" => Some(OutputFormat::Csv),
+            _ => None,
+        }
+    }
+}
+
+// The stable shape of an entry in machine-readable output. Fields are only
+// ever added, never renamed.
+#[derive(Serialize)]
+pub struct EntryRecord {
+    pub id: String,
+    pub date: String,
+    pub filename: String,
+    pub format: String,
+    pub word_count: usize,
+    pub tags: Vec<String>,
+    pub created_at: Option<String>,
+    pub mentions: Vec<String>,
+    pub title: Option<String>,
+    pub mood: Option<String>,
+    pub energy: Option<String>,
+    pub location: Option<String>,
+    pub weather: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub score: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub snippet: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub body: Option<String>,
+}
+
+impl EntryRecord {
+    pub fn new(entry: &JournalEntry) -> Self {
+        Self {
+            id: entry.id.clone(),
+            date: entry.date.clone(),
+            filename: entry.filename.clone(),
+            format: entry
+                .format()
+                .map_or("", |format| format.extension())
+                .to_string(),
+            word_count: entry.word_count(),
+            tags: entry.metadata.tags.clone(),
+            created_at: entry.created_at.clone(),
+            mentions: entry.metadata.mentions.clone(),
+            title: entry.metadata.title.clone(),
+            mood: entry.metadata.mood.clone(),
+            energy: entry.metadata.energy.clone(),
+            location: entry.metadata.location.clone(),
+            weather: entry.metadata.weather.clone(),
+            score: None,
+            snippet: None,
+            body: None,
+        }
+    }
+}
+
+// CSV columns, in order. Commands append the optional fields they fill in.
+pub const ENTRY_COLUMNS: [&str; 13] = [
+    "id",
+    "date",
+    "filename",
+    "format",
+    "word_count",
+    "tags",
+    "created_at",
+    "mentions",
+    "title",
+    "mood",
+    "energy",
+    "location",
+    "weather",
+];
+
+// Prints records in a machine-readable format; `columns` are the CSV columns
+pub fn print_records(
+    out: &mut impl Write,
+    records: &[EntryRecord],
+    format: OutputFormat,
+    columns: &[&str],
+) -> Result<()> {
+    match format {
+        OutputFormat::Text => {}
+        OutputFormat::Json => writeln!(out, "{}", serde_json::to_string_pretty(records)?)?,
+        OutputFormat::Ndjson => {
+            for record in records {
+                writeln!(out, "{}", serde_json::to_string(record)?)?;
+            }
+        }
+        OutputFormat::Csv => {
+            writeln!(out, "{}", columns.join(","))?;
+            for record in records {
+                let serde_json::Value::Object(fields) = serde_json::to_value(record)? else {
+                    continue;
+                };
+                let row: Vec<String> = columns
+                    .iter()
+                    .map(|column| CsvField(fields.get(*column)).to_string())
+                    .collect();
+                writeln!(out, "{}", row.join(","))?;
+            }
+        }
+    }
+    Ok(())
+}
+
+// A CSV cell: lists are joined with ';', missing values are empty, and cells
+// with separators, quotes or line breaks are quoted
+struct CsvField<'a>(Option<&'a serde_json::Value>);
+
+impl fmt::Display for CsvField<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let text = match self.0 {
+            None | Some(serde_json::Value::Null) => String::new(),
+            Some(serde_json::Value::String(text)) => text.clone(),
+            Some(serde_json::Value::Array(items)) => items
+                .iter()
+                .map(|item| {
+                    item.as_str()
+                        .map_or_else(|| item.to_string(), str::to_string)
+                })
+                .collect::<Vec<_>>()
+                .join(";"),
+            Some(value) => value.to_string(),
+        };
+
+        if text.contains([',', '"', '\n', '\r']) {
+            write!(f, "\"{}\"", text.replace('"', "\"\""))
+        } else {
+            write!(f, "{}", text)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use time::{Date, Month};
+
+    fn record() -> EntryRecord {
+        let day = Date::from_calendar_date(2024, Month::May, 4).unwrap();
+        let created_at = day.with_hms(6, 30, 0).unwrap().assume_utc();
+        let mut entry = JournalEntry::new(
+            "2024-05-04",
+            day,
+            created_at,
+            "Swim, then \"coffee\"\nat the lake",
+        );
+        entry.id = "20240504063000000".to_string();
+        entry.filename = "2024-05-04.md".to_string();
+        entry.metadata.tags = vec!["lake".to_string(), "sport".to_string()];
+        entry.metadata.title = Some("Lake, swim".to_string());
+        entry.metadata.mood = Some("4".to_string());
+        EntryRecord::new(&entry)
+    }
+
+    fn print(records: &[EntryRecord], format: OutputFormat, columns: &[&str]) -> String {
+        let mut out = Vec::new();
+        print_records(&mut out, records, format, columns).unwrap();
+        String::from_utf8(out).unwrap()
+    }
+
+    fn csv(value: serde_json::Value) -> String {
+        CsvField(Some(&value)).to_string()
+    }
+
+    #[test]
+    fn csv_fields_are_quoted_when_needed() {
+        assert_eq!(csv(json!("plain text")), "plain text");
+        assert_eq!(csv(json!("a, b")), "\"a, b\"");
+        assert_eq!(csv(json!("say \"hi\"")), "\"say \"\"hi\"\"\"");
+        assert_eq!(csv(json!("two\nlines")), "\"two\nlines\"");
+        assert_eq!(csv(json!("crlf\r")), "\"crlf\r\"");
+        assert_eq!(csv(json!(42)), "42");
+        assert_eq!(csv(json!(0.5)), "0.5");
+        assert_eq!(csv(json!(null)), "");
+        assert_eq!(CsvField(None).to_string(), "");
+    }
+
+    #[test]
+    fn csv_lists_are_joined() {
+        assert_eq!(csv(json!(["lake", "sport"])), "lake;sport");
+        assert_eq!(csv(json!([])), "");
+        assert_eq!(csv(json!(["a,b", "c"])), "\"a,b;c\"");
+        assert_eq!(csv(json!([1, "two"])), "1;two");
+    }
+
+    #[test]
+    fn csv_rows_follow_the_columns() {
+        let output = print(&[record()], OutputFormat::Csv, &ENTRY_COLUMNS);
+
+        assert_eq!(
+            output,
+            "id,date,filename,format,word_count,tags,created_at,mentions,title,mood,energy,location,weather\n\
+             20240504063000000,2024-05-04,2024-05-04.md,md,6,lake;sport,2024-05-04T06:30:00Z,,\"Lake, swim\",4,,,\n"
+        );
+
+        let columns = ["snippet", "id", "score"];
+        let record = EntryRecord {
+            score: Some(1.5),
+            snippet: Some("then \"coffee\"".to_string()),
+            ..record()
+        };
+        assert_eq!(
+            print(&[record], OutputFormat::Csv, &columns),
+            "snippet,id,score\n\"then \"\"coffee\"\"\",20240504063000000,1.5\n"
+        );
+    }
+
+    #[test]
+    fn json_keys_keep_the_column_order() {
+        for format in [OutputFormat::Json, OutputFormat::Ndjson] {
+            let output = print(&[record()], format, &ENTRY_COLUMNS);
+            let positions: Vec<usize> = ENTRY_COLUMNS
+                .iter()
+                .map(|column| output.find(&format!("\"{}\":", column)).unwrap())
+                .collect();
+
+            assert!(
+                positions.windows(2).all(|pair| pair[0] < pair[1]),
+                "{:?}: {}",
+                format,
+                output
+            );
+        }
+    }
+
+    #[test]
+    fn json_output_has_a_stable_shape() {
+        let searched = EntryRecord {
+            score: Some(1.5),
+            snippet: Some("Swim".to_string()),
+            ..record()
+        };
+        let output = print(&[record(), searched], OutputFormat::Json, &ENTRY_COLUMNS);
+        let value: serde_json::Value = serde_json::from_str(&output).unwrap();
+
+        let expected = json!({
+            "id": "20240504063000000",
+            "date": "2024-05-04",
+            "filename": "2024-05-04.md",
+            "format": "md",
+            "word_count": 6,
+            "tags": ["lake", "sport"],
+            "created_at": "2024-05-04T06:30:00Z",
+            "mentions": [],
+            "title": "Lake, swim",
+            "mood": "4",
+            "energy": null,
+            "location": null,
+            "weather": null,
+        });
+        let mut with_score = expected.clone();
+        with_score["score"] = json!(1.5);
+        with_score["snippet"] = json!("Swim");
+        assert_eq!(value, json!([expected, with_score]));
+
+        let lines: Vec<serde_json::Value> = print(&[record()], OutputFormat::Ndjson, &[])
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+        assert_eq!(lines, [expected]);
+    }
+
+    #[test]
+    fn empty_results_are_still_valid_output() {
+        assert_eq!(print(&[], OutputFormat::Json, &ENTRY_COLUMNS), "[]\n");
+        assert_eq!(print(&[], OutputFormat::Ndjson, &ENTRY_COLUMNS), "");
+        assert_eq!(print(&[], OutputFormat::Csv, &["id", "date"]), "id,date\n");
+        assert_eq!(print(&[record()], OutputFormat::Text, &ENTRY_COLUMNS), "");
+    }
+
+    #[test]
+    fn formats_are_parsed_by_name() {
+        assert_eq!(OutputFormat::from_name(" JSON "), Some(OutputFormat::Json));
+        assert_eq!(OutputFormat::from_name("jsonl"), Some(OutputFormat::Ndjson));
+        assert_eq!(OutputFormat::from_name("csv"), Some(OutputFormat::Csv));
+        assert_eq!(OutputFormat::from_name("text"), Some(OutputFormat::Text));
+        assert_eq!(OutputFormat::from_name("xml"), None);
+    }
+}
@@ -0,0 +1,99 @@
+use crate::config::Config;
+use crate::search::SearchIndex;
+use crate::storage;
+use anyhow::{anyhow, bail, Result};
+use colored::Colorize;
+
+// Prints a setting by its dotted key (e.g. `llm.model`), or the whole config
+pub fn exe_config_get(key: Option<&str>) -> Result<()> {
+    let config = Config::load()?;
+    let table = toml::Table::try_from(&config)?;
+
+    let Some(key) = key else {
+        print!("{}", toml::to_string_pretty(&table)?);
+        return Ok(());
+    };
+
+    match lookup(&table, key) {
+        Some(toml::Value::String(text)) => println!("{}", text),
+        Some(toml::Value::Table(table)) => print!("{}", toml::to_string_pretty(table)?),
+        Some(value) => println!("{}", value),
+        None => bail!("Unknown or unset setting: {}", key),
+    }
+    Ok(())
+}
+
+// Changes a setting and saves the config. Values of text settings are taken
+// as-is; others are parsed as TOML (`true`, `120`, ...).
+pub fn exe_config_set(key: &str, value: &str) -> Result<()> {
+    let config = Config::load()?;
+    let mut table = toml::Table::try_from(&config)?;
+
+    let parts: Vec<&str> = key.split('.').collect();
+    let (name, parents) = parts
+        .split_last()
+        .filter(|(name, _)| !name.is_empty())
+        .ok_or_else(|| anyhow!("Unknown setting: {}", key))?;
+    let mut current = &mut table;
+    for part in parents {
+        current = current
+            .entry(part.to_string())
+            .or_insert_with(|| toml::Value::Table(toml::Table::new()))
+            .as_table_mut()
+            .ok_or_else(|| anyhow!("Unknown setting: {}", key))?;
+    }
+
+    let parsed = match current.get(*name) {
+        None | Some(toml::Value::String(_)) => toml::Value::String(value.to_string()),
+        Some(_) => format!("value = {}", value)
+            .parse::<toml::Table>()
+            .ok()
+            .and_then(|mut table| table.remove("value"))
+            .ok_or_else(|| anyhow!("Invalid value for {}: {}", key, value))?,
+    };
+    current.insert(name.to_string(), parsed);
+
+    let updated: Config = toml::Value::Table(table)
+        .try_into()
+        .map_err(|e| anyhow!("Invalid value for {}: {}", key, e))?;
+
+    // Keys the config doesn't know are dropped when it's deserialized
+    if lookup(&toml::Table::try_from(&updated)?, key).is_none() {
+        bail!("Unknown setting: {}", key);
+    }
+
+    updated
+        .date_formatter()
+        .map_err(|e| anyhow!("Invalid date format: {}", e))?;
+    updated
+        .journal_time_zone()
+        .map_err(|e| anyhow!("Invalid time zone: {}", e))?;
+
+    // Day files are renamed first so the journal keeps listing them; the
+    // setting isn't saved when they can't be
+    let renamed = if updated.date_format != config.date_format {
+        super::unlock_journal()?;
+        let renamed = storage::rename_day_files(&updated, &config.date_format)
+            .map_err(|e| anyhow!("Failed to rename the day files: {}", e))?;
+        SearchIndex::delete(&updated)?;
+        Some(renamed)
+    } else {
+        None
+    };
+
+    updated.save()?;
+    println!("{}", format!("{} = {}", key, value).green());
+    if let Some(renamed) = renamed {
+        println!("Renamed {} day files to the new date format.", renamed);
+    }
+    Ok(())
+}
+
+fn lookup<'a>(table: &'a toml::Table, key: &str) -> Option<&'a toml::Value> {
+    let mut parts = key.split('.');
+    let mut value = table.get(parts.next()?)?;
+    for part in parts {
+        value = value.as_table()?.get(part)?;
+    }
+    Some(value)
+}
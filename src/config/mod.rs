@@ -1,5 +1,8 @@
+use crate::storage;
+use crate::utils::{DateFormat, JournalTimeZone};
 use anyhow::Result;
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 use std::fs;
 use std::path::PathBuf;
 
@@ -8,8 +11,72 @@ pub struct Config {
     pub journal_directory: PathBuf,
     pub file_format: FileFormat,
     pub date_format: String,
+    #[serde(default = "default_time_zone")]
+    pub time_zone: String,
+    // Snapshot day files into the backup directory before they are changed
     pub auto_backup: bool,
     pub editor_command: Option<String>,
+    // Ask for mood and energy when saving from the Add Journal screen
+    #[serde(default)]
+    pub mood_check_in: bool,
+    #[serde(default)]
+    pub backup: BackupConfig,
+    #[serde(default)]
+    pub llm: LlmConfig,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub prompts: BTreeMap<String, String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BackupConfig {
+    // Where snapshots and archives are kept; `rusty_diary/backups` in the
+    // user's data directory when unset
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub directory: Option<PathBuf>,
+    // Snapshots kept of each day file, newest first; 0 keeps them all
+    #[serde(default = "default_keep_versions")]
+    pub keep_versions: usize,
+    // Snapshots older than this many days are deleted; 0 keeps them
+    // regardless of age
+    #[serde(default)]
+    pub keep_days: u64,
+}
+
+// Settings left out of the `[llm]` table keep their defaults
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct LlmConfig {
+    pub backend: LlmBackendKind,
+    pub base_url: String,
+    pub model: String,
+    pub timeout_secs: u64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub api_key: Option<String>,
+    #[serde(default = "default_llm_command")]
+    pub command: String,
+    #[serde(default = "default_prompt")]
+    pub default_prompt: String,
+    #[serde(default)]
+    pub fallback: LlmFallback,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub enum LlmBackendKind {
+    Ollama,
+    OpenAi,
+    Cli,
+}
+
+// What happens to a Markdown entry when the LLM can't be reached
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq)]
+pub enum LlmFallback {
+    // Save the entry as written
+    SaveRaw,
+    // Save the entry as written and format it later with --process-pending
+    #[default]
+    Queue,
+    // Don't save the entry
+    Fail,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
@@ -20,6 +87,13 @@ pub enum FileFormat {
 }
 
 impl FileFormat {
+    // Every format day files can be stored in; files of all of them are loaded
+    pub const ALL: [FileFormat; 3] = [
+        FileFormat::Markdown,
+        FileFormat::PlainText,
+        FileFormat::Json,
+    ];
+
     pub fn extension(&self) -> &'static str {
         match self {
             FileFormat::Markdown => "md",
@@ -28,6 +102,20 @@ impl FileFormat {
         }
     }
 
+    pub fn from_extension(extension: &str) -> Option<Self> {
+        Self::ALL
+            .into_iter()
+            .find(|format| format.extension() == extension)
+    }
+
+    // Accepts the extension ("md") or the config name ("Markdown"), in any case
+    pub fn from_name(name: &str) -> Option<Self> {
+        let name = name.trim().to_lowercase();
+        Self::ALL.into_iter().find(|format| {
+            format.extension() == name || format!("{:?}", format).to_lowercase() == name
+        })
+    }
+
     pub fn display_name(&self) -> &'static str {
         match self {
             FileFormat::Markdown => "Markdown (.md)",
@@ -37,6 +125,57 @@ impl FileFormat {
     }
 }
 
+fn default_time_zone() -> String {
+    "local".to_string()
+}
+
+fn default_keep_versions() -> usize {
+    10
+}
+
+fn default_llm_command() -> String {
+    "ollama".to_string()
+}
+
+fn default_prompt() -> String {
+    crate::llm::DEFAULT_PROMPT.to_string()
+}
+
+impl Default for LlmConfig {
+    fn default() -> Self {
+        Self {
+            backend: LlmBackendKind::Ollama,
+            base_url: "http://localhost:11434".to_string(),
+            model: "llama3.1:8b".to_string(),
+            timeout_secs: 120,
+            api_key: None,
+            command: default_llm_command(),
+            default_prompt: default_prompt(),
+            fallback: LlmFallback::default(),
+        }
+    }
+}
+
+impl Default for BackupConfig {
+    fn default() -> Self {
+        Self {
+            directory: None,
+            keep_versions: default_keep_versions(),
+            keep_days: 0,
+        }
+    }
+}
+
+impl LlmBackendKind {
+    pub fn display_name(&self) -> &'static str {
+        match self {
+            LlmBackendKind::Ollama => "Ollama HTTP API",
+            LlmBackendKind::OpenAi => "OpenAI-compatible API",
+            LlmBackendKind::Cli => "Command line",
+        }
+    }
+}
+
 impl Default for Config {
     fn default() -> Self {
         let home_dir = dirs::home_dir().unwrap_or_else(|| PathBuf::from("."));
@@ -46,8 +185,13 @@ impl Default for Config {
             journal_directory: journal_dir,
             file_format: FileFormat::Markdown,
             date_format: "%Y-%m-%d".to_string(),
+            time_zone: default_time_zone(),
             auto_backup: false,
             editor_command: None,
+            mood_check_in: false,
+            backup: BackupConfig::default(),
+            llm: LlmConfig::default(),
+            prompts: BTreeMap::new(),
         }
     }
 }
@@ -84,19 +228,86 @@ impl Config {
     }
 
     fn config_file_path() -> Result<PathBuf> {
+        Ok(Self::config_directory()?.join("config.toml"))
+    }
+
+    pub fn prompts_directory() -> Result<PathBuf> {
+        Ok(Self::config_directory()?.join("prompts"))
+    }
+
+    fn config_directory() -> Result<PathBuf> {
         let config_dir = dirs::config_dir()
             .or_else(|| dirs::home_dir().map(|h| h.join(".config")))
             .unwrap_or_else(|| PathBuf::from("."));
 
-        Ok(config_dir.join("rusty_diary").join("config.toml"))
+        Ok(config_dir.join("rusty_diary"))
     }
 
+    // The day file new entries for the date go to, encrypted in an encrypted
+    // journal
     pub fn get_journal_file_path(&self, date: &str) -> PathBuf {
-        let filename = format!("{}.{}", date, self.file_format.extension());
+        let filename =
+            storage::journal_file_name(date, &self.file_format, storage::is_encrypted(self));
         self.journal_directory.join(filename)
     }
 
+    pub fn backup_directory(&self) -> PathBuf {
+        self.backup.directory.clone().unwrap_or_else(|| {
+            dirs::data_dir()
+                .or_else(|| dirs::home_dir().map(|h| h.join(".local").join("share")))
+                .unwrap_or_else(|| PathBuf::from("."))
+                .join("rusty_diary")
+                .join("backups")
+        })
+    }
+
+    pub fn date_formatter(&self) -> Result<DateFormat> {
+        DateFormat::new(&self.date_format)
+    }
+
+    pub fn journal_time_zone(&self) -> Result<JournalTimeZone> {
+        JournalTimeZone::from_setting(&self.time_zone)
+    }
+
     pub fn get_journal_directory_display(&self) -> String {
         self.journal_directory.display().to_string()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn partial_llm_table_keeps_the_other_defaults() {
+        let config: Config = toml::from_str(
+            r#"
+            journal_directory = "/tmp/journal"
+            file_format = "Markdown"
+            date_format = "%Y-%m-%d"
+            auto_backup = false
+
+            [llm]
+            model = "llama3"
+            "#,
+        )
+        .unwrap();
+
+        let defaults = LlmConfig::default();
+        assert_eq!(config.llm.model, "llama3");
+        assert_eq!(config.llm.backend, defaults.backend);
+        assert_eq!(config.llm.base_url, defaults.base_url);
+        assert_eq!(config.llm.timeout_secs, defaults.timeout_secs);
+        assert_eq!(config.llm.fallback, defaults.fallback);
+        assert_eq!(config.backup.keep_versions, default_keep_versions());
+    }
+
+    #[test]
+    fn example_config_is_valid() {
+        let config: Config = toml::from_str(include_str!("../../config.example.toml")).unwrap();
+        assert!(!config.auto_backup);
+        assert_eq!(config.backup.keep_versions, 10);
+        assert_eq!(config.backup.keep_days, 0);
+        assert!(config.backup.directory.is_none());
+    }
+}
@@ -0,0 +1,81 @@
+use super::{Cancelled, LlmBackend};
+use crate::config::LlmConfig;
+use anyhow::{anyhow, Result};
+use std::io::{BufRead, BufReader, Read, Write};
+use std::ops::ControlFlow;
+use std::process::{Command, Stdio};
+
+// Runs `<command> run <model>` (the Ollama CLI by default) with the prompt on stdin
+pub struct CliBackend {
+    command: String,
+    model: String,
+}
+
+impl CliBackend {
+    pub fn new(config: &LlmConfig) -> Self {
+        Self {
+            command: config.command.clone(),
+            model: config.model.clone(),
+        }
+    }
+}
+
+impl LlmBackend for CliBackend {
+    fn model(&self) -> &str {
+        &self.model
+    }
+
+    fn generate(
+        &self,
+        prompt: &str,
+        on_token: &mut dyn FnMut(&str) -> ControlFlow<()>,
+    ) -> Result<String> {
+        let mut child = Command::new(&self.command)
+            .args(["run", &self.model])
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()
+            .map_err(|e| anyhow!("Failed to run '{}': {}", self.command, e))?;
+
+        if let Some(mut stdin) = child.stdin.take() {
+            stdin.write_all(prompt.as_bytes())?;
+        }
+
+        // Drain stderr on its own thread so a chatty child can't block on it
+        let stderr = child.stderr.take().map(|mut pipe| {
+            std::thread::spawn(move || {
+                let mut stderr = String::new();
+                let _ = pipe.read_to_string(&mut stderr);
+                stderr
+            })
+        });
+
+        let mut output = String::new();
+        if let Some(stdout) = child.stdout.take() {
+            for line in BufReader::new(stdout).lines() {
+                let line = format!("{}\n", line?);
+                if on_token(&line).is_break() {
+                    let _ = child.kill();
+                    let _ = child.wait();
+                    return Err(Cancelled.into());
+                }
+                output.push_str(&line);
+            }
+        }
+
+        let stderr = stderr
+            .and_then(|handle| handle.join().ok())
+            .unwrap_or_default();
+
+        if child.wait()?.success() {
+            Ok(output)
+        } else {
+            Err(anyhow!(
+                "{} execution failed: {}",
+                self.command,
+                stderr.trim()
+            ))
+        }
+    }
+}
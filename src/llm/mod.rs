@@ -0,0 +1,138 @@
+mod cli;
+mod ollama;
+mod openai;
+mod prompt;
+mod worker;
+
+pub use cli::CliBackend;
+pub use ollama::OllamaBackend;
+pub use openai::OpenAiBackend;
+pub use prompt::*;
+pub use worker::*;
+
+use crate::config::{LlmBackendKind, LlmConfig};
+use anyhow::Result;
+use std::ops::ControlFlow;
+
+pub trait LlmBackend: Send + Sync {
+    fn model(&self) -> &str;
+
+    // Sends the prompt and passes each piece of the response to `on_token` as it
+    // arrives. Returns the whole response, or a `Cancelled` error once
+    // `on_token` breaks.
+    fn generate(
+        &self,
+        prompt: &str,
+        on_token: &mut dyn FnMut(&str) -> ControlFlow<()>,
+    ) -> Result<String>;
+}
+
+#[derive(Debug)]
+pub struct Cancelled;
+
+impl std::fmt::Display for Cancelled {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "LLM request cancelled")
+    }
+}
+
+impl std::error::Error for Cancelled {}
+
+pub fn backend_from_config(config: &LlmConfig) -> Box<dyn LlmBackend> {
+    match config.backend {
+        LlmBackendKind::Ollama => Box::new(OllamaBackend::new(config)),
+        LlmBackendKind::OpenAi => Box::new(OpenAiBackend::new(config)),
+        LlmBackendKind::Cli => Box::new(CliBackend::new(config)),
+    }
+}
+
+fn http_agent(config: &LlmConfig) -> ureq::Agent {
+    ureq::AgentBuilder::new()
+        .timeout(std::time::Duration::from_secs(config.timeout_secs))
+        .build()
+}
+
+// Turns HTTP error statuses into errors that include the server's message
+fn http_error(error: ureq::Error, url: &str) -> anyhow::Error {
+    match error {
+        ureq::Error::Status(code, response) => {
+            let body = response.into_string().unwrap_or_default();
+            anyhow::anyhow!("{} returned {}: {}", url, code, body.trim())
+        }
+        ureq::Error::Transport(transport) => {
+            // The transport error's own message repeats the URL
+            let detail = std::error::Error::source(&transport)
+                .map(|source| source.to_string())
+                .or_else(|| transport.message().map(str::to_string))
+                .unwrap_or_else(|| transport.kind().to_string());
+            anyhow::anyhow!("Could not reach {}: {}", url, detail)
+        }
+    }
+}
+
+// A server on a local port that answers one HTTP request. `respond` writes the
+// response; the request, headers and body, is returned when the thread is
+// joined.
+#[cfg(test)]
+fn serve_once(
+    respond: impl FnOnce(&mut std::net::TcpStream) + Send + 'static,
+) -> (String, std::thread::JoinHandle<String>) {
+    use std::io::{BufRead, BufReader, Read};
+
+    let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+    let base_url = format!("http://{}", listener.local_addr().unwrap());
+    let handle = std::thread::spawn(move || {
+        let (mut stream, _) = listener.accept().unwrap();
+        let mut reader = BufReader::new(stream.try_clone().unwrap());
+        let mut request = String::new();
+        let mut length = 0;
+        loop {
+            let mut line = String::new();
+            reader.read_line(&mut line).unwrap();
+            if let Some((name, value)) = line.split_once(':') {
+                if name.eq_ignore_ascii_case("content-length") {
+                    length = value.trim().parse().unwrap();
+                }
+            }
+            request.push_str(&line);
+            if line == "\r\n" || line.is_empty() {
+                break;
+            }
+        }
+        let mut body = vec![0; length];
+        reader.read_exact(&mut body).unwrap();
+        request.push_str(&String::from_utf8(body).unwrap());
+
+        respond(&mut stream);
+        request
+    });
+    (base_url, handle)
+}
+
+// Writes a whole response and closes the connection. Errors are ignored, as
+// the client may have gone away.
+#[cfg(test)]
+fn write_response(stream: &mut std::net::TcpStream, status: &str, body: &str) {
+    use std::io::Write;
+
+    let _ = write!(
+        stream,
+        "HTTP/1.1 {}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        status,
+        body.len(),
+        body
+    );
+    let _ = stream.flush();
+}
+
+#[cfg(test)]
+fn test_config(backend: LlmBackendKind, base_url: &str) -> LlmConfig {
+    LlmConfig {
+        backend,
+        base_url: base_url.to_string(),
+        model: "test-model".to_string(),
+        timeout_secs: 5,
+        api_key: Some("secret".to_string()),
+        ..LlmConfig::default()
+    }
+}
@@ -0,0 +1,182 @@
+use super::{http_agent, http_error, Cancelled, LlmBackend};
+use crate::config::LlmConfig;
+use anyhow::{anyhow, Result};
+use serde::Deserialize;
+use std::io::{BufRead, BufReader};
+use std::ops::ControlFlow;
+
+// Ollama's native HTTP API (`/api/generate`)
+pub struct OllamaBackend {
+    agent: ureq::Agent,
+    base_url: String,
+    model: String,
+}
+
+#[derive(Deserialize)]
+struct GenerateChunk {
+    #[serde(default)]
+    response: String,
+    #[serde(default)]
+    done: bool,
+    error: Option<String>,
+}
+
+impl OllamaBackend {
+    pub fn new(config: &LlmConfig) -> Self {
+        Self {
+            agent: http_agent(config),
+            base_url: config.base_url.trim_end_matches('/').to_string(),
+            model: config.model.clone(),
+        }
+    }
+}
+
+impl LlmBackend for OllamaBackend {
+    fn model(&self) -> &str {
+        &self.model
+    }
+
+    fn generate(
+        &self,
+        prompt: &str,
+        on_token: &mut dyn FnMut(&str) -> ControlFlow<()>,
+    ) -> Result<String> {
+        let url = format!("{}/api/generate", self.base_url);
+        let response = self
+            .agent
+            .post(&url)
+            .send_json(serde_json::json!({
+                "model": self.model,
+                "prompt": prompt,
+                "stream": true,
+            }))
+            .map_err(|e| http_error(e, &url))?;
+
+        // The response is streamed as one JSON object per line
+        let mut output = String::new();
+        for line in BufReader::new(response.into_reader()).lines() {
+            let line = line?;
+            if line.trim().is_empty() {
+                continue;
+            }
+
+            let chunk: GenerateChunk = serde_json::from_str(&line)?;
+            if let Some(error) = chunk.error {
+                return Err(anyhow!("Ollama error: {}", error));
+            }
+            if on_token(&chunk.response).is_break() {
+                // Dropping the response closes the connection
+                return Err(Cancelled.into());
+            }
+            output.push_str(&chunk.response);
+            if chunk.done {
+                break;
+            }
+        }
+
+        Ok(output)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::LlmBackendKind;
+    use crate::llm::{serve_once, test_config, write_response};
+
+    fn backend(base_url: &str) -> OllamaBackend {
+        OllamaBackend::new(&test_config(LlmBackendKind::Ollama, base_url))
+    }
+
+    #[test]
+    fn response_is_streamed_line_by_line() {
+        let (base_url, server) = serve_once(|stream| {
+            write_response(
+                stream,
+                "200 OK",
+                "{\"response\":\"Dear \"}\n\n{\"response\":\"diary\"}\n{\"response\":\"\",\"done\":true}\n",
+            )
+        });
+
+        let mut tokens = Vec::new();
+        let output = backend(&format!("{}/", base_url))
+            .generate("Tidy this", &mut |token| {
+                tokens.push(token.to_string());
+                ControlFlow::Continue(())
+            })
+            .unwrap();
+        assert_eq!(output, "Dear diary");
+        assert_eq!(tokens, ["Dear ", "diary", ""]);
+
+        let request = server.join().unwrap();
+        assert!(request.starts_with("POST /api/generate "));
+        let body: serde_json::Value =
+            serde_json::from_str(request.split("\r\n\r\n").nth(1).unwrap()).unwrap();
+        assert_eq!(
+            body,
+            serde_json::json!({ "model": "test-model", "prompt": "Tidy this", "stream": true })
+        );
+    }
+
+    #[test]
+    fn errors_include_the_servers_message() {
+        let (base_url, server) = serve_once(|stream| {
+            write_response(stream, "200 OK", "{\"error\":\"out of memory\"}\n")
+        });
+        let error = backend(&base_url)
+            .generate("Tidy this", &mut |_| ControlFlow::Continue(()))
+            .unwrap_err();
+        assert_eq!(error.to_string(), "Ollama error: out of memory");
+        server.join().unwrap();
+
+        let (base_url, server) = serve_once(|stream| {
+            write_response(
+                stream,
+                "404 Not Found",
+                "{\"error\":\"model 'test-model' not found\"}\n",
+            )
+        });
+        let error = backend(&base_url)
+            .generate("Tidy this", &mut |_| ControlFlow::Continue(()))
+            .unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            format!(
+                "{}/api/generate returned 404: {{\"error\":\"model 'test-model' not found\"}}",
+                base_url
+            )
+        );
+        server.join().unwrap();
+    }
+
+    #[test]
+    fn unreachable_servers_are_reported() {
+        // A port nothing listens on any more
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let base_url = format!("http://{}", listener.local_addr().unwrap());
+        drop(listener);
+
+        let error = backend(&base_url)
+            .generate("Tidy this", &mut |_| ControlFlow::Continue(()))
+            .unwrap_err();
+        assert!(error
+            .to_string()
+            .starts_with(&format!("Could not reach {}/api/generate: ", base_url)));
+    }
+
+    #[test]
+    fn breaking_cancels_the_request() {
+        let (base_url, server) = serve_once(|stream| {
+            write_response(
+                stream,
+                "200 OK",
+                "{\"response\":\"Dear \"}\n{\"response\":\"diary\",\"done\":true}\n",
+            )
+        });
+        let error = backend(&base_url)
+            .generate("Tidy this", &mut |_| ControlFlow::Break(()))
+            .unwrap_err();
+        assert!(error.is::<Cancelled>());
+        server.join().unwrap();
+    }
+}
@@ -0,0 +1,198 @@
+use super::{http_agent, http_error, Cancelled, LlmBackend};
+use crate::config::LlmConfig;
+use anyhow::Result;
+use serde::Deserialize;
+use std::io::{BufRead, BufReader};
+use std::ops::ControlFlow;
+
+// Any server speaking the OpenAI chat completions API, such as the llama.cpp
+// server, vLLM or LM Studio
+pub struct OpenAiBackend {
+    agent: ureq::Agent,
+    url: String,
+    model: String,
+    api_key: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct CompletionChunk {
+    choices: Vec<ChunkChoice>,
+}
+
+#[derive(Deserialize)]
+struct ChunkChoice {
+    delta: ChunkDelta,
+}
+
+#[derive(Deserialize)]
+struct ChunkDelta {
+    content: Option<String>,
+}
+
+impl OpenAiBackend {
+    pub fn new(config: &LlmConfig) -> Self {
+        // Accept base URLs given with or without the `/v1` suffix
+        let base_url = config.base_url.trim_end_matches('/');
+        let url = if base_url.ends_with("/v1") {
+            format!("{}/chat/completions", base_url)
+        } else {
+            format!("{}/v1/chat/completions", base_url)
+        };
+
+        Self {
+            agent: http_agent(config),
+            url,
+            model: config.model.clone(),
+            api_key: config.api_key.clone(),
+        }
+    }
+}
+
+impl LlmBackend for OpenAiBackend {
+    fn model(&self) -> &str {
+        &self.model
+    }
+
+    fn generate(
+        &self,
+        prompt: &str,
+        on_token: &mut dyn FnMut(&str) -> ControlFlow<()>,
+    ) -> Result<String> {
+        let mut request = self.agent.post(&self.url);
+        if let Some(api_key) = &self.api_key {
+            request = request.set("Authorization", &format!("Bearer {}", api_key));
+        }
+
+        let response = request
+            .send_json(serde_json::json!({
+                "model": self.model,
+                "messages": [{ "role": "user", "content": prompt }],
+                "stream": true,
+            }))
+            .map_err(|e| http_error(e, &self.url))?;
+
+        // Server-sent events, one `data: {...}` line per chunk
+        let mut output = String::new();
+        for line in BufReader::new(response.into_reader()).lines() {
+            let line = line?;
+            let Some(data) = line.strip_prefix("data:").map(str::trim) else {
+                continue;
+            };
+            if data == "[DONE]" {
+                break;
+            }
+
+            let chunk: CompletionChunk = serde_json::from_str(data)?;
+            for content in chunk.choices.into_iter().filter_map(|c| c.delta.content) {
+                if on_token(&content).is_break() {
+                    return Err(Cancelled.into());
+                }
+                output.push_str(&content);
+            }
+        }
+
+        Ok(output)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::LlmBackendKind;
+    use crate::llm::{serve_once, test_config, write_response};
+
+    fn backend(base_url: &str) -> OpenAiBackend {
+        OpenAiBackend::new(&test_config(LlmBackendKind::OpenAi, base_url))
+    }
+
+    #[test]
+    fn url_gets_the_v1_prefix_once() {
+        assert_eq!(
+            backend("http://localhost:8080/").url,
+            "http://localhost:8080/v1/chat/completions"
+        );
+        assert_eq!(
+            backend("http://localhost:8080/v1").url,
+            "http://localhost:8080/v1/chat/completions"
+        );
+    }
+
+    #[test]
+    fn server_sent_events_are_streamed() {
+        let (base_url, server) = serve_once(|stream| {
+            write_response(
+                stream,
+                "200 OK",
+                "data: {\"choices\":[{\"delta\":{\"role\":\"assistant\"}}]}\n\n\
+                 data: {\"choices\":[{\"delta\":{\"content\":\"Dear \"}}]}\n\n\
+                 : keep-alive\n\n\
+                 data: {\"choices\":[{\"delta\":{\"content\":\"diary\"}}]}\n\n\
+                 data: [DONE]\n\n",
+            )
+        });
+
+        let mut tokens = Vec::new();
+        let output = backend(&base_url)
+            .generate("Tidy this", &mut |token| {
+                tokens.push(token.to_string());
+                ControlFlow::Continue(())
+            })
+            .unwrap();
+        assert_eq!(output, "Dear diary");
+        assert_eq!(tokens, ["Dear ", "diary"]);
+
+        let request = server.join().unwrap();
+        assert!(request.starts_with("POST /v1/chat/completions "));
+        assert!(request
+            .lines()
+            .any(|line| line.eq_ignore_ascii_case("authorization: Bearer secret")));
+        let body: serde_json::Value =
+            serde_json::from_str(request.split("\r\n\r\n").nth(1).unwrap()).unwrap();
+        assert_eq!(body["model"], "test-model");
+        assert_eq!(body["stream"], true);
+        assert_eq!(
+            body["messages"],
+            serde_json::json!([{ "role": "user", "content": "Tidy this" }])
+        );
+    }
+
+    #[test]
+    fn error_statuses_include_the_servers_message() {
+        let (base_url, server) =
+            serve_once(|stream| write_response(stream, "401 Unauthorized", "invalid api key\n"));
+        let error = backend(&base_url)
+            .generate("Tidy this", &mut |_| ControlFlow::Continue(()))
+            .unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            format!(
+                "{}/v1/chat/completions returned 401: invalid api key",
+                base_url
+            )
+        );
+        server.join().unwrap();
+    }
+
+    #[test]
+    fn malformed_chunks_and_cancelling_are_errors() {
+        let (base_url, server) =
+            serve_once(|stream| write_response(stream, "200 OK", "data: {not json\n\n"));
+        assert!(backend(&base_url)
+            .generate("Tidy this", &mut |_| ControlFlow::Continue(()))
+            .is_err());
+        server.join().unwrap();
+
+        let (base_url, server) = serve_once(|stream| {
+            write_response(
+                stream,
+                "200 OK",
+                "data: {\"choices\":[{\"delta\":{\"content\":\"Dear \"}}]}\n\ndata: [DONE]\n\n",
+            )
+        });
+        let error = backend(&base_url)
+            .generate("Tidy this", &mut |_| ControlFlow::Break(()))
+            .unwrap_err();
+        assert!(error.is::<Cancelled>());
+        server.join().unwrap();
+    }
+}
@@ -0,0 +1,195 @@
+use crate::config::Config;
+use anyhow::{anyhow, Result};
+use std::collections::BTreeMap;
+use std::fs;
+
+pub const DEFAULT_PROMPT: &str = "format";
+
+const BUILT_IN_PROMPTS: [(&str, &str); 3] = [
+    (
+        "format",
+        "Please just structure and organize the following journal entry into a beautiful and organized markdown format. You should only respond with the markdown result, do not add any additional details, you have to deal with the entry.\n\n{{entry}}",
+    ),
+    (
+        "gratitude",
+        "Rewrite the following journal entry from {{date}} as a short gratitude journal in markdown. Keep every fact from the entry, highlight what the writer can be thankful for and do not invent new events. Only respond with the markdown result.\n\n{{entry}}",
+    ),
+    (
+        "spelling",
+        "Fix only the spelling, grammar and punctuation of the following journal entry. Do not change the wording, structure or meaning, and only respond with the corrected text.\n\n{{entry}}",
+    ),
+];
+
+// A named prompt. Templates may use {{entry}}, {{date}}, {{previous_entry}} and
+// {{tags}}; the entry is appended when the template doesn't place it.
+#[derive(Debug, Clone)]
+pub struct PromptTemplate {
+    pub name: String,
+    pub template: String,
+}
+
+#[derive(Default)]
+pub struct PromptContext<'a> {
+    pub entry: &'a str,
+    pub date: &'a str,
+    pub previous_entry: Option<&'a str>,
+    pub tags: &'a [String],
+}
+
+impl PromptTemplate {
+    // Placeholders are substituted in a single pass so text inside the values
+    // (an entry quoting "{{tags}}", say) is never expanded again. Unknown
+    // placeholders are left as they are.
+    pub fn render(&self, context: &PromptContext) -> String {
+        let tags = context.tags.join(", ");
+        let mut prompt = String::with_capacity(self.template.len() + context.entry.len());
+        let mut rest = self.template.as_str();
+
+        while let Some(start) = rest.find("{{") {
+            prompt.push_str(&rest[..start]);
+            let after = &rest[start + 2..];
+            let value = after.find("}}").and_then(|end| {
+                let value = match &after[..end] {
+                    "entry" => context.entry,
+                    "date" => context.date,
+                    "previous_entry" => context.previous_entry.unwrap_or(""),
+                    "tags" => &tags,
+                    _ => return None,
+                };
+                Some((value, end))
+            });
+            match value {
+                Some((value, end)) => {
+                    prompt.push_str(value);
+                    rest = &after[end + 2..];
+                }
+                None => {
+                    prompt.push_str("{{");
+                    rest = after;
+                }
+            }
+        }
+        prompt.push_str(rest);
+
+        if !self.template.contains("{{entry}}") {
+            prompt = format!("{}\n\n{}", prompt.trim_end(), context.entry);
+        }
+        prompt
+    }
+}
+
+// Built-in prompts, overridden by `[prompts]` in config.toml, overridden in turn
+// by files in the `prompts/` directory next to it (the file stem is the name)
+pub fn available_prompts(config: &Config) -> Vec<PromptTemplate> {
+    let mut prompts: BTreeMap<String, String> = BUILT_IN_PROMPTS
+        .iter()
+        .map(|(name, template)| (name.to_string(), template.to_string()))
+        .collect();
+
+    prompts.extend(config.prompts.clone());
+
+    if let Ok(directory) = Config::prompts_directory() {
+        if let Ok(files) = fs::read_dir(directory) {
+            for path in files.flatten().map(|file| file.path()) {
+                let Some(name) = path.file_stem().and_then(|stem| stem.to_str()) else {
+                    continue;
+                };
+                if let Ok(template) = fs::read_to_string(&path) {
+                    prompts.insert(name.to_string(), template);
+                }
+            }
+        }
+    }
+
+    prompts
+        .into_iter()
+        .map(|(name, template)| PromptTemplate { name, template })
+        .collect()
+}
+
+pub fn find_prompt(config: &Config, name: &str) -> Result<PromptTemplate> {
+    available_prompts(config)
+        .into_iter()
+        .find(|prompt| prompt.name == name)
+        .ok_or_else(|| anyhow!("Unknown prompt '{}'", name))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn render(template: &str, context: &PromptContext) -> String {
+        PromptTemplate {
+            name: "test".to_string(),
+            template: template.to_string(),
+        }
+        .render(context)
+    }
+
+    #[test]
+    fn placeholders_are_filled_in() {
+        let tags = ["work".to_string(), "travel".to_string()];
+        let context = PromptContext {
+            entry: "Flew to Oslo",
+            date: "2024-05-04",
+            previous_entry: Some("Packed"),
+            tags: &tags,
+        };
+
+        assert_eq!(
+            render(
+                "{{date}} [{{tags}}] after {{previous_entry}}: {{entry}}",
+                &context
+            ),
+            "2024-05-04 [work, travel] after Packed: Flew to Oslo"
+        );
+    }
+
+    #[test]
+    fn the_entry_is_appended_when_the_template_does_not_place_it() {
+        let context = PromptContext {
+            entry: "Flew to Oslo",
+            ..Default::default()
+        };
+
+        assert_eq!(
+            render("Summarize:\n", &context),
+            "Summarize:\n\nFlew to Oslo"
+        );
+    }
+
+    #[test]
+    fn unknown_placeholders_are_left_alone() {
+        let context = PromptContext {
+            entry: "Flew to Oslo",
+            date: "2024-05-04",
+            ..Default::default()
+        };
+
+        assert_eq!(
+            render("{{mood}} {{ date }} {{date}} {{ {{entry}} }}", &context),
+            "{{mood}} {{ date }} 2024-05-04 {{ Flew to Oslo }}"
+        );
+        assert_eq!(render("{{entry}} {{date", &context), "Flew to Oslo {{date");
+    }
+
+    #[test]
+    fn placeholders_inside_values_are_not_expanded() {
+        let tags = ["{{entry}}".to_string()];
+        let context = PromptContext {
+            entry: "Wrote {{date}} on the wall",
+            date: "2024-05-04",
+            previous_entry: Some("Read about {{entry}} and {{tags}}"),
+            tags: &tags,
+        };
+
+        assert_eq!(
+            render("{{previous_entry}} | {{tags}} | {{entry}}", &context),
+            "Read about {{entry}} and {{tags}} | {{entry}} | Wrote {{date}} on the wall"
+        );
+        assert_eq!(
+            render("Fix this:", &context),
+            "Fix this:\n\nWrote {{date}} on the wall"
+        );
+    }
+}
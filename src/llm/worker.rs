@@ -0,0 +1,149 @@
+use super::backend_from_config;
+use crate::config::LlmConfig;
+use anyhow::{anyhow, Result};
+use std::ops::ControlFlow;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{self, Receiver, TryRecvError};
+use std::sync::Arc;
+use std::thread;
+
+pub enum LlmEvent {
+    Token(String),
+    Finished(Result<String>),
+}
+
+// An LLM request running on a background thread. The UI loop collects its
+// events with `poll`; dropping the job cancels the request.
+pub struct LlmJob {
+    model: String,
+    events: Receiver<LlmEvent>,
+    cancelled: Arc<AtomicBool>,
+}
+
+impl LlmJob {
+    pub fn spawn(config: &LlmConfig, prompt: String) -> Self {
+        let backend = backend_from_config(config);
+        let model = backend.model().to_string();
+        let (sender, events) = mpsc::channel();
+        let cancelled = Arc::new(AtomicBool::new(false));
+
+        let flag = Arc::clone(&cancelled);
+        thread::spawn(move || {
+            let result = backend.generate(&prompt, &mut |token| {
+                let stop = flag.load(Ordering::Relaxed)
+                    || sender.send(LlmEvent::Token(token.to_string())).is_err();
+                if stop {
+                    ControlFlow::Break(())
+                } else {
+                    ControlFlow::Continue(())
+                }
+            });
+            let _ = sender.send(LlmEvent::Finished(result));
+        });
+
+        Self {
+            model,
+            events,
+            cancelled,
+        }
+    }
+
+    pub fn model(&self) -> &str {
+        &self.model
+    }
+
+    // Stops the request at the next token. A request still waiting for the
+    // server's first response finishes in the background and is discarded.
+    pub fn cancel(&self) {
+        self.cancelled.store(true, Ordering::Relaxed);
+    }
+
+    // Events received since the last call, without blocking
+    pub fn poll(&self) -> Vec<LlmEvent> {
+        let mut events = Vec::new();
+        loop {
+            match self.events.try_recv() {
+                Ok(event) => events.push(event),
+                Err(TryRecvError::Empty) => break,
+                Err(TryRecvError::Disconnected) => {
+                    // The worker went away without reporting back (it panicked)
+                    if !matches!(events.last(), Some(LlmEvent::Finished(_))) {
+                        events.push(LlmEvent::Finished(Err(anyhow!(
+                            "The LLM worker stopped unexpectedly"
+                        ))));
+                    }
+                    break;
+                }
+            }
+        }
+        events
+    }
+}
+
+impl Drop for LlmJob {
+    fn drop(&mut self) {
+        self.cancel();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::LlmBackendKind;
+    use crate::llm::{serve_once, test_config, Cancelled};
+    use std::io::Write;
+    use std::time::{Duration, Instant};
+
+    // Polls the job until `until` accepts an event; returns the events up to it
+    fn wait_for(job: &LlmJob, mut until: impl FnMut(&LlmEvent) -> bool) -> Vec<LlmEvent> {
+        let started = Instant::now();
+        let mut seen = Vec::new();
+        while started.elapsed() < Duration::from_secs(5) {
+            for event in job.poll() {
+                let done = until(&event);
+                seen.push(event);
+                if done {
+                    return seen;
+                }
+            }
+            thread::sleep(Duration::from_millis(5));
+        }
+        panic!("the LLM job didn't get there in time");
+    }
+
+    #[test]
+    fn cancelling_stops_at_the_next_token() {
+        let (resume, paused) = mpsc::channel::<()>();
+        let (base_url, server) = serve_once(move |stream| {
+            let _ = write!(
+                stream,
+                "HTTP/1.1 200 OK\r\nConnection: close\r\n\r\n{{\"response\":\"Dear \"}}\n"
+            );
+            let _ = stream.flush();
+            // The rest is sent once the job was cancelled
+            let _ = paused.recv();
+            let _ = write!(
+                stream,
+                "{{\"response\":\"diary\"}}\n{{\"response\":\"\",\"done\":true}}\n"
+            );
+        });
+
+        let job = LlmJob::spawn(
+            &test_config(LlmBackendKind::Ollama, &base_url),
+            "Tidy this".to_string(),
+        );
+        assert_eq!(job.model(), "test-model");
+        let events = wait_for(&job, |event| matches!(event, LlmEvent::Token(_)));
+        assert!(matches!(events.last(), Some(LlmEvent::Token(token)) if token == "Dear "));
+
+        job.cancel();
+        resume.send(()).unwrap();
+        let events = wait_for(&job, |event| matches!(event, LlmEvent::Finished(_)));
+        assert_eq!(events.len(), 1);
+        let Some(LlmEvent::Finished(Err(error))) = events.last() else {
+            panic!("expected a cancelled request");
+        };
+        assert!(error.is::<Cancelled>());
+        server.join().unwrap();
+    }
+}
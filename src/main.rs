@@ -1,48 +1,136 @@
 mod app;
+mod cli;
 mod commands;
 mod config;
+mod llm;
+mod search;
+mod stats;
+mod storage;
 mod ui;
 mod utils;
 
 use anyhow::Result;
 use app::App;
-use crossterm::{
-    event::{DisableMouseCapture, EnableMouseCapture},
-    execute,
-    terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen},
-};
+use cli::Command;
+use colored::Colorize;
 use ratatui::{backend::CrosstermBackend, Terminal};
-use std::{env, io};
+use std::io::{self, IsTerminal};
+use std::{env, process};
+use storage::TagFilter;
+
+// Exit statuses of the command line, documented in --help and the README
+const EXIT_FAILURE: i32 = 1;
+const EXIT_USAGE: i32 = 2;
+const EXIT_NO_MATCH: i32 = 3;
 
 fn main() -> Result<()> {
-    let args: Vec<String> = env::args().collect();
-
-    // Check for command line arguments for backward compatibility
-    if args.len() >= 2 {
-        match args[1].as_str() {
-            "-h" | "--help" => {
-                commands::exe_help();
-                return Ok(());
-            }
-            "-aj" | "--add-journal" => {
-                commands::exe_add_journal();
-                return Ok(());
-            }
-            "--tui" | "-t" => {
-                // Explicit TUI mode
-            }
-            _ => {
-                println!("Unknown command. Use --tui for TUI mode or -h for help.");
-                return Ok(());
-            }
+    let args: Vec<String> = env::args().skip(1).collect();
+    let command = match cli::parse(&args) {
+        Ok(command) => command,
+        Err(e) => {
+            eprintln!("{} {}", "Error:".red(), e);
+            eprintln!("Run `rusty_diary --help` for usage.");
+            process::exit(EXIT_USAGE);
+        }
+    };
+
+    // Keep piped output free of color codes
+    if !io::stdout().is_terminal() {
+        colored::control::set_override(false);
+    }
+
+    if command.reads_journal() {
+        if let Err(e) = commands::unlock_journal() {
+            eprintln!("{} {}", "Error:".red(), e);
+            process::exit(EXIT_FAILURE);
+        }
+    }
+
+    let result = match command {
+        Command::Tui => return run_tui(),
+        Command::Help => {
+            commands::exe_help();
+            Ok(())
         }
+        Command::AddJournal { date } => commands::exe_add_journal(date.as_deref()),
+        Command::Add {
+            text,
+            file,
+            date,
+            prompt,
+            raw,
+            mood,
+            energy,
+        } => commands::exe_add(
+            text,
+            file.as_deref(),
+            date.as_deref(),
+            prompt.as_deref(),
+            raw,
+            mood.as_deref(),
+            energy.as_deref(),
+        ),
+        Command::List {
+            from,
+            to,
+            limit,
+            tags,
+            any_tag,
+            output,
+        } => {
+            let filter = TagFilter {
+                labels: tags.iter().map(|tag| storage::tag_label(tag)).collect(),
+                match_any: any_tag,
+            };
+            commands::exe_list(from.as_deref(), to.as_deref(), limit, &filter, output)
+        }
+        Command::Show { date, id, output } => commands::exe_show(&date, id.as_deref(), output),
+        Command::Search {
+            query,
+            limit,
+            output,
+        } => commands::exe_search(&query, limit, output),
+        Command::Edit { date, entry_date } => {
+            commands::exe_editor(date.as_deref(), entry_date.as_deref())
+        }
+        Command::Delete { date, id, all } => commands::exe_delete(&date, id.as_deref(), all),
+        Command::Export { format, output } => {
+            commands::exe_export(format.as_deref(), output.as_deref())
+        }
+        Command::Stats { output } => commands::exe_stats(output),
+        Command::ConfigGet { key } => commands::exe_config_get(key.as_deref()),
+        Command::ConfigSet { key, value } => commands::exe_config_set(&key, &value),
+        Command::ProcessPending => commands::exe_process_pending(),
+        Command::MigrateFormat { to, from } => {
+            commands::exe_migrate_format(to.as_deref(), from.as_deref())
+        }
+        Command::Encrypt => commands::exe_encrypt(),
+        Command::Decrypt => commands::exe_decrypt(),
+        Command::ChangePassphrase => commands::exe_change_passphrase(),
+        Command::Backup { output } => commands::exe_backup(output.as_deref()),
+        Command::Restore { archive } => commands::exe_restore(archive.as_deref()),
+    };
+
+    if let Err(e) = result {
+        // The reader went away, as with `| head`; nothing is left to report
+        if e.downcast_ref::<io::Error>()
+            .is_some_and(|e| e.kind() == io::ErrorKind::BrokenPipe)
+        {
+            return Ok(());
+        }
+        eprintln!("{} {}", "Error:".red(), e);
+        if e.is::<commands::NoMatch>() {
+            process::exit(EXIT_NO_MATCH);
+        }
+        process::exit(EXIT_FAILURE);
     }
+    Ok(())
+}
 
+fn run_tui() -> Result<()> {
     // Setup terminal
-    enable_raw_mode()?;
-    let mut stdout = io::stdout();
-    execute!(stdout, EnterAlternateScreen, EnableMouseCapture)?;
-    let backend = CrosstermBackend::new(stdout);
+    ui::enter_terminal()?;
+    let backend = CrosstermBackend::new(io::stdout());
     let mut terminal = Terminal::new(backend)?;
 
     // Create app and run TUI
@@ -50,13 +138,7 @@ fn main() -> Result<()> {
     let result = app.run(&mut terminal);
 
     // Restore terminal
-    disable_raw_mode()?;
-    execute!(
-        terminal.backend_mut(),
-        LeaveAlternateScreen,
-        DisableMouseCapture
-    )?;
-    terminal.show_cursor()?;
+    ui::leave_terminal()?;
 
     result
 }
@@ -0,0 +1,511 @@
+use super::SearchQuery;
+use crate::config::Config;
+use crate::storage::{self, DayFile};
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::UNIX_EPOCH;
+
+const INDEX_VERSION: u32 = 1;
+
+// BM25 tuning constants
+const K1: f64 = 1.2;
+const B: f64 = 0.75;
+
+const SNIPPET_CONTEXT: usize = 40;
+
+// An inverted index over every entry in the journal directory, stored in
+// `.rusty_diary/index.json` (encrypted like the day files in an encrypted
+// journal). Day files are re-indexed when their modification time or size
+// changes.
+#[derive(Default, Serialize, Deserialize)]
+pub struct SearchIndex {
+    version: u32,
+    next_document: u32,
+    files: BTreeMap<String, IndexedFile>,
+    documents: BTreeMap<u32, IndexedDocument>,
+    postings: BTreeMap<String, Vec<(u32, u32)>>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct IndexedFile {
+    modified: (u64, u32),
+    size: u64,
+    documents: Vec<u32>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct IndexedDocument {
+    entry_id: String,
+    filename: String,
+    date: String,
+    length: u32,
+}
+
+pub struct SearchHit {
+    pub entry_id: String,
+    pub filename: String,
+    pub score: f64,
+    pub snippet: String,
+}
+
+impl SearchIndex {
+    pub fn path(config: &Config) -> PathBuf {
+        let path = config
+            .journal_directory
+            .join(".rusty_diary")
+            .join("index.json");
+        if storage::is_encrypted(config) {
+            storage::encrypted_path(&path)
+        } else {
+            path
+        }
+    }
+
+    // Deletes the stored index in either form; it is rebuilt by the next search
+    pub fn delete(config: &Config) -> Result<()> {
+        let path = storage::decrypted_path(&Self::path(config));
+        for path in [storage::encrypted_path(&path), path] {
+            if path.exists() {
+                fs::remove_file(path)?;
+            }
+        }
+        Ok(())
+    }
+
+    // Loads the index, bringing it up to date with the journal directory
+    pub fn open(config: &Config) -> Result<Self> {
+        let mut index = storage::read_file(&Self::path(config))
+            .ok()
+            .and_then(|content| serde_json::from_str::<Self>(&content).ok())
+            .filter(|index| index.version == INDEX_VERSION)
+            .unwrap_or_else(|| Self {
+                version: INDEX_VERSION,
+                ..Self::default()
+            });
+
+        if index.refresh(config)? {
+            index.save(config)?;
+        }
+        Ok(index)
+    }
+
+    pub fn save(&self, config: &Config) -> Result<()> {
+        let path = Self::path(config);
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        storage::write_file(&path, &serde_json::to_string(self)?)
+    }
+
+    // Re-indexes new and changed day files and drops deleted ones. Returns
+    // whether anything changed.
+    pub fn refresh(&mut self, config: &Config) -> Result<bool> {
+        let day_files = storage::list_day_files(config)?;
+        let mut changed = false;
+
+        let current: BTreeSet<String> = day_files.iter().map(file_key).collect();
+        let removed: Vec<String> = self
+            .files
+            .keys()
+            .filter(|filename| !current.contains(*filename))
+            .cloned()
+            .collect();
+        for filename in removed {
+            self.remove_file(&filename);
+            changed = true;
+        }
+
+        for day_file in &day_files {
+            let (modified, size) = file_stamp(&day_file.path)?;
+            let up_to_date = self
+                .files
+                .get(&file_key(day_file))
+                .is_some_and(|file| file.modified == modified && file.size == size);
+
+            if !up_to_date {
+                self.index_file(day_file)?;
+                changed = true;
+            }
+        }
+
+        Ok(changed)
+    }
+
+    pub fn index_file(&mut self, day_file: &DayFile) -> Result<()> {
+        let filename = file_key(day_file);
+        self.remove_file(&filename);
+
+        let (modified, size) = file_stamp(&day_file.path)?;
+        let mut documents = Vec::new();
+
+        for entry in day_file.read()? {
+            let document = self.next_document;
+            self.next_document += 1;
+
+            let terms = term_frequencies(&entry.body);
+            for (term, frequency) in &terms {
+                self.postings
+                    .entry(term.clone())
+                    .or_default()
+                    .push((document, *frequency));
+            }
+
+            self.documents.insert(
+                document,
+                IndexedDocument {
+                    entry_id: entry.id,
+                    filename: filename.clone(),
+                    date: entry.date,
+                    length: terms.values().sum(),
+                },
+            );
+            documents.push(document);
+        }
+
+        self.files.insert(
+            filename,
+            IndexedFile {
+                modified,
+                size,
+                documents,
+            },
+        );
+        Ok(())
+    }
+
+    // Ranked (BM25) search. The index narrows down and scores candidates; the
+    // candidate entries are then read back to check phrases and build snippets.
+    pub fn search(&self, query: &SearchQuery, config: &Config) -> Result<Vec<SearchHit>> {
+        if query.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let mut scores: HashMap<u32, f64> = HashMap::new();
+        if query.alternatives().is_empty() {
+            // Only exclusions: everything else matches equally
+            scores.extend(self.documents.keys().map(|&document| (document, 0.0)));
+        }
+
+        for terms in query.alternatives() {
+            let mut candidates: Option<HashMap<u32, f64>> = None;
+
+            for term in terms {
+                let mut term_scores: HashMap<u32, f64> = HashMap::new();
+                let tokens = tokenize(term);
+                let last = tokens.len().saturating_sub(1);
+
+                for (position, token) in tokens.iter().enumerate() {
+                    // The last word of a term may still be being typed
+                    let token_scores = self.score_token(token, position == last);
+                    term_scores = if position == 0 {
+                        token_scores
+                    } else {
+                        term_scores
+                            .into_iter()
+                            .filter_map(|(document, score)| {
+                                token_scores
+                                    .get(&document)
+                                    .map(|extra| (document, score + extra))
+                            })
+                            .collect()
+                    };
+                }
+
+                candidates = Some(match candidates {
+                    None => term_scores,
+                    Some(candidates) => candidates
+                        .into_iter()
+                        .filter_map(|(document, score)| {
+                            term_scores
+                                .get(&document)
+                                .map(|extra| (document, score + extra))
+                        })
+                        .collect(),
+                });
+            }
+
+            for (document, score) in candidates.unwrap_or_default() {
+                let best = scores.entry(document).or_insert(score);
+                *best = best.max(score);
+            }
+        }
+
+        // Read candidate entries back, one file at a time
+        let mut by_file: BTreeMap<&str, Vec<(u32, f64)>> = BTreeMap::new();
+        for (&document, &score) in &scores {
+            if let Some(indexed) = self.documents.get(&document) {
+                by_file
+                    .entry(indexed.filename.as_str())
+                    .or_default()
+                    .push((document, score));
+            }
+        }
+
+        let day_files: HashMap<String, DayFile> = storage::list_day_files(config)?
+            .into_iter()
+            .map(|day_file| (file_key(&day_file), day_file))
+            .collect();
+
+        let mut hits = Vec::new();
+        for (filename, documents) in by_file {
+            let entries = match day_files.get(filename) {
+                Some(day_file) => day_file.read()?,
+                None => continue,
+            };
+
+            for (document, score) in documents {
+                let indexed = &self.documents[&document];
+                let Some(entry) = entries.iter().find(|entry| entry.id == indexed.entry_id) else {
+                    continue;
+                };
+                if !query.matches(&entry.body) {
+                    continue;
+                }
+
+                hits.push(SearchHit {
+                    entry_id: indexed.entry_id.clone(),
+                    filename: indexed.filename.clone(),
+                    score,
+                    snippet: snippet(&entry.body, query),
+                });
+            }
+        }
+
+        // Entry ids start with their timestamp, so ties go to the newest entry
+        hits.sort_by(|a, b| {
+            b.score
+                .total_cmp(&a.score)
+                .then_with(|| b.entry_id.cmp(&a.entry_id))
+        });
+        Ok(hits)
+    }
+
+    fn score_token(&self, token: &str, prefix: bool) -> HashMap<u32, f64> {
+        let mut scores = HashMap::new();
+        if self.documents.is_empty() {
+            return scores;
+        }
+
+        let document_count = self.documents.len() as f64;
+        let average_length = self
+            .documents
+            .values()
+            .map(|document| document.length as f64)
+            .sum::<f64>()
+            / document_count;
+
+        let matching = self
+            .postings
+            .range(token.to_string()..)
+            .take_while(|(term, _)| {
+                if prefix {
+                    term.starts_with(token)
+                } else {
+                    term.as_str() == token
+                }
+            });
+
+        for (_, postings) in matching {
+            let document_frequency = postings.len() as f64;
+            let idf = (1.0
+                + (document_count - document_frequency + 0.5) / (document_frequency + 0.5))
+                .ln();
+
+            for &(document, frequency) in postings {
+                let length = self.documents[&document].length as f64;
+                let frequency = frequency as f64;
+                let score = idf * frequency * (K1 + 1.0)
+                    / (frequency + K1 * (1.0 - B + B * length / average_length.max(1.0)));
+                *scores.entry(document).or_insert(0.0) += score;
+            }
+        }
+
+        scores
+    }
+
+    fn remove_file(&mut self, filename: &str) {
+        let Some(file) = self.files.remove(filename) else {
+            return;
+        };
+        let removed: BTreeSet<u32> = file.documents.into_iter().collect();
+
+        for document in &removed {
+            self.documents.remove(document);
+        }
+        self.postings.retain(|_, postings| {
+            postings.retain(|(document, _)| !removed.contains(document));
+            !postings.is_empty()
+        });
+    }
+}
+
+// Re-indexes a single day file after it has been written. The index is left
+// alone when it doesn't exist yet; the first search builds it.
+pub fn update_index(config: &Config, path: &Path) -> Result<()> {
+    if !SearchIndex::path(config).exists() {
+        return Ok(());
+    }
+
+    let Some(day_file) = storage::list_day_files(config)?
+        .into_iter()
+        .find(|day_file| day_file.path == path)
+    else {
+        return Ok(());
+    };
+
+    let mut index = SearchIndex::open(config)?;
+    index.index_file(&day_file)?;
+    index.save(config)
+}
+
+fn file_key(day_file: &DayFile) -> String {
+    day_file
+        .path
+        .file_name()
+        .map(|name| name.to_string_lossy().to_string())
+        .unwrap_or_default()
+}
+
+fn file_stamp(path: &Path) -> Result<((u64, u32), u64)> {
+    let metadata = fs::metadata(path)?;
+    let modified = metadata.modified()?.duration_since(UNIX_EPOCH)?;
+    Ok((
+        (modified.as_secs(), modified.subsec_nanos()),
+        metadata.len(),
+    ))
+}
+
+fn tokenize(text: &str) -> Vec<String> {
+    text.split(|c: char| !c.is_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .map(str::to_lowercase)
+        .collect()
+}
+
+fn term_frequencies(text: &str) -> BTreeMap<String, u32> {
+    let mut frequencies = BTreeMap::new();
+    for token in tokenize(text) {
+        *frequencies.entry(token).or_insert(0) += 1;
+    }
+    frequencies
+}
+
+// A single line of context around the first match
+fn snippet(text: &str, query: &SearchQuery) -> String {
+    let flat = text.split_whitespace().collect::<Vec<_>>().join(" ");
+    let Some(&(start, end)) = query.match_ranges(&flat).first() else {
+        return flat.chars().take(SNIPPET_CONTEXT * 2).collect();
+    };
+
+    let before: String = flat[..start]
+        .chars()
+        .rev()
+        .take(SNIPPET_CONTEXT)
+        .collect::<Vec<_>>()
+        .into_iter()
+        .rev()
+        .collect();
+    let after: String = flat[end..].chars().take(SNIPPET_CONTEXT).collect();
+
+    format!(
+        "{}{}{}{}{}",
+        if before.len() < start { "…" } else { "" },
+        before,
+        &flat[start..end],
+        after,
+        if end + after.len() < flat.len() {
+            "…"
+        } else {
+            ""
+        }
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::TempJournal;
+
+    fn hit_bodies(journal: &TempJournal, query: &str) -> Vec<String> {
+        let index = SearchIndex::open(&journal.config).unwrap();
+        let hits = index
+            .search(&SearchQuery::parse(query), &journal.config)
+            .unwrap();
+        let entries = storage::read_day(
+            &journal.config,
+            time::Date::from_calendar_date(2024, time::Month::May, 4).unwrap(),
+        )
+        .unwrap();
+        hits.iter()
+            .filter_map(|hit| entries.iter().find(|entry| entry.id == hit.entry_id))
+            .map(|entry| entry.body.clone())
+            .collect()
+    }
+
+    #[test]
+    fn tokens_are_lowercased_words() {
+        assert_eq!(
+            tokenize("Cold-water swim, 6AM!"),
+            ["cold", "water", "swim", "6am"]
+        );
+
+        let frequencies = term_frequencies("Lake lake LAKE heron");
+        assert_eq!(frequencies.get("lake"), Some(&3));
+        assert_eq!(frequencies.get("heron"), Some(&1));
+    }
+
+    #[test]
+    fn entries_with_more_matches_rank_first() {
+        let journal = TempJournal::new("index-rank");
+        journal.add(6, "Walked to the lake and home");
+        journal.add(7, "Lake swim, then back to the lake");
+        journal.add(8, "Stayed in and read");
+
+        assert_eq!(
+            hit_bodies(&journal, "lake"),
+            [
+                "Lake swim, then back to the lake",
+                "Walked to the lake and home"
+            ]
+        );
+        assert!(hit_bodies(&journal, "heron").is_empty());
+    }
+
+    #[test]
+    fn last_word_matches_as_a_prefix_and_exclusions_apply() {
+        let journal = TempJournal::new("index-prefix");
+        journal.add(6, "Walked to the lake and home");
+        journal.add(7, "Lake swim, then back to the lake");
+        journal.add(8, "Stayed in and read");
+
+        assert_eq!(
+            hit_bodies(&journal, "swi"),
+            ["Lake swim, then back to the lake"]
+        );
+        assert_eq!(
+            hit_bodies(&journal, "lake -swim"),
+            ["Walked to the lake and home"]
+        );
+        assert_eq!(hit_bodies(&journal, "-lake"), ["Stayed in and read"]);
+    }
+
+    #[test]
+    fn changed_and_deleted_files_are_reindexed() {
+        let journal = TempJournal::new("index-refresh");
+        journal.add(6, "Walked to the lake");
+        assert!(hit_bodies(&journal, "heron").is_empty());
+
+        journal.add(7, "A heron by the water");
+        assert_eq!(hit_bodies(&journal, "heron"), ["A heron by the water"]);
+
+        let mut index = SearchIndex::open(&journal.config).unwrap();
+        for day_file in storage::list_day_files(&journal.config).unwrap() {
+            fs::remove_file(&day_file.path).unwrap();
+        }
+        assert!(index.refresh(&journal.config).unwrap());
+        assert!(index.documents.is_empty());
+    }
+}
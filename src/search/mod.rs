@@ -0,0 +1,5 @@
+mod index;
+mod query;
+
+pub use index::*;
+pub use query::*;
@@ -0,0 +1,218 @@
+// A parsed search query. Whitespace-separated terms must all match (an explicit
+// AND is allowed), OR separates alternatives, "quoted text" is matched as a
+// phrase and a leading '-' excludes entries containing the term. Matching is
+// case-insensitive.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct SearchQuery {
+    alternatives: Vec<Vec<String>>,
+    excluded: Vec<String>,
+}
+
+impl SearchQuery {
+    pub fn parse(input: &str) -> Self {
+        let mut query = Self::default();
+        let mut current = Vec::new();
+
+        for (token, quoted) in tokenize(input) {
+            if !quoted && token == "OR" {
+                if !current.is_empty() {
+                    query.alternatives.push(std::mem::take(&mut current));
+                }
+                continue;
+            }
+            if !quoted && token == "AND" {
+                continue;
+            }
+
+            let (excluded, term) = match token.strip_prefix('-') {
+                Some(term) if !quoted => (true, term.to_string()),
+                _ => (false, token),
+            };
+            let term = term.to_lowercase();
+            if term.is_empty() {
+                continue;
+            }
+
+            if excluded {
+                query.excluded.push(term);
+            } else {
+                current.push(term);
+            }
+        }
+
+        if !current.is_empty() {
+            query.alternatives.push(current);
+        }
+
+        query
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.alternatives.is_empty() && self.excluded.is_empty()
+    }
+
+    pub fn matches(&self, text: &str) -> bool {
+        if self.is_empty() {
+            return false;
+        }
+
+        let text = text.to_lowercase();
+        if self
+            .excluded
+            .iter()
+            .any(|term| text.contains(term.as_str()))
+        {
+            return false;
+        }
+
+        // A query made only of exclusions matches everything else
+        self.alternatives.is_empty()
+            || self
+                .alternatives
+                .iter()
+                .any(|terms| terms.iter().all(|term| text.contains(term.as_str())))
+    }
+
+    pub fn alternatives(&self) -> &[Vec<String>] {
+        &self.alternatives
+    }
+
+    // Terms worth highlighting: everything that isn't excluded
+    pub fn terms(&self) -> impl Iterator<Item = &str> {
+        self.alternatives.iter().flatten().map(String::as_str)
+    }
+
+    // Byte ranges of `text` covered by query terms, sorted and merged
+    pub fn match_ranges(&self, text: &str) -> Vec<(usize, usize)> {
+        // Lowercasing can change byte lengths, so remember where each lowered
+        // byte came from in the original text.
+        let mut lowered = String::with_capacity(text.len());
+        let mut origins = Vec::with_capacity(text.len());
+        for (index, c) in text.char_indices() {
+            for lower in c.to_lowercase() {
+                lowered.push(lower);
+                origins.extend(std::iter::repeat_n(index, lower.len_utf8()));
+            }
+        }
+        origins.push(text.len());
+
+        let mut ranges: Vec<(usize, usize)> = Vec::new();
+        for term in self.terms() {
+            for (start, matched) in lowered.match_indices(term) {
+                let end = start + matched.len();
+                let original_end = text[origins[end - 1]..]
+                    .chars()
+                    .next()
+                    .map_or(text.len(), |c| origins[end - 1] + c.len_utf8());
+                ranges.push((origins[start], original_end));
+            }
+        }
+
+        ranges.sort_unstable();
+        let mut merged: Vec<(usize, usize)> = Vec::with_capacity(ranges.len());
+        for (start, end) in ranges {
+            match merged.last_mut() {
+                Some(last) if start <= last.1 => last.1 = last.1.max(end),
+                _ => merged.push((start, end)),
+            }
+        }
+        merged
+    }
+}
+
+// Splits the input on whitespace, keeping "quoted phrases" together. The flag
+// tells whether the token was quoted.
+fn tokenize(input: &str) -> Vec<(String, bool)> {
+    let mut tokens = Vec::new();
+    let mut chars = input.chars().peekable();
+
+    while let Some(&c) = chars.peek() {
+        if c.is_whitespace() {
+            chars.next();
+            continue;
+        }
+
+        // -"phrase" excludes a whole phrase
+        let negated = c == '-' && {
+            let mut lookahead = chars.clone();
+            lookahead.next();
+            lookahead.peek() == Some(&'"')
+        };
+        if negated {
+            chars.next();
+        }
+
+        if chars.peek() == Some(&'"') {
+            chars.next();
+            let phrase: String = chars.by_ref().take_while(|&c| c != '"').collect();
+            let phrase = phrase.trim().to_string();
+            if negated {
+                tokens.push((format!("-{}", phrase), false));
+            } else {
+                tokens.push((phrase, true));
+            }
+            continue;
+        }
+
+        let mut token = String::new();
+        while let Some(c) = chars.next_if(|c| !c.is_whitespace()) {
+            token.push(c);
+        }
+        tokens.push((token, false));
+    }
+
+    tokens
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_terms_phrases_alternatives_and_exclusions() {
+        let query = SearchQuery::parse(r#"Lake AND "cold water" OR heron -"rainy day" -boat"#);
+        assert_eq!(
+            query.alternatives(),
+            [
+                vec!["lake".to_string(), "cold water".to_string()],
+                vec!["heron".to_string()]
+            ]
+        );
+        assert_eq!(query.excluded, ["rainy day", "boat"]);
+    }
+
+    #[test]
+    fn matches_all_terms_of_any_alternative() {
+        let query = SearchQuery::parse("lake swim OR heron -boat");
+        assert!(query.matches("A cold SWIM in the lake"));
+        assert!(query.matches("A heron on the shore"));
+        assert!(!query.matches("A lake without swimming... the boat"));
+        assert!(!query.matches("Just the lake"));
+
+        let exclusions = SearchQuery::parse("-work");
+        assert!(exclusions.matches("A quiet day"));
+        assert!(!exclusions.matches("Long day at work"));
+
+        assert!(SearchQuery::parse("  ").is_empty());
+        assert!(!SearchQuery::parse("").matches("anything"));
+    }
+
+    #[test]
+    fn match_ranges_are_merged_byte_ranges_of_the_original_text() {
+        let query = SearchQuery::parse("lake ke");
+        assert_eq!(query.match_ranges("Lake, lake"), [(0, 4), (6, 10)]);
+
+        // "É" is two bytes, and its lowercase form too
+        let query = SearchQuery::parse("été");
+        let text = "Un ÉTÉ chaud";
+        let ranges = query.match_ranges(text);
+        assert_eq!(ranges, [(3, 8)]);
+        assert_eq!(&text[ranges[0].0..ranges[0].1], "ÉTÉ");
+
+        // Lowercasing 'İ' adds a byte; ranges still land on the original
+        let query = SearchQuery::parse("x");
+        let text = "İx";
+        let ranges = query.match_ranges(text);
+        assert_eq!(&text[ranges[0].0..ranges[0].1], "x");
+    }
+}
@@ -0,0 +1,379 @@
+use crate::storage::JournalEntry;
+use serde::{Serialize, Serializer};
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+use time::Date;
+
+// Number of most frequent words reported
+pub const TOP_WORDS: usize = 10;
+
+// Number of recent months with a word count
+const MONTHS: usize = 12;
+
+// Words too common to say anything about an entry
+const STOP_WORDS: &[&str] = &[
+    "a", "about", "after", "again", "all", "also", "am", "an", "and", "any", "are", "as", "at",
+    "back", "be", "because", "been", "before", "being", "but", "by", "can", "could", "day", "did",
+    "do", "does", "doing", "don", "down", "each", "even", "few", "for", "from", "get", "got",
+    "had", "has", "have", "having", "he", "her", "here", "hers", "him", "his", "how", "i", "if",
+    "in", "into", "is", "it", "its", "just", "like", "ll", "me", "more", "most", "much", "my",
+    "no", "not", "now", "of", "off", "on", "one", "only", "or", "other", "our", "out", "over",
+    "really", "re", "s", "said", "same", "she", "should", "so", "some", "still", "such", "t",
+    "than", "that", "the", "their", "them", "then", "there", "these", "they", "this", "those",
+    "through", "to", "today", "too", "under", "up", "ve", "very", "was", "we", "well", "went",
+    "were", "what", "when", "where", "which", "while", "who", "why", "will", "with", "would",
+    "you", "your",
+];
+
+// Writing statistics of a set of entries, as of `today`. Dates are written
+// as YYYY-MM-DD.
+#[derive(Debug, Serialize)]
+pub struct JournalStats {
+    pub entries: usize,
+    pub days_written: usize,
+    pub words: usize,
+    #[serde(serialize_with = "iso_date")]
+    pub first_day: Option<Date>,
+    #[serde(serialize_with = "iso_date")]
+    pub last_day: Option<Date>,
+    // Words per entry
+    pub average_words: f64,
+    // Averages over the days, weeks and months from the first entry to today
+    pub words_per_day: f64,
+    pub words_per_week: f64,
+    pub words_per_month: f64,
+    // Days in a row with at least one entry, ending today or yesterday
+    pub current_streak: Streak,
+    pub longest_streak: Streak,
+    // Hour of the day, in the time zone each entry was written in, with the
+    // most entries; entries without a timestamp aren't counted
+    pub most_active_hour: Option<u8>,
+    // Entries written in each hour of the day
+    pub hours: [usize; 24],
+    // Entries and words in each of the last twelve months, oldest first
+    pub months: Vec<MonthStats>,
+    pub top_words: Vec<WordCount>,
+}
+
+#[derive(Debug, Default, Clone, Copy, Serialize)]
+pub struct Streak {
+    pub days: usize,
+    #[serde(serialize_with = "iso_date")]
+    pub start: Option<Date>,
+    #[serde(serialize_with = "iso_date")]
+    pub end: Option<Date>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct MonthStats {
+    // "2024-05"
+    pub month: String,
+    pub entries: usize,
+    pub words: usize,
+}
+
+#[derive(Debug, Serialize)]
+pub struct WordCount {
+    pub word: String,
+    pub count: usize,
+}
+
+impl JournalStats {
+    pub fn new(entries: &[JournalEntry], today: Date) -> Self {
+        let days: BTreeSet<Date> = entries.iter().map(|entry| entry.day).collect();
+        let words: usize = entries.iter().map(JournalEntry::word_count).sum();
+        let first_day = days.first().copied();
+
+        // The span counts at least one day, week and month so a new journal
+        // doesn't report more words per week than it has
+        let span_days = first_day.map_or(0, |first| (today - first).whole_days().max(0) + 1) as f64;
+        let per = |period: f64| {
+            if entries.is_empty() {
+                0.0
+            } else {
+                words as f64 / (span_days / period).max(1.0)
+            }
+        };
+
+        let mut hours = [0; 24];
+        for timestamp in entries.iter().filter_map(JournalEntry::timestamp) {
+            hours[timestamp.hour() as usize] += 1;
+        }
+        let most_active_hour = (0..24u8)
+            .filter(|hour| hours[*hour as usize] > 0)
+            .max_by_key(|hour| (hours[*hour as usize], std::cmp::Reverse(*hour)));
+
+        let (current_streak, longest_streak) = streaks(&days, today);
+
+        Self {
+            entries: entries.len(),
+            days_written: days.len(),
+            words,
+            first_day,
+            last_day: days.last().copied(),
+            average_words: if entries.is_empty() {
+                0.0
+            } else {
+                words as f64 / entries.len() as f64
+            },
+            words_per_day: per(1.0),
+            words_per_week: per(7.0),
+            words_per_month: per(365.25 / 12.0),
+            current_streak,
+            longest_streak,
+            most_active_hour,
+            hours,
+            months: months(entries, today),
+            top_words: top_words(entries, TOP_WORDS),
+        }
+    }
+}
+
+// The streak reaching today (or yesterday, while today can still be written),
+// and the longest one (the latest of equally long ones)
+fn streaks(days: &BTreeSet<Date>, today: Date) -> (Streak, Streak) {
+    let mut current = Streak::default();
+    let mut longest = Streak::default();
+    let mut run = Streak::default();
+
+    for &day in days {
+        let continues = run.end.and_then(Date::next_day) == Some(day);
+        if !continues {
+            run = Streak {
+                days: 0,
+                start: Some(day),
+                end: None,
+            };
+        }
+        run.days += 1;
+        run.end = Some(day);
+        if run.days >= longest.days {
+            longest = run;
+        }
+    }
+
+    if run.end == Some(today) || run.end == today.previous_day() {
+        current = run;
+    }
+    (current, longest)
+}
+
+fn months(entries: &[JournalEntry], today: Date) -> Vec<MonthStats> {
+    let mut months: BTreeMap<(i32, u8), (usize, usize)> = BTreeMap::new();
+    let (mut year, mut month) = (today.year(), today.month() as u8);
+    for _ in 0..MONTHS {
+        months.insert((year, month), (0, 0));
+        (year, month) = if month == 1 {
+            (year - 1, 12)
+        } else {
+            (year, month - 1)
+        };
+    }
+
+    for entry in entries {
+        if let Some((count, words)) = months.get_mut(&(entry.day.year(), entry.day.month() as u8)) {
+            *count += 1;
+            *words += entry.word_count();
+        }
+    }
+
+    months
+        .into_iter()
+        .map(|((year, month), (entries, words))| MonthStats {
+            month: format!("{:04}-{:02}", year, month),
+            entries,
+            words,
+        })
+        .collect()
+}
+
+// The most used words of at least three letters, leaving out stop words,
+// numbers and tags
+fn top_words(entries: &[JournalEntry], limit: usize) -> Vec<WordCount> {
+    let stop_words: HashSet<&str> = STOP_WORDS.iter().copied().collect();
+    let mut counts: HashMap<String, usize> = HashMap::new();
+
+    for entry in entries {
+        for word in entry.body.split_whitespace() {
+            if word.starts_with(['#', '@']) {
+                continue;
+            }
+            let word = word.replace('’', "'");
+            for word in word.split(|c: char| !c.is_alphanumeric() && c != '\'') {
+                let word = word.trim_matches('\'').to_lowercase();
+                // Contractions are left out with the stop words they are made of
+                let word = word.strip_suffix("'s").unwrap_or(&word);
+                if word.chars().count() < 3
+                    || word.contains('\'')
+                    || !word.chars().any(char::is_alphabetic)
+                    || stop_words.contains(word)
+                {
+                    continue;
+                }
+                *counts.entry(word.to_string()).or_default() += 1;
+            }
+        }
+    }
+
+    let mut counts: Vec<(String, usize)> = counts.into_iter().collect();
+    counts.sort_by(|(a_word, a), (b_word, b)| b.cmp(a).then_with(|| a_word.cmp(b_word)));
+    counts
+        .into_iter()
+        .take(limit)
+        .map(|(word, count)| WordCount { word, count })
+        .collect()
+}
+
+fn iso_date<S: Serializer>(day: &Option<Date>, serializer: S) -> Result<S::Ok, S::Error> {
+    match day {
+        Some(day) => serializer.serialize_str(&day.to_string()),
+        None => serializer.serialize_none(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use time::{Month, UtcOffset};
+
+    fn date(year: i32, month: Month, day: u8) -> Date {
+        Date::from_calendar_date(year, month, day).unwrap()
+    }
+
+    fn may(day: u8) -> Date {
+        date(2024, Month::May, day)
+    }
+
+    fn days(days: &[u8]) -> BTreeSet<Date> {
+        days.iter().map(|&day| may(day)).collect()
+    }
+
+    fn streak(days: usize, start: u8, end: u8) -> (usize, Option<Date>, Option<Date>) {
+        (days, Some(may(start)), Some(may(end)))
+    }
+
+    fn parts(streak: Streak) -> (usize, Option<Date>, Option<Date>) {
+        (streak.days, streak.start, streak.end)
+    }
+
+    // An entry for the journal day `day`, written at `hour` in `offset`
+    fn entry(day: Date, hour: u8, offset: i8, body: &str) -> JournalEntry {
+        let created_at = day
+            .with_hms(hour, 0, 0)
+            .unwrap()
+            .assume_offset(UtcOffset::from_hms(offset, 0, 0).unwrap());
+        JournalEntry::new(&day.to_string(), day, created_at, body)
+    }
+
+    #[test]
+    fn the_current_streak_ends_today_or_yesterday() {
+        let (current, _) = streaks(&days(&[8, 9, 10]), may(10));
+        assert_eq!(parts(current), streak(3, 8, 10));
+
+        let (current, _) = streaks(&days(&[8, 9, 10]), may(11));
+        assert_eq!(parts(current), streak(3, 8, 10));
+
+        let (current, longest) = streaks(&days(&[8, 9, 10]), may(12));
+        assert_eq!(parts(current), (0, None, None));
+        assert_eq!(parts(longest), streak(3, 8, 10));
+    }
+
+    #[test]
+    fn gaps_end_a_streak() {
+        let (current, longest) = streaks(&days(&[1, 2, 3, 4, 6, 8, 9]), may(9));
+
+        assert_eq!(parts(current), streak(2, 8, 9));
+        assert_eq!(parts(longest), streak(4, 1, 4));
+    }
+
+    #[test]
+    fn the_latest_of_equally_long_streaks_is_the_longest() {
+        let (_, longest) = streaks(&days(&[1, 2, 5, 6, 20]), may(25));
+        assert_eq!(parts(longest), streak(2, 5, 6));
+
+        let (current, longest) = streaks(&BTreeSet::new(), may(25));
+        assert_eq!(parts(current), (0, None, None));
+        assert_eq!(parts(longest), (0, None, None));
+    }
+
+    #[test]
+    fn streaks_continue_across_months() {
+        let days = [date(2024, Month::April, 30), may(1)].into();
+
+        let (current, _) = streaks(&days, may(2));
+        assert_eq!(
+            parts(current),
+            (2, Some(date(2024, Month::April, 30)), Some(may(1)))
+        );
+    }
+
+    #[test]
+    fn entries_count_in_the_month_of_their_journal_day() {
+        let entries = [
+            // 04:00 on June 1 in UTC, still May 31 in the journal
+            entry(may(31), 20, -8, "Late shift"),
+            // 22:30 on May 31 in UTC, already June 1 in the journal
+            entry(
+                date(2024, Month::June, 1),
+                0,
+                2,
+                "Midnight snack with friends",
+            ),
+            // Older than the last twelve months
+            entry(date(2023, Month::June, 30), 12, 0, "Too old"),
+        ];
+
+        let months = months(&entries, date(2024, Month::June, 1));
+
+        assert_eq!(months.len(), 12);
+        assert_eq!(months[0].month, "2023-07");
+        let counts: Vec<(&str, usize, usize)> = months[10..]
+            .iter()
+            .map(|month| (month.month.as_str(), month.entries, month.words))
+            .collect();
+        assert_eq!(counts, [("2024-05", 1, 2), ("2024-06", 1, 4)]);
+        assert_eq!(months.iter().map(|month| month.entries).sum::<usize>(), 2);
+    }
+
+    #[test]
+    fn months_wrap_around_the_new_year() {
+        let months = months(&[], date(2025, Month::February, 10));
+
+        assert_eq!(months[0].month, "2024-03");
+        assert_eq!(months[9].month, "2024-12");
+        assert_eq!(months[11].month, "2025-02");
+    }
+
+    #[test]
+    fn top_words_fold_case_and_skip_stop_words() {
+        let entries = [
+            entry(
+                may(4),
+                8,
+                0,
+                "Coffee with Anna. COFFEE again, then the coffee shop.",
+            ),
+            entry(
+                may(5),
+                9,
+                0,
+                "Anna's garden: I really like it #garden @Anna 2024 it's ok",
+            ),
+        ];
+
+        let words: Vec<(String, usize)> = top_words(&entries, 10)
+            .into_iter()
+            .map(|word| (word.word, word.count))
+            .collect();
+
+        assert_eq!(
+            words,
+            [
+                ("coffee".to_string(), 3),
+                ("anna".to_string(), 2),
+                ("garden".to_string(), 1),
+                ("shop".to_string(), 1),
+            ]
+        );
+        assert_eq!(top_words(&entries, 1).len(), 1);
+    }
+}
@@ -0,0 +1,481 @@
+use super::{key_file_path, list_day_files, parse_file_name, trash_directory};
+use crate::config::Config;
+use anyhow::{anyhow, Result};
+use flate2::read::GzDecoder;
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use std::fs::{self, File};
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use time::{Duration, OffsetDateTime};
+
+const SNAPSHOT_DIRECTORY: &str = "snapshots";
+// Separates the time a snapshot was taken from the day file's name
+const NAME_SEPARATOR: &str = "__";
+
+const ARCHIVE_PREFIX: &str = "rusty_diary-";
+const ARCHIVE_EXTENSION: &str = ".tar.gz";
+// Added to the archive taken of the journal before a restore replaces it
+const BEFORE_RESTORE: &str = "-before-restore";
+
+// Copies a day file into the backup directory before it is overwritten,
+// appended to or deleted, when `auto_backup` is on. Snapshots are named
+// `<time>__<file name>`, so encrypted day files stay encrypted, and the
+// retention settings are applied to the file's snapshots afterwards.
+pub fn snapshot_file(config: &Config, path: &Path) -> Result<()> {
+    if !config.auto_backup || !path.is_file() {
+        return Ok(());
+    }
+    let name = path
+        .file_name()
+        .and_then(|name| name.to_str())
+        .ok_or_else(|| anyhow!("Invalid file name: {}", path.display()))?;
+
+    let directory = config.backup_directory().join(SNAPSHOT_DIRECTORY);
+    fs::create_dir_all(&directory)?;
+    let snapshot = directory.join(format!(
+        "{}{}{}",
+        timestamp(OffsetDateTime::now_utc()),
+        NAME_SEPARATOR,
+        name
+    ));
+    fs::copy(path, &snapshot)
+        .map_err(|e| anyhow!("Failed to back up {}: {}", path.display(), e))?;
+
+    prune_snapshots(config, &directory, name)
+}
+
+// Deletes the snapshots of a day file beyond `keep_versions` and older than
+// `keep_days`
+fn prune_snapshots(config: &Config, directory: &Path, name: &str) -> Result<()> {
+    let mut snapshots: Vec<(String, PathBuf)> = Vec::new();
+    for file in fs::read_dir(directory)? {
+        let path = file?.path();
+        let Some((taken_at, file_name)) = path
+            .file_name()
+            .and_then(|name| name.to_str())
+            .and_then(|name| name.split_once(NAME_SEPARATOR))
+        else {
+            continue;
+        };
+        if file_name == name {
+            snapshots.push((taken_at.to_string(), path.clone()));
+        }
+    }
+    // Timestamps sort like the times they stand for; newest first
+    snapshots.sort_by(|a, b| b.0.cmp(&a.0));
+
+    let keep_versions = config.backup.keep_versions;
+    let cutoff = (config.backup.keep_days > 0).then(|| {
+        let days = i64::try_from(config.backup.keep_days).unwrap_or(i64::MAX);
+        timestamp(OffsetDateTime::now_utc() - Duration::days(days))
+    });
+    for (index, (taken_at, path)) in snapshots.iter().enumerate() {
+        let too_many = keep_versions > 0 && index >= keep_versions;
+        let too_old = cutoff.as_ref().is_some_and(|cutoff| taken_at < cutoff);
+        if too_many || too_old {
+            fs::remove_file(path)?;
+        }
+    }
+    Ok(())
+}
+
+// Snapshots of day files in the backup directory, encrypted or not
+pub(super) fn snapshot_files(config: &Config) -> Result<Vec<PathBuf>> {
+    let directory = config.backup_directory().join(SNAPSHOT_DIRECTORY);
+    if !directory.is_dir() {
+        return Ok(Vec::new());
+    }
+
+    let mut files = Vec::new();
+    for file in fs::read_dir(&directory)? {
+        let path = file?.path();
+        let is_snapshot = path
+            .file_name()
+            .and_then(|name| name.to_str())
+            .and_then(|name| name.split_once(NAME_SEPARATOR))
+            .is_some_and(|(_, name)| parse_file_name(Path::new(name)).is_some());
+        if is_snapshot {
+            files.push(path);
+        }
+    }
+    Ok(files)
+}
+
+// Writes every file of the journal directory, trash and encryption key
+// settings included, into a gzip-compressed tar archive. It goes to `output`,
+// or to `rusty_diary-<time>.tar.gz` in the backup directory.
+pub fn create_archive(config: &Config, output: Option<&Path>) -> Result<PathBuf> {
+    create_archive_named(config, output, "")
+}
+
+fn create_archive_named(config: &Config, output: Option<&Path>, suffix: &str) -> Result<PathBuf> {
+    let path = match output {
+        Some(path) => path.to_path_buf(),
+        None => config.backup_directory().join(format!(
+            "{}{}{}{}",
+            ARCHIVE_PREFIX,
+            timestamp(OffsetDateTime::now_utc()),
+            suffix,
+            ARCHIVE_EXTENSION
+        )),
+    };
+    if let Some(parent) = path
+        .parent()
+        .filter(|parent| !parent.as_os_str().is_empty())
+    {
+        fs::create_dir_all(parent)?;
+    }
+
+    // Written under another name first so a failed backup can't replace a
+    // good archive with a broken one
+    let mut temp_path = path.clone().into_os_string();
+    temp_path.push(".tmp");
+    let temp_path = PathBuf::from(temp_path);
+
+    let result = (|| -> Result<()> {
+        let encoder = GzEncoder::new(File::create(&temp_path)?, Compression::default());
+        let mut builder = tar::Builder::new(encoder);
+        // Backups kept inside the journal directory aren't archived again
+        let skip = [config.backup_directory(), path.clone(), temp_path.clone()];
+        if config.journal_directory.is_dir() {
+            add_directory(
+                &mut builder,
+                &config.journal_directory,
+                Path::new(""),
+                &skip,
+            )?;
+        }
+        builder.into_inner()?.finish()?;
+        Ok(())
+    })();
+    if let Err(e) = result {
+        let _ = fs::remove_file(&temp_path);
+        return Err(e);
+    }
+
+    fs::rename(&temp_path, &path)?;
+    Ok(path)
+}
+
+fn add_directory(
+    builder: &mut tar::Builder<GzEncoder<File>>,
+    directory: &Path,
+    relative: &Path,
+    skip: &[PathBuf],
+) -> Result<()> {
+    let mut files: Vec<PathBuf> = fs::read_dir(directory)?
+        .map(|file| file.map(|file| file.path()))
+        .collect::<Result<_, _>>()?;
+    files.sort();
+
+    for path in files {
+        if skip.contains(&path) {
+            continue;
+        }
+        let Some(name) = path.file_name() else {
+            continue;
+        };
+        let name = relative.join(name);
+        if path.is_dir() {
+            add_directory(builder, &path, &name, skip)?;
+        } else if path.is_file() {
+            builder.append_path_with_name(&path, &name)?;
+        }
+    }
+    Ok(())
+}
+
+// A file converted by `convert_archive`: the name and content to store instead,
+// or None to leave it out
+pub(super) type ConvertedFile = Option<(PathBuf, Vec<u8>)>;
+
+// Writes a copy of an archive to `output` with every file passed through
+// `convert`, which is given its name in the archive and its content. The
+// `extra` files are added at the end.
+pub(super) fn convert_archive(
+    archive: &Path,
+    output: &Path,
+    convert: &mut dyn FnMut(&Path, Vec<u8>) -> Result<ConvertedFile>,
+    extra: &[(PathBuf, Vec<u8>)],
+) -> Result<()> {
+    let result = (|| -> Result<()> {
+        let encoder = GzEncoder::new(File::create(output)?, Compression::default());
+        let mut builder = tar::Builder::new(encoder);
+        for entry in open_archive(archive)?.entries()? {
+            let mut entry = entry?;
+            let mut header = entry.header().clone();
+            if !header.entry_type().is_file() {
+                builder.append(&header, &mut entry)?;
+                continue;
+            }
+
+            let name = entry.path()?.into_owned();
+            let mut content = Vec::new();
+            entry.read_to_end(&mut content)?;
+            if let Some((name, content)) = convert(&name, content)? {
+                header.set_size(content.len() as u64);
+                builder.append_data(&mut header, name, content.as_slice())?;
+            }
+        }
+        for (name, content) in extra {
+            let mut header = tar::Header::new_gnu();
+            header.set_mode(0o600);
+            header.set_mtime(OffsetDateTime::now_utc().unix_timestamp().max(0) as u64);
+            header.set_size(content.len() as u64);
+            builder.append_data(&mut header, name, content.as_slice())?;
+        }
+        builder.into_inner()?.finish()?;
+        Ok(())
+    })();
+    if let Err(e) = result {
+        let _ = fs::remove_file(output);
+        return Err(anyhow!("Failed to convert {}: {}", archive.display(), e));
+    }
+    Ok(())
+}
+
+// Archives in the backup directory, newest first. The archives taken before
+// a restore are listed too.
+pub fn list_archives(config: &Config) -> Result<Vec<PathBuf>> {
+    let directory = config.backup_directory();
+    if !directory.is_dir() {
+        return Ok(Vec::new());
+    }
+
+    let mut archives: Vec<PathBuf> = Vec::new();
+    for file in fs::read_dir(&directory)? {
+        let path = file?.path();
+        let is_archive = path
+            .file_name()
+            .and_then(|name| name.to_str())
+            .is_some_and(|name| {
+                name.starts_with(ARCHIVE_PREFIX) && name.ends_with(ARCHIVE_EXTENSION)
+            });
+        if is_archive {
+            archives.push(path);
+        }
+    }
+    archives.sort_by(|a, b| b.cmp(a));
+    Ok(archives)
+}
+
+// The newest archive made by `backup`, leaving out those taken before a
+// restore
+pub fn latest_archive(config: &Config) -> Result<Option<PathBuf>> {
+    Ok(list_archives(config)?.into_iter().find(|path| {
+        !path
+            .to_string_lossy()
+            .ends_with(&format!("{}{}", BEFORE_RESTORE, ARCHIVE_EXTENSION))
+    }))
+}
+
+pub struct RestoreReport {
+    pub files: usize,
+    // The journal as it was before the restore, when it had any files
+    pub previous: Option<PathBuf>,
+}
+
+// Replaces the journal with the contents of an archive. Day files, the trash
+// and the encryption key settings are removed first, so nothing written after
+// the archive was taken is mixed into it; other files in the journal directory
+// are kept. The journal is archived before that, so a restore can be undone by
+// restoring that archive.
+pub fn restore_archive(config: &Config, archive: &Path) -> Result<RestoreReport> {
+    // Read through once so a damaged archive fails before the journal changes
+    let damaged = |e: std::io::Error| anyhow!("Damaged archive {}: {}", archive.display(), e);
+    for entry in open_archive(archive)?.entries().map_err(damaged)? {
+        entry.map_err(damaged)?;
+    }
+
+    let has_files =
+        fs::read_dir(&config.journal_directory).is_ok_and(|mut files| files.next().is_some());
+    let previous = if has_files {
+        Some(create_archive_named(config, None, BEFORE_RESTORE)?)
+    } else {
+        None
+    };
+
+    clear_journal(config)?;
+    fs::create_dir_all(&config.journal_directory)?;
+    let mut files = 0;
+    for entry in open_archive(archive)?.entries()? {
+        let mut entry = entry?;
+        let is_file = entry.header().entry_type().is_file();
+        // Paths leading out of the journal directory are skipped
+        if entry.unpack_in(&config.journal_directory)? && is_file {
+            files += 1;
+        }
+    }
+
+    Ok(RestoreReport { files, previous })
+}
+
+fn open_archive(path: &Path) -> Result<tar::Archive<GzDecoder<File>>> {
+    let file = File::open(path).map_err(|e| anyhow!("Failed to open {}: {}", path.display(), e))?;
+    Ok(tar::Archive::new(GzDecoder::new(file)))
+}
+
+// Removes the files a restore replaces. The search index is left to the
+// caller, which deletes it once the restore is done.
+fn clear_journal(config: &Config) -> Result<()> {
+    for day_file in list_day_files(config)? {
+        fs::remove_file(&day_file.path)?;
+    }
+    let trash = trash_directory(config);
+    if trash.is_dir() {
+        fs::remove_dir_all(&trash)?;
+    }
+    let key_file = key_file_path(config);
+    if key_file.exists() {
+        fs::remove_file(&key_file)?;
+    }
+    Ok(())
+}
+
+// "20240504T061817.123456Z", in UTC so names sort by time
+fn timestamp(time: OffsetDateTime) -> String {
+    format!(
+        "{:04}{:02}{:02}T{:02}{:02}{:02}.{:06}Z",
+        time.year(),
+        time.month() as u8,
+        time.day(),
+        time.hour(),
+        time.minute(),
+        time.second(),
+        time.microsecond()
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::{read_day, TempJournal};
+    use time::{Date, Month};
+
+    fn bodies(config: &Config) -> Vec<String> {
+        let day = Date::from_calendar_date(2024, Month::May, 4).unwrap();
+        read_day(config, day)
+            .unwrap()
+            .into_iter()
+            .map(|entry| entry.body)
+            .collect()
+    }
+
+    fn file_names(directory: &Path) -> Vec<String> {
+        let mut names: Vec<String> = fs::read_dir(directory)
+            .unwrap()
+            .map(|file| file.unwrap().file_name().to_string_lossy().to_string())
+            .collect();
+        names.sort();
+        names
+    }
+
+    #[test]
+    fn timestamps_are_utc_and_sortable() {
+        let time = Date::from_calendar_date(2024, Month::May, 4)
+            .unwrap()
+            .with_hms_micro(6, 18, 17, 123_456)
+            .unwrap()
+            .assume_utc();
+        assert_eq!(timestamp(time), "20240504T061817.123456Z");
+        assert!(timestamp(time) < timestamp(time + Duration::seconds(1)));
+    }
+
+    #[test]
+    fn snapshots_are_taken_only_with_auto_backup() {
+        let mut journal = TempJournal::new("backup-snapshot");
+        journal.add(6, "Lake swim");
+        let path = list_day_files(&journal.config).unwrap().remove(0).path;
+        let directory = journal.config.backup_directory().join(SNAPSHOT_DIRECTORY);
+
+        snapshot_file(&journal.config, &path).unwrap();
+        assert!(!directory.exists());
+
+        journal.config.auto_backup = true;
+        snapshot_file(&journal.config, &path).unwrap();
+        let names = file_names(&directory);
+        assert_eq!(names.len(), 1);
+        assert!(names[0].ends_with("__2024-05-04.md"));
+        assert_eq!(
+            fs::read_to_string(directory.join(&names[0])).unwrap(),
+            fs::read_to_string(&path).unwrap()
+        );
+    }
+
+    #[test]
+    fn retention_keeps_recent_versions_of_each_file() {
+        let mut journal = TempJournal::new("backup-retention");
+        let directory = journal.config.backup_directory().join(SNAPSHOT_DIRECTORY);
+        fs::create_dir_all(&directory).unwrap();
+
+        let now = OffsetDateTime::now_utc();
+        let taken = |days: i64| timestamp(now - Duration::days(days));
+        for days in [1, 2, 3, 40] {
+            fs::write(
+                directory.join(format!("{}__2024-05-04.md", taken(days))),
+                "",
+            )
+            .unwrap();
+        }
+        let other = format!("{}__2024-05-05.md", taken(50));
+        fs::write(directory.join(&other), "").unwrap();
+        fs::write(directory.join("notes.txt"), "").unwrap();
+
+        journal.config.backup.keep_versions = 0;
+        journal.config.backup.keep_days = 30;
+        prune_snapshots(&journal.config, &directory, "2024-05-04.md").unwrap();
+        assert_eq!(file_names(&directory).len(), 5);
+
+        journal.config.backup.keep_versions = 2;
+        journal.config.backup.keep_days = 0;
+        prune_snapshots(&journal.config, &directory, "2024-05-04.md").unwrap();
+        let mut expected = vec![
+            format!("{}__2024-05-04.md", taken(1)),
+            format!("{}__2024-05-04.md", taken(2)),
+            other,
+            "notes.txt".to_string(),
+        ];
+        expected.sort();
+        assert_eq!(file_names(&directory), expected);
+    }
+
+    #[test]
+    fn restore_replaces_the_journal() {
+        let journal = TempJournal::new("backup-restore");
+        let config = &journal.config;
+        journal.add(6, "Before the backup");
+        let archive = create_archive(config, None).unwrap();
+        assert_eq!(latest_archive(config).unwrap(), Some(archive.clone()));
+
+        journal.add(7, "After the backup");
+        fs::write(config.journal_directory.join("notes.txt"), "kept").unwrap();
+
+        let report = restore_archive(config, &archive).unwrap();
+        assert_eq!(report.files, 1);
+        assert_eq!(bodies(config), ["Before the backup"]);
+        assert!(config.journal_directory.join("notes.txt").exists());
+
+        // The journal as it was is archived, but isn't the latest backup
+        let previous = report.previous.unwrap();
+        assert_eq!(list_archives(config).unwrap().len(), 2);
+        assert_eq!(latest_archive(config).unwrap(), Some(archive));
+
+        restore_archive(config, &previous).unwrap();
+        assert_eq!(bodies(config), ["Before the backup", "After the backup"]);
+    }
+
+    #[test]
+    fn damaged_archives_leave_the_journal_alone() {
+        let journal = TempJournal::new("backup-damaged");
+        let config = &journal.config;
+        journal.add(6, "Lake swim");
+        let archive = config.backup_directory().join("damaged.tar.gz");
+        fs::create_dir_all(config.backup_directory()).unwrap();
+        fs::write(&archive, "not an archive").unwrap();
+
+        assert!(restore_archive(config, &archive).is_err());
+        assert_eq!(bodies(config), ["Lake swim"]);
+        assert!(list_archives(config).unwrap().is_empty());
+    }
+}
@@ -0,0 +1,643 @@
+use super::{
+    convert_archive, list_archives, list_day_files, parse_file_name, snapshot_files,
+    trash_directory,
+};
+use crate::config::Config;
+use crate::search::SearchIndex;
+use anyhow::{anyhow, Result};
+use argon2::{Algorithm, Argon2, Params, Version};
+use chacha20poly1305::aead::rand_core::RngCore;
+use chacha20poly1305::aead::{Aead, AeadCore, KeyInit, OsRng, Payload};
+use chacha20poly1305::{Key, XChaCha20Poly1305, XNonce};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+// Added to the name of every file written while the journal is encrypted:
+// "2024-05-04.md.enc"
+pub const ENCRYPTED_EXTENSION: &str = "enc";
+
+// Read instead of asking for the passphrase
+pub const PASSPHRASE_VARIABLE: &str = "RUSTY_DIARY_PASSPHRASE";
+
+// Marks an encrypted journal and holds what's needed to derive its key; the
+// key itself is never stored
+const KEY_FILE: &str = ".encryption.json";
+
+const KEY_FILE_VERSION: u32 = 1;
+
+// Encrypted files start with this line, followed by the nonce and the
+// ciphertext. It is also authenticated with the content.
+const MAGIC: &[u8] = b"rusty_diary encrypted v1\n";
+
+const NONCE_LENGTH: usize = 24;
+const SALT_LENGTH: usize = 16;
+
+// Encrypted into the key file so a wrong passphrase is told apart from a
+// damaged day file
+const CHECK_TEXT: &[u8] = b"rusty_diary";
+
+#[derive(Serialize, Deserialize)]
+struct KeyFile {
+    version: u32,
+    cipher: String,
+    kdf: String,
+    memory_kib: u32,
+    iterations: u32,
+    parallelism: u32,
+    salt: String,
+    check: String,
+}
+
+// The key of the unlocked journal, shared by the TUI and its worker threads
+struct Session {
+    directory: PathBuf,
+    key: Key,
+}
+
+static SESSION: Mutex<Option<Session>> = Mutex::new(None);
+
+pub fn is_encrypted(config: &Config) -> bool {
+    key_file_path(config).exists()
+}
+
+pub fn is_unlocked(config: &Config) -> bool {
+    session_key(config).is_some()
+}
+
+// Whether a file is encrypted, going by its name
+pub fn is_encrypted_path(path: &Path) -> bool {
+    path.extension()
+        .is_some_and(|extension| extension == ENCRYPTED_EXTENSION)
+}
+
+// The path with the encrypted extension added
+pub fn encrypted_path(path: &Path) -> PathBuf {
+    let mut path = path.as_os_str().to_owned();
+    path.push(".");
+    path.push(ENCRYPTED_EXTENSION);
+    PathBuf::from(path)
+}
+
+// The path with the encrypted extension removed
+pub fn decrypted_path(path: &Path) -> PathBuf {
+    if is_encrypted_path(path) {
+        path.with_extension("")
+    } else {
+        path.to_path_buf()
+    }
+}
+
+// Derives the journal's key from the passphrase and keeps it for this process
+pub fn unlock(config: &Config, passphrase: &str) -> Result<()> {
+    let key_file = read_key_file(config)?;
+    let key = derive_key(passphrase, &key_file)?;
+    let check = from_hex(&key_file.check)?;
+    decrypt(&key, &check).map_err(|_| anyhow!("Wrong passphrase"))?;
+
+    set_session(config, key);
+    Ok(())
+}
+
+// Unlocks with RUSTY_DIARY_PASSPHRASE when it is set. Returns whether the
+// journal is unlocked.
+pub fn unlock_from_env(config: &Config) -> Result<bool> {
+    if is_unlocked(config) {
+        return Ok(true);
+    }
+    match std::env::var(PASSPHRASE_VARIABLE) {
+        Ok(passphrase) => {
+            unlock(config, &passphrase)
+                .map_err(|e| anyhow!("{} from {}", e, PASSPHRASE_VARIABLE))?;
+            Ok(true)
+        }
+        Err(_) => Ok(false),
+    }
+}
+
+// A journal file's text, decrypted when its name has the encrypted extension
+pub fn read_file(path: &Path) -> Result<String> {
+    if !is_encrypted_path(path) {
+        return Ok(fs::read_to_string(path)?);
+    }
+    let data = fs::read(path)?;
+    let text = decrypt(&unlocked_key()?, &data)
+        .map_err(|e| anyhow!("Can't decrypt {}: {}", path.display(), e))?;
+    Ok(String::from_utf8(text)?)
+}
+
+// Writes a journal file, encrypted when its name has the encrypted extension.
+// The content goes to a sibling file first so a failed write can't truncate
+// the file.
+pub fn write_file(path: &Path, content: &str) -> Result<()> {
+    let data = if is_encrypted_path(path) {
+        encrypt(&unlocked_key()?, content.as_bytes())?
+    } else {
+        content.as_bytes().to_vec()
+    };
+    let temp_path = temp_path(path);
+    fs::write(&temp_path, data)?;
+    fs::rename(&temp_path, path)?;
+    Ok(())
+}
+
+// Encrypts every day file, trashed entry and snapshot in place, with a key
+// from the passphrase for a plain journal, then the files inside the archives
+// in the backup directory. Each encrypted file is written before its plain one
+// is removed, so an interrupted run leaves a journal that still reads; running
+// it again on the unlocked journal finishes it. Returns the number of files
+// encrypted, archives aside.
+pub fn encrypt_journal(config: &Config, passphrase: Option<&str>) -> Result<usize> {
+    if !is_encrypted(config) {
+        let passphrase = passphrase.ok_or_else(|| anyhow!("A passphrase is needed"))?;
+        let (key_file, key) = KeyFile::new(passphrase)?;
+        write_key_file(config, &key_file)?;
+        set_session(config, key);
+    }
+
+    let mut count = 0;
+    for path in journal_files(config)?
+        .into_iter()
+        .filter(|path| !is_encrypted_path(path))
+    {
+        write_file(&encrypted_path(&path), &read_file(&path)?)?;
+        fs::remove_file(&path)?;
+        count += 1;
+    }
+
+    let key_file = read_key_file(config)?;
+    for (archive, converted) in convert_archives(config, Some((&unlocked_key()?, &key_file)))? {
+        fs::rename(converted, archive)?;
+    }
+    Ok(count)
+}
+
+// Decrypts every file and snapshot of the unlocked journal in place and removes
+// the key file, leaving a plain journal. The archives are decrypted too. Returns the number of files decrypted.
+pub fn decrypt_journal(config: &Config) -> Result<usize> {
+    let mut count = 0;
+    for path in journal_files(config)?
+        .into_iter()
+        .filter(|path| is_encrypted_path(path))
+    {
+        let plain_path = decrypted_path(&path);
+        if plain_path.exists() {
+            return Err(anyhow!(
+                "{} exists both encrypted and decrypted",
+                plain_path.display()
+            ));
+        }
+        write_file(&plain_path, &read_file(&path)?)?;
+        fs::remove_file(&path)?;
+        count += 1;
+    }
+
+    for (archive, converted) in convert_archives(config, None)? {
+        fs::rename(converted, archive)?;
+    }
+    fs::remove_file(key_file_path(config))?;
+    if let Ok(mut session) = SESSION.lock() {
+        *session = None;
+    }
+    Ok(count)
+}
+
+// Re-encrypts the unlocked journal, its snapshots and archives with a key from
+// a new passphrase. Every file is re-encrypted next to the original first; the
+// originals and the key file are replaced only once all of them were written.
+// Returns the number of files re-encrypted, archives aside.
+pub fn change_passphrase(config: &Config, passphrase: &str) -> Result<usize> {
+    let (key_file, key) = KeyFile::new(passphrase)?;
+    let files: Vec<PathBuf> = journal_files(config)?
+        .into_iter()
+        .filter(|path| is_encrypted_path(path))
+        .collect();
+
+    let mut written = Vec::new();
+    for path in &files {
+        let temp_path = temp_path(path);
+        let result = read_file(path).and_then(|text| {
+            fs::write(&temp_path, encrypt(&key, text.as_bytes())?)?;
+            Ok(())
+        });
+        if let Err(e) = result {
+            for temp_path in written {
+                let _ = fs::remove_file(temp_path);
+            }
+            return Err(e);
+        }
+        written.push(temp_path);
+    }
+    let archives = match convert_archives(config, Some((&key, &key_file))) {
+        Ok(archives) => archives,
+        Err(e) => {
+            for temp_path in written {
+                let _ = fs::remove_file(temp_path);
+            }
+            return Err(e);
+        }
+    };
+
+    write_key_file(config, &key_file)?;
+    set_session(config, key);
+    for (path, temp_path) in files.iter().zip(&written) {
+        fs::rename(temp_path, path)?;
+    }
+    for (archive, converted) in archives {
+        fs::rename(converted, archive)?;
+    }
+    Ok(files.len())
+}
+
+impl KeyFile {
+    // Settings for a new key with a random salt, and the key the passphrase
+    // gives with them
+    fn new(passphrase: &str) -> Result<(Self, Key)> {
+        let mut salt = [0u8; SALT_LENGTH];
+        OsRng.fill_bytes(&mut salt);
+
+        let mut key_file = Self {
+            version: KEY_FILE_VERSION,
+            cipher: "XChaCha20-Poly1305".to_string(),
+            kdf: "Argon2id".to_string(),
+            memory_kib: Params::DEFAULT_M_COST,
+            iterations: Params::DEFAULT_T_COST,
+            parallelism: Params::DEFAULT_P_COST,
+            salt: to_hex(&salt),
+            check: String::new(),
+        };
+        let key = derive_key(passphrase, &key_file)?;
+        key_file.check = to_hex(&encrypt(&key, CHECK_TEXT)?);
+        Ok((key_file, key))
+    }
+}
+
+// Day files of every format, trashed entries and snapshots, encrypted or not
+fn journal_files(config: &Config) -> Result<Vec<PathBuf>> {
+    let mut files: Vec<PathBuf> = list_day_files(config)?
+        .into_iter()
+        .map(|day_file| day_file.path)
+        .collect();
+
+    let trash = trash_directory(config);
+    if trash.exists() {
+        for file in fs::read_dir(&trash)? {
+            let path = file?.path();
+            if parse_file_name(&path).is_some() {
+                files.push(path);
+            }
+        }
+    }
+    files.extend(snapshot_files(config)?);
+    Ok(files)
+}
+
+// Copies of the archives in the backup directory, next to them, with the day
+// files and trashed entries encrypted under the key and the key file replaced,
+// or decrypted and the key file left out when there is no key. Files that were
+// already encrypted are read with the unlocked key. The search index is left
+// out; a restore deletes it anyway. Returns each archive with its copy.
+fn convert_archives(
+    config: &Config,
+    key: Option<(&Key, &KeyFile)>,
+) -> Result<Vec<(PathBuf, PathBuf)>> {
+    let extra = match key {
+        Some((_, key_file)) => vec![(
+            PathBuf::from(KEY_FILE),
+            serde_json::to_string_pretty(key_file)?.into_bytes(),
+        )],
+        None => Vec::new(),
+    };
+    let index = decrypted_path(&SearchIndex::path(config));
+    let is_journal_file = |path: &Path| {
+        parse_file_name(path).is_some()
+            && path.parent().is_some_and(|parent| {
+                parent == config.journal_directory || parent == trash_directory(config)
+            })
+    };
+
+    let mut converted: Vec<(PathBuf, PathBuf)> = Vec::new();
+    for archive in list_archives(config)? {
+        let output = temp_path(&archive);
+        let result = convert_archive(
+            &archive,
+            &output,
+            &mut |name, content| {
+                let path = config.journal_directory.join(name);
+                if path == key_file_path(config) || decrypted_path(&path) == index {
+                    return Ok(None);
+                }
+                if !is_journal_file(&path) {
+                    return Ok(Some((name.to_path_buf(), content)));
+                }
+                let content = if is_encrypted_path(&path) {
+                    decrypt(&unlocked_key()?, &content)
+                        .map_err(|e| anyhow!("Can't decrypt {}: {}", name.display(), e))?
+                } else {
+                    content
+                };
+                Ok(Some(match key {
+                    Some((key, _)) => (
+                        encrypted_path(&decrypted_path(name)),
+                        encrypt(key, &content)?,
+                    ),
+                    None => (decrypted_path(name), content),
+                }))
+            },
+            &extra,
+        );
+        if let Err(e) = result {
+            for (_, output) in converted {
+                let _ = fs::remove_file(output);
+            }
+            return Err(e);
+        }
+        converted.push((archive, output));
+    }
+    Ok(converted)
+}
+
+fn read_key_file(config: &Config) -> Result<KeyFile> {
+    let content = fs::read_to_string(key_file_path(config))
+        .map_err(|e| anyhow!("The journal isn't encrypted ({})", e))?;
+    let key_file: KeyFile = serde_json::from_str(&content)?;
+    if key_file.version > KEY_FILE_VERSION {
+        return Err(anyhow!(
+            "The journal was encrypted by a newer version of Rusty Diary"
+        ));
+    }
+    Ok(key_file)
+}
+
+fn write_key_file(config: &Config, key_file: &KeyFile) -> Result<()> {
+    fs::create_dir_all(&config.journal_directory)?;
+    let path = key_file_path(config);
+    let temp_path = temp_path(&path);
+    fs::write(&temp_path, serde_json::to_string_pretty(key_file)?)?;
+    fs::rename(&temp_path, &path)?;
+    Ok(())
+}
+
+fn temp_path(path: &Path) -> PathBuf {
+    let mut path = path.as_os_str().to_owned();
+    path.push(".tmp");
+    PathBuf::from(path)
+}
+
+pub(super) fn key_file_path(config: &Config) -> PathBuf {
+    config.journal_directory.join(KEY_FILE)
+}
+
+fn set_session(config: &Config, key: Key) {
+    if let Ok(mut session) = SESSION.lock() {
+        *session = Some(Session {
+            directory: config.journal_directory.clone(),
+            key,
+        });
+    }
+}
+
+fn session_key(config: &Config) -> Option<Key> {
+    let session = SESSION.lock().ok()?;
+    session
+        .as_ref()
+        .filter(|session| session.directory == config.journal_directory)
+        .map(|session| session.key)
+}
+
+fn unlocked_key() -> Result<Key> {
+    SESSION
+        .lock()
+        .ok()
+        .and_then(|session| session.as_ref().map(|session| session.key))
+        .ok_or_else(|| anyhow!("The journal is encrypted and hasn't been unlocked"))
+}
+
+fn derive_key(passphrase: &str, key_file: &KeyFile) -> Result<Key> {
+    let params = Params::new(
+        key_file.memory_kib,
+        key_file.iterations,
+        key_file.parallelism,
+        Some(32),
+    )
+    .map_err(|e| anyhow!("Invalid key derivation settings: {}", e))?;
+    let salt = from_hex(&key_file.salt)?;
+
+    let mut key = Key::default();
+    Argon2::new(Algorithm::Argon2id, Version::V0x13, params)
+        .hash_password_into(passphrase.as_bytes(), &salt, &mut key)
+        .map_err(|e| anyhow!("Failed to derive the key: {}", e))?;
+    Ok(key)
+}
+
+fn encrypt(key: &Key, plaintext: &[u8]) -> Result<Vec<u8>> {
+    let nonce = XChaCha20Poly1305::generate_nonce(&mut OsRng);
+    let ciphertext = XChaCha20Poly1305::new(key)
+        .encrypt(
+            &nonce,
+            Payload {
+                msg: plaintext,
+                aad: MAGIC,
+            },
+        )
+        .map_err(|_| anyhow!("Encryption failed"))?;
+
+    let mut data = Vec::with_capacity(MAGIC.len() + NONCE_LENGTH + ciphertext.len());
+    data.extend_from_slice(MAGIC);
+    data.extend_from_slice(&nonce);
+    data.extend_from_slice(&ciphertext);
+    Ok(data)
+}
+
+fn decrypt(key: &Key, data: &[u8]) -> Result<Vec<u8>> {
+    let data = data
+        .strip_prefix(MAGIC)
+        .filter(|data| data.len() >= NONCE_LENGTH)
+        .ok_or_else(|| anyhow!("not an encrypted Rusty Diary file"))?;
+    let (nonce, ciphertext) = data.split_at(NONCE_LENGTH);
+    XChaCha20Poly1305::new(key)
+        .decrypt(
+            XNonce::from_slice(nonce),
+            Payload {
+                msg: ciphertext,
+                aad: MAGIC,
+            },
+        )
+        .map_err(|_| anyhow!("it was encrypted with another passphrase or is damaged"))
+}
+
+fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{:02x}", byte)).collect()
+}
+
+fn from_hex(text: &str) -> Result<Vec<u8>> {
+    if !text.len().is_multiple_of(2) {
+        return Err(anyhow!("Damaged key file"));
+    }
+    // Pairs of bytes, so text that isn't ASCII fails to parse instead of
+    // being split inside a character
+    text.as_bytes()
+        .chunks(2)
+        .map(|pair| {
+            if !pair.iter().all(u8::is_ascii_hexdigit) {
+                return Err(anyhow!("Damaged key file"));
+            }
+            let pair = std::str::from_utf8(pair)?;
+            Ok(u8::from_str_radix(pair, 16)?)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::{create_archive, read_day, restore_archive, TempJournal};
+    use std::io::Read;
+    use std::sync::MutexGuard;
+    use time::{Date, Month};
+
+    // Held by the tests that unlock a journal, as the key is kept for the
+    // whole process
+    static SESSION_TEST: Mutex<()> = Mutex::new(());
+
+    fn session_test() -> MutexGuard<'static, ()> {
+        SESSION_TEST.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    fn day() -> Date {
+        Date::from_calendar_date(2024, Month::May, 4).unwrap()
+    }
+
+    // Every snapshot, and every file inside the archives, by name
+    fn backup_files(config: &Config) -> Vec<(String, Vec<u8>)> {
+        let mut files = Vec::new();
+        for path in snapshot_files(config).unwrap() {
+            let name = path.file_name().unwrap().to_string_lossy().to_string();
+            files.push((name, fs::read(&path).unwrap()));
+        }
+        for archive in list_archives(config).unwrap() {
+            let file = fs::File::open(archive).unwrap();
+            let mut archive = tar::Archive::new(flate2::read::GzDecoder::new(file));
+            for entry in archive.entries().unwrap() {
+                let mut entry = entry.unwrap();
+                let name = entry.path().unwrap().to_string_lossy().to_string();
+                let mut content = Vec::new();
+                entry.read_to_end(&mut content).unwrap();
+                files.push((name, content));
+            }
+        }
+        files
+    }
+
+    fn contains(content: &[u8], text: &str) -> bool {
+        content
+            .windows(text.len())
+            .any(|window| window == text.as_bytes())
+    }
+
+    #[test]
+    fn data_decrypts_only_with_its_key() {
+        let key = Key::from([7u8; 32]);
+        let data = encrypt(&key, b"Lake swim").unwrap();
+        assert!(data.starts_with(MAGIC));
+        assert_eq!(decrypt(&key, &data).unwrap(), b"Lake swim");
+
+        assert!(decrypt(&Key::from([8u8; 32]), &data).is_err());
+        let mut damaged = data.clone();
+        *damaged.last_mut().unwrap() ^= 1;
+        assert!(decrypt(&key, &damaged).is_err());
+        assert!(decrypt(&key, b"Lake swim").is_err());
+    }
+
+    #[test]
+    fn hex_round_trips_and_rejects_damage() {
+        assert_eq!(to_hex(&[0, 15, 255]), "000fff");
+        assert_eq!(from_hex("000fFF").unwrap(), [0, 15, 255]);
+        assert!(from_hex("abc").is_err());
+        assert!(from_hex("zz").is_err());
+        assert!(from_hex("é0").is_err());
+    }
+
+    #[test]
+    fn journal_round_trips_through_encryption() {
+        let _session = session_test();
+        let journal = TempJournal::new("crypto");
+        let config = &journal.config;
+        journal.add(6, "Lake swim");
+
+        assert_eq!(encrypt_journal(config, Some("correct horse")).unwrap(), 1);
+        assert!(is_encrypted(config) && is_unlocked(config));
+        let day_files = list_day_files(config).unwrap();
+        assert_eq!(day_files.len(), 1);
+        assert!(is_encrypted_path(&day_files[0].path));
+        assert!(!contains(
+            &fs::read(&day_files[0].path).unwrap(),
+            "Lake swim"
+        ));
+        assert_eq!(read_day(config, day()).unwrap()[0].body, "Lake swim");
+
+        *SESSION.lock().unwrap() = None;
+        assert!(read_day(config, day()).is_err());
+        let error = unlock(config, "wrong horse").unwrap_err();
+        assert_eq!(error.to_string(), "Wrong passphrase");
+        assert!(!is_unlocked(config));
+
+        unlock(config, "correct horse").unwrap();
+        assert_eq!(read_day(config, day()).unwrap()[0].body, "Lake swim");
+
+        assert_eq!(decrypt_journal(config).unwrap(), 1);
+        assert!(!is_encrypted(config) && !is_unlocked(config));
+        let day_files = list_day_files(config).unwrap();
+        assert!(!is_encrypted_path(&day_files[0].path));
+        assert_eq!(read_day(config, day()).unwrap()[0].body, "Lake swim");
+    }
+
+    #[test]
+    fn backups_are_encrypted_with_the_journal() {
+        let _session = session_test();
+        let mut journal = TempJournal::new("crypto-backups");
+        journal.config.auto_backup = true;
+        let config = &journal.config;
+        journal.add(6, "Lake swim");
+        journal.add(7, "Heron at dawn");
+        create_archive(config, None).unwrap();
+        assert_eq!(snapshot_files(config).unwrap().len(), 1);
+
+        encrypt_journal(config, Some("correct horse")).unwrap();
+        let files = backup_files(config);
+        assert!(files.iter().any(|(name, _)| name == KEY_FILE));
+        assert!(files.iter().any(|(name, _)| name.ends_with(".md.enc")));
+        for (name, content) in &files {
+            assert!(!name.ends_with(".md"), "{} is plain", name);
+            assert!(!contains(content, "Lake swim") && !contains(content, "Heron"));
+        }
+
+        // Snapshots and archives can be read with the new passphrase only
+        change_passphrase(config, "battery staple").unwrap();
+        *SESSION.lock().unwrap() = None;
+        unlock(config, "battery staple").unwrap();
+        let snapshot = snapshot_files(config).unwrap().remove(0);
+        assert!(read_file(&snapshot).unwrap().contains("Lake swim"));
+        let archive = list_archives(config).unwrap().remove(0);
+        restore_archive(config, &archive).unwrap();
+        let bodies: Vec<String> = read_day(config, day())
+            .unwrap()
+            .into_iter()
+            .map(|entry| entry.body)
+            .collect();
+        assert_eq!(bodies, ["Lake swim", "Heron at dawn"]);
+
+        decrypt_journal(config).unwrap();
+        for (name, _) in backup_files(config) {
+            assert!(
+                !name.ends_with(".enc") && name != KEY_FILE,
+                "{} is left",
+                name
+            );
+        }
+        assert!(fs::read_to_string(&snapshot_files(config).unwrap()[0])
+            .unwrap()
+            .contains("Lake swim"));
+    }
+}
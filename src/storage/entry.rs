@@ -0,0 +1,139 @@
+use super::{extract_tags, FrontMatter};
+use crate::config::FileFormat;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet};
+use time::format_description::well_known::Rfc3339;
+use time::{Date, OffsetDateTime};
+
+#[derive(Debug, Clone)]
+pub struct JournalEntry {
+    pub id: String,
+    pub date: String,
+    pub day: Date,
+    pub created_at: Option<String>,
+    pub body: String,
+    pub filename: String,
+    pub metadata: EntryMetadata,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct EntryMetadata {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub utc_offset: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub time_zone: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub processed_at: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub llm_model: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub prompt: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub title: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub mood: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub energy: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub location: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub weather: Option<String>,
+    // `#tags` and `@mentions` from the text, and tags given explicitly (a JSON
+    // `tags` field or front matter)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub mentions: Vec<String>,
+    // Saved as written because the LLM was unavailable; formatted later
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub pending_formatting: bool,
+    // The front matter block of a Markdown or PlainText entry, with the keys
+    // that aren't read into the fields above
+    #[serde(skip)]
+    pub front_matter: Option<FrontMatter>,
+    // Keys written by other tools or newer versions are kept as-is
+    #[serde(flatten)]
+    pub extra: BTreeMap<String, serde_json::Value>,
+}
+
+impl JournalEntry {
+    pub fn new(date: &str, day: Date, created_at: OffsetDateTime, body: &str) -> Self {
+        Self {
+            id: new_entry_id(created_at),
+            date: date.to_string(),
+            day,
+            created_at: Some(crate::utils::format_timestamp(created_at)),
+            body: body.to_string(),
+            filename: String::new(),
+            metadata: EntryMetadata::default(),
+        }
+    }
+
+    pub fn timestamp(&self) -> Option<OffsetDateTime> {
+        self.created_at
+            .as_deref()
+            .and_then(|created_at| OffsetDateTime::parse(created_at, &Rfc3339).ok())
+    }
+
+    // Format of the day file the entry was loaded from
+    pub fn format(&self) -> Option<FileFormat> {
+        super::parse_file_name(std::path::Path::new(&self.filename)).map(|(_, format)| format)
+    }
+
+    // "HH:MM" in the time zone the entry was written in
+    pub fn time_label(&self) -> Option<String> {
+        self.timestamp()
+            .map(|timestamp| format!("{:02}:{:02}", timestamp.hour(), timestamp.minute()))
+    }
+
+    pub fn word_count(&self) -> usize {
+        self.body.split_whitespace().count()
+    }
+
+    // Re-derives tags and mentions from the text. Those that didn't come from
+    // `previous_body` were given explicitly and are kept.
+    pub fn update_tags(&mut self, previous_body: Option<&str>) {
+        let previous = previous_body.map(extract_tags).unwrap_or_default();
+        let current = extract_tags(&self.body);
+        self.metadata.tags = merge_tags(&self.metadata.tags, &previous.tags, current.tags);
+        self.metadata.mentions = merge_tags(
+            &self.metadata.mentions,
+            &previous.mentions,
+            current.mentions,
+        );
+    }
+
+    // Tags as shown and filtered on: `#tag` and `@person`
+    pub fn labels(&self) -> impl Iterator<Item = String> + '_ {
+        let tags = self.metadata.tags.iter().map(|tag| format!("#{}", tag));
+        let mentions = self
+            .metadata
+            .mentions
+            .iter()
+            .map(|mention| format!("@{}", mention));
+        tags.chain(mentions)
+    }
+}
+
+fn merge_tags(stored: &[String], previous: &[String], current: Vec<String>) -> Vec<String> {
+    let mut tags: BTreeSet<String> = stored
+        .iter()
+        .filter(|tag| !previous.contains(tag))
+        .cloned()
+        .collect();
+    tags.extend(current);
+    tags.into_iter().collect()
+}
+
+fn new_entry_id(created_at: OffsetDateTime) -> String {
+    format!(
+        "{:04}{:02}{:02}T{:02}{:02}{:02}-{:04x}",
+        created_at.year(),
+        created_at.month() as u8,
+        created_at.day(),
+        created_at.hour(),
+        created_at.minute(),
+        created_at.second(),
+        created_at.nanosecond() & 0xffff
+    )
+}
@@ -0,0 +1,462 @@
+use super::{
+    entry_front_matter, is_front_matter_key, parse_front_matter, EntryMetadata, JournalEntry,
+};
+use crate::config::FileFormat;
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use time::Date;
+
+// Entries in Markdown and PlainText day files start with a marker line carrying
+// their id, timestamp and metadata as key="value" attributes. A front matter
+// block may follow it; its keys are read into the metadata and left out of the
+// marker line.
+const MARKDOWN_MARKER: (&str, &str) = ("<!-- entry", "-->");
+const PLAIN_TEXT_MARKER: (&str, &str) = ("=== entry", "===");
+
+// Separator older versions wrote between entries appended to the same day
+const LEGACY_SEPARATOR: &str = "\n---\n";
+
+#[derive(Serialize, Deserialize)]
+struct JsonEntry {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    id: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    date: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    created_at: Option<String>,
+    #[serde(default)]
+    content: String,
+    #[serde(flatten)]
+    metadata: EntryMetadata,
+}
+
+struct Section {
+    id: Option<String>,
+    created_at: Option<String>,
+    metadata: EntryMetadata,
+    body: String,
+}
+
+pub fn parse_day_file(
+    content: &str,
+    format: &FileFormat,
+    date: &str,
+    day: Date,
+) -> Vec<JournalEntry> {
+    let sections = match format {
+        FileFormat::Json => parse_json(content),
+        FileFormat::Markdown => parse_marked(content, MARKDOWN_MARKER, false),
+        FileFormat::PlainText => parse_marked(content, PLAIN_TEXT_MARKER, true),
+    };
+
+    sections
+        .into_iter()
+        .enumerate()
+        .map(|(index, section)| {
+            let mut entry = section.into_entry(date, day, index);
+            // Entries saved before tags were extracted get them when read
+            entry.update_tags(None);
+            entry
+        })
+        .collect()
+}
+
+pub fn render_day_file(format: &FileFormat, entries: &[JournalEntry]) -> Result<String> {
+    match format {
+        FileFormat::Json => {
+            let json_entries: Vec<JsonEntry> = entries
+                .iter()
+                .map(|entry| {
+                    // Front matter keys of a converted entry become JSON fields
+                    let mut metadata = entry.metadata.clone();
+                    if let Some(front_matter) = metadata.front_matter.take() {
+                        for (key, value) in front_matter.kept_fields() {
+                            metadata.extra.entry(key).or_insert(value);
+                        }
+                    }
+                    JsonEntry {
+                        id: Some(entry.id.clone()),
+                        date: Some(entry.date.clone()),
+                        created_at: entry.created_at.clone(),
+                        content: entry.body.clone(),
+                        metadata,
+                    }
+                })
+                .collect();
+            Ok(format!(
+                "{}\n",
+                serde_json::to_string_pretty(&json_entries)?
+            ))
+        }
+        FileFormat::Markdown => render_marked(entries, MARKDOWN_MARKER),
+        FileFormat::PlainText => render_marked(entries, PLAIN_TEXT_MARKER),
+    }
+}
+
+impl Section {
+    fn legacy(body: &str) -> Self {
+        Self {
+            id: None,
+            created_at: None,
+            metadata: EntryMetadata::default(),
+            body: body.trim().to_string(),
+        }
+    }
+
+    fn from_attributes(attributes: Vec<(String, String)>, body: &str) -> Self {
+        let mut section = Self::legacy(body);
+        let mut metadata = serde_json::Map::new();
+
+        for (key, value) in attributes {
+            match key.as_str() {
+                "id" => section.id = Some(value),
+                "created_at" => section.created_at = Some(value),
+                _ => {
+                    metadata.insert(key, attribute_to_json(value));
+                }
+            }
+        }
+
+        // Fall back to keeping everything untyped if a value doesn't fit its field
+        section.metadata = serde_json::from_value(serde_json::Value::Object(metadata.clone()))
+            .unwrap_or_else(|_| EntryMetadata {
+                extra: metadata.into_iter().collect(),
+                ..EntryMetadata::default()
+            });
+        section
+    }
+
+    // Moves a front matter block at the start of the body into the metadata
+    fn take_front_matter(mut self) -> Self {
+        if let Some((front_matter, fields, body)) = parse_front_matter(&self.body) {
+            let body = body.trim().to_string();
+            fields.apply(&mut self.metadata);
+            self.metadata.front_matter = Some(front_matter);
+            self.body = body;
+        }
+        self
+    }
+
+    fn into_entry(self, date: &str, day: Date, index: usize) -> JournalEntry {
+        JournalEntry {
+            // Entries written before ids existed are numbered within their day
+            id: self.id.unwrap_or_else(|| format!("{}-{}", date, index + 1)),
+            date: date.to_string(),
+            day,
+            created_at: self.created_at,
+            body: self.body,
+            filename: String::new(),
+            metadata: self.metadata,
+        }
+    }
+}
+
+fn parse_json(content: &str) -> Vec<Section> {
+    let json_entries = serde_json::from_str::<Vec<JsonEntry>>(content)
+        .or_else(|_| serde_json::from_str::<JsonEntry>(content).map(|entry| vec![entry]))
+        .or_else(|_| {
+            // Older versions appended whole JSON documents, separated by "---"
+            // or written back to back
+            content
+                .split(LEGACY_SEPARATOR)
+                .flat_map(|chunk| {
+                    serde_json::Deserializer::from_str(chunk).into_iter::<JsonEntry>()
+                })
+                .collect::<Result<Vec<_>, _>>()
+        });
+
+    match json_entries {
+        Ok(json_entries) => json_entries
+            .into_iter()
+            .map(|entry| Section {
+                id: entry.id,
+                created_at: entry.created_at,
+                metadata: entry.metadata,
+                body: entry.content,
+            })
+            .collect(),
+        // Not valid JSON, show the raw text rather than nothing
+        Err(_) => vec![Section::legacy(content)],
+    }
+}
+
+fn parse_marked(content: &str, marker: (&str, &str), split_legacy: bool) -> Vec<Section> {
+    let mut sections = Vec::new();
+    let mut preamble = String::new();
+    let mut current: Option<(Vec<(String, String)>, String)> = None;
+
+    for line in content.lines() {
+        if let Some(attributes) = parse_marker_line(line, marker) {
+            if let Some((attributes, body)) = current.take() {
+                sections.push(Section::from_attributes(attributes, &body).take_front_matter());
+            }
+            current = Some((attributes, String::new()));
+            continue;
+        }
+
+        let body = match current.as_mut() {
+            Some((_, body)) => body,
+            None => &mut preamble,
+        };
+        body.push_str(line);
+        body.push('\n');
+    }
+
+    if let Some((attributes, body)) = current {
+        sections.push(Section::from_attributes(attributes, &body).take_front_matter());
+    }
+
+    // Text before the first marker comes from files written before entries were
+    // delimited, or by other tools. A file with front matter is one entry.
+    if !preamble.trim().is_empty() {
+        let preamble = preamble.trim_start();
+        let legacy: Vec<Section> = if split_legacy && parse_front_matter(preamble).is_none() {
+            preamble
+                .split(LEGACY_SEPARATOR)
+                .filter(|chunk| !chunk.trim().is_empty())
+                .map(Section::legacy)
+                .collect()
+        } else {
+            vec![Section::legacy(preamble).take_front_matter()]
+        };
+        sections.splice(0..0, legacy);
+    }
+
+    sections
+}
+
+fn render_marked(entries: &[JournalEntry], marker: (&str, &str)) -> Result<String> {
+    let mut output = String::new();
+
+    for (index, entry) in entries.iter().enumerate() {
+        if index > 0 {
+            output.push('\n');
+        }
+
+        let front_matter = entry_front_matter(&entry.metadata);
+        let mut attributes = vec![("id".to_string(), entry.id.clone())];
+        if let Some(created_at) = &entry.created_at {
+            attributes.push(("created_at".to_string(), created_at.clone()));
+        }
+        if let serde_json::Value::Object(metadata) = serde_json::to_value(&entry.metadata)? {
+            attributes.extend(
+                metadata
+                    .into_iter()
+                    .filter(|(key, _)| front_matter.is_none() || !is_front_matter_key(key))
+                    .map(|(key, value)| (key, json_to_attribute(&value))),
+            );
+        }
+
+        output.push_str(marker.0);
+        for (key, value) in attributes {
+            output.push_str(&format!(" {}=\"{}\"", key, escape_attribute(&value)));
+        }
+        output.push(' ');
+        output.push_str(marker.1);
+        output.push('\n');
+        if let Some(front_matter) = front_matter {
+            output.push_str(&front_matter.render(&entry.metadata, entry.word_count())?);
+        }
+        output.push_str(entry.body.trim());
+        output.push('\n');
+    }
+
+    Ok(output)
+}
+
+fn parse_marker_line(line: &str, marker: (&str, &str)) -> Option<Vec<(String, String)>> {
+    let inner = line.trim().strip_prefix(marker.0)?.strip_suffix(marker.1)?;
+    if !inner.is_empty() && !inner.starts_with(' ') {
+        return None;
+    }
+
+    let mut attributes = Vec::new();
+    let mut chars = inner.trim().chars().peekable();
+
+    loop {
+        while chars.next_if(|c| c.is_whitespace()).is_some() {}
+        if chars.peek().is_none() {
+            break;
+        }
+
+        let mut key = String::new();
+        while let Some(c) = chars.next_if(|&c| c != '=') {
+            key.push(c);
+        }
+        if chars.next() != Some('=') || chars.next() != Some('"') {
+            return None;
+        }
+
+        let mut value = String::new();
+        loop {
+            match chars.next()? {
+                '"' => break,
+                '\\' => value.push(chars.next()?),
+                c => value.push(c),
+            }
+        }
+        attributes.push((key, value));
+    }
+
+    Some(attributes)
+}
+
+fn escape_attribute(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', " ")
+}
+
+fn json_to_attribute(value: &serde_json::Value) -> String {
+    match value {
+        serde_json::Value::String(value) => value.clone(),
+        other => other.to_string(),
+    }
+}
+
+fn attribute_to_json(value: String) -> serde_json::Value {
+    match serde_json::from_str::<serde_json::Value>(&value) {
+        Ok(parsed) if !parsed.is_string() => parsed,
+        _ => serde_json::Value::String(value),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use time::{Month, OffsetDateTime};
+
+    fn day() -> Date {
+        Date::from_calendar_date(2024, Month::May, 4).unwrap()
+    }
+
+    fn entries() -> Vec<JournalEntry> {
+        let first = JournalEntry {
+            id: "20240504T081500-ab12".to_string(),
+            ..JournalEntry::new(
+                "2024-05-04",
+                day(),
+                OffsetDateTime::UNIX_EPOCH,
+                "Morning run with @sam.\n\nFelt \"great\". #running",
+            )
+        };
+        let mut second = JournalEntry {
+            id: "20240504T213000-cd34".to_string(),
+            ..JournalEntry::new("2024-05-04", day(), OffsetDateTime::UNIX_EPOCH, "Evening")
+        };
+        second.metadata.mood = Some("4".to_string());
+        second.metadata.pending_formatting = true;
+        vec![first, second]
+    }
+
+    #[test]
+    fn every_format_reads_back_what_it_writes() {
+        for format in [
+            FileFormat::Markdown,
+            FileFormat::PlainText,
+            FileFormat::Json,
+        ] {
+            let written = entries();
+            let content = render_day_file(&format, &written).unwrap();
+            let read = parse_day_file(&content, &format, "2024-05-04", day());
+
+            assert_eq!(read.len(), 2, "{:?}", format);
+            for (read, written) in read.iter().zip(&written) {
+                assert_eq!(read.id, written.id);
+                assert_eq!(read.created_at, written.created_at);
+                assert_eq!(read.body, written.body);
+                assert_eq!(read.metadata.mood, written.metadata.mood);
+                assert_eq!(
+                    read.metadata.pending_formatting,
+                    written.metadata.pending_formatting
+                );
+            }
+            assert_eq!(read[0].metadata.tags, ["running"]);
+            assert_eq!(read[0].metadata.mentions, ["sam"]);
+        }
+    }
+
+    #[test]
+    fn marker_attributes_are_escaped() {
+        let mut entry = entries().remove(0);
+        entry.metadata.prompt = Some("the \"old\" pier \\ harbour".to_string());
+        let content = render_marked(std::slice::from_ref(&entry), MARKDOWN_MARKER).unwrap();
+        let marker = content.lines().next().unwrap();
+
+        let attributes = parse_marker_line(marker, MARKDOWN_MARKER).unwrap();
+        assert!(attributes.contains(&(
+            "prompt".to_string(),
+            "the \"old\" pier \\ harbour".to_string()
+        )));
+    }
+
+    #[test]
+    fn legacy_plain_text_is_split_into_numbered_entries() {
+        let content = "First thought\n---\nSecond thought\n";
+        let read = parse_day_file(content, &FileFormat::PlainText, "2024-05-04", day());
+
+        let ids: Vec<&str> = read.iter().map(|entry| entry.id.as_str()).collect();
+        assert_eq!(ids, ["2024-05-04-1", "2024-05-04-2"]);
+        assert_eq!(read[1].body, "Second thought");
+    }
+
+    #[test]
+    fn text_before_the_first_marker_is_kept() {
+        let content = "Written by hand\n<!-- entry id=\"a\" -->\nLater\n";
+        let read = parse_day_file(content, &FileFormat::Markdown, "2024-05-04", day());
+
+        assert_eq!(read.len(), 2);
+        assert_eq!(read[0].body, "Written by hand");
+        assert_eq!(read[1].id, "a");
+        assert_eq!(read[1].body, "Later");
+    }
+
+    // A day file written by the first versions: the first entry when the file
+    // was created, one appended by the command line right after it, and one
+    // appended by the TUI after a separator
+    const LEGACY_JSON: &str = r#"{
+  "content": "Morning run",
+  "created_at": "2024-05-04 6:18:17.0 +00:00:00",
+  "date": "2024-05-04"
+}{
+  "content": "Lunch by the lake",
+  "created_at": "2024-05-04 12:02:41.0 +00:00:00",
+  "date": "2024-05-04"
+}
+
+---
+
+{
+  "content": "Evening, \"tired\" {but happy}",
+  "date": "2024-05-04",
+  "processed_at": "2024-05-04 21:30:00.0 +00:00:00"
+}
+"#;
+
+    #[test]
+    fn legacy_json_documents_are_split_into_entries() {
+        let read = parse_day_file(LEGACY_JSON, &FileFormat::Json, "2024-05-04", day());
+
+        let bodies: Vec<&str> = read.iter().map(|entry| entry.body.as_str()).collect();
+        assert_eq!(
+            bodies,
+            [
+                "Morning run",
+                "Lunch by the lake",
+                "Evening, \"tired\" {but happy}"
+            ]
+        );
+        assert_eq!(
+            read[2].metadata.processed_at.as_deref(),
+            Some("2024-05-04 21:30:00.0 +00:00:00")
+        );
+    }
+
+    #[test]
+    fn invalid_json_is_shown_as_text() {
+        let read = parse_day_file("{not json", &FileFormat::Json, "2024-05-04", day());
+        assert_eq!(read.len(), 1);
+        assert_eq!(read[0].body, "{not json");
+    }
+}
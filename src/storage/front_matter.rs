@@ -0,0 +1,370 @@
+use super::{normalize_tag, EntryMetadata};
+use anyhow::Result;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+// Keys read into an entry's metadata, in the order they are written
+const KNOWN_KEYS: [&str; 8] = [
+    "title",
+    "mood",
+    "energy",
+    "tags",
+    "location",
+    "weather",
+    "llm_model",
+    "word_count",
+];
+
+// A YAML block between `---` lines or a TOML block between `+++` lines at the
+// start of a Markdown or PlainText entry. Only the keys this version doesn't
+// read are held here, so they are written back as they were.
+#[derive(Debug, Clone, PartialEq)]
+pub enum FrontMatter {
+    Yaml(serde_yaml::Mapping),
+    Toml(toml::Table),
+}
+
+// The keys of a front matter block that become entry metadata. `word_count`
+// is derived from the text, so it is read only to be replaced.
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct FrontMatterFields {
+    #[serde(default, deserialize_with = "scalar")]
+    pub title: Option<String>,
+    #[serde(
+        default,
+        deserialize_with = "scalar",
+        serialize_with = "number_or_text"
+    )]
+    pub mood: Option<String>,
+    #[serde(
+        default,
+        deserialize_with = "scalar",
+        serialize_with = "number_or_text"
+    )]
+    pub energy: Option<String>,
+    #[serde(default, deserialize_with = "tag_list")]
+    pub tags: Vec<String>,
+    #[serde(default, deserialize_with = "scalar")]
+    pub location: Option<String>,
+    #[serde(default, deserialize_with = "scalar")]
+    pub weather: Option<String>,
+    #[serde(default, deserialize_with = "scalar")]
+    pub llm_model: Option<String>,
+    #[serde(default)]
+    word_count: Option<usize>,
+}
+
+impl FrontMatterFields {
+    fn from_metadata(metadata: &EntryMetadata, word_count: usize) -> Self {
+        Self {
+            title: metadata.title.clone(),
+            mood: metadata.mood.clone(),
+            energy: metadata.energy.clone(),
+            tags: metadata.tags.clone(),
+            location: metadata.location.clone(),
+            weather: metadata.weather.clone(),
+            llm_model: metadata.llm_model.clone(),
+            word_count: Some(word_count),
+        }
+    }
+
+    // Front matter values win over the ones in the entry's marker line
+    pub fn apply(self, metadata: &mut EntryMetadata) {
+        let tags = self.tags.iter().filter_map(|tag| normalize_tag(tag));
+        metadata.tags.extend(tags);
+        metadata.tags.sort();
+        metadata.tags.dedup();
+
+        metadata.title = self.title.or(metadata.title.take());
+        metadata.mood = self.mood.or(metadata.mood.take());
+        metadata.energy = self.energy.or(metadata.energy.take());
+        metadata.location = self.location.or(metadata.location.take());
+        metadata.weather = self.weather.or(metadata.weather.take());
+        metadata.llm_model = self.llm_model.or(metadata.llm_model.take());
+    }
+}
+
+impl FrontMatter {
+    fn delimiter(&self) -> &'static str {
+        match self {
+            FrontMatter::Yaml(_) => "---",
+            FrontMatter::Toml(_) => "+++",
+        }
+    }
+
+    // The block for an entry, delimiters included: the metadata fields and
+    // word count first, then the kept keys
+    pub fn render(&self, metadata: &EntryMetadata, word_count: usize) -> Result<String> {
+        let fields = FrontMatterFields::from_metadata(metadata, word_count);
+        let content = match self {
+            FrontMatter::Yaml(kept) => {
+                let mut mapping = match serde_yaml::to_value(&fields)? {
+                    serde_yaml::Value::Mapping(mapping) => mapping,
+                    _ => serde_yaml::Mapping::new(),
+                };
+                mapping.retain(|_, value| !is_empty_yaml(value));
+                for (key, value) in kept {
+                    if !mapping.contains_key(key) {
+                        mapping.insert(key.clone(), value.clone());
+                    }
+                }
+                serde_yaml::to_string(&mapping)?
+            }
+            FrontMatter::Toml(kept) => {
+                let mut table = toml::Table::try_from(&fields)?;
+                table.retain(
+                    |_, value| !matches!(value, toml::Value::Array(items) if items.is_empty()),
+                );
+                for (key, value) in kept {
+                    table.entry(key.clone()).or_insert_with(|| value.clone());
+                }
+                toml::to_string(&table)?
+            }
+        };
+
+        let delimiter = self.delimiter();
+        Ok(format!("{}\n{}{}\n", delimiter, content, delimiter))
+    }
+}
+
+impl FrontMatter {
+    // The kept keys as JSON, for writing the entry to a JSON day file
+    pub fn kept_fields(&self) -> serde_json::Map<String, serde_json::Value> {
+        let value = match self {
+            FrontMatter::Yaml(kept) => serde_json::to_value(kept),
+            FrontMatter::Toml(kept) => serde_json::to_value(kept),
+        };
+        match value {
+            Ok(serde_json::Value::Object(fields)) => fields,
+            _ => serde_json::Map::new(),
+        }
+    }
+}
+
+// The front matter an entry is written with: the block it was read with, or a
+// new YAML one once it has a title, mood, energy, location or weather
+pub fn entry_front_matter(metadata: &EntryMetadata) -> Option<FrontMatter> {
+    metadata.front_matter.clone().or_else(|| {
+        [
+            &metadata.title,
+            &metadata.mood,
+            &metadata.energy,
+            &metadata.location,
+            &metadata.weather,
+        ]
+        .iter()
+        .any(|field| field.is_some())
+        .then(|| FrontMatter::Yaml(serde_yaml::Mapping::new()))
+    })
+}
+
+// Whether a key of the marker line is written to front matter instead
+pub fn is_front_matter_key(key: &str) -> bool {
+    KNOWN_KEYS.contains(&key)
+}
+
+// Splits a front matter block off the start of the text. A block that isn't
+// valid YAML or TOML is left in the text.
+pub fn parse_front_matter(text: &str) -> Option<(FrontMatter, FrontMatterFields, &str)> {
+    let (delimiter, block, rest) = split_front_matter(text)?;
+    match delimiter {
+        "---" => {
+            let mut mapping: serde_yaml::Mapping = match block.trim() {
+                "" => serde_yaml::Mapping::new(),
+                block => serde_yaml::from_str(block).ok()?,
+            };
+            let mut known = serde_yaml::Mapping::new();
+            for key in KNOWN_KEYS {
+                if let Some(value) = mapping.get(key) {
+                    known.insert(key.into(), value.clone());
+                }
+            }
+            // Values that don't fit their field are kept untouched instead
+            let Ok(fields) = serde_yaml::from_value(serde_yaml::Value::Mapping(known)) else {
+                return Some((
+                    FrontMatter::Yaml(mapping),
+                    FrontMatterFields::default(),
+                    rest,
+                ));
+            };
+            mapping.retain(|key, _| !key.as_str().is_some_and(is_front_matter_key));
+            Some((FrontMatter::Yaml(mapping), fields, rest))
+        }
+        _ => {
+            let mut table: toml::Table = toml::from_str(block).ok()?;
+            let known: toml::Table = KNOWN_KEYS
+                .iter()
+                .filter_map(|key| Some((key.to_string(), table.get(*key)?.clone())))
+                .collect();
+            let Ok(fields) = toml::Value::Table(known).try_into() else {
+                return Some((FrontMatter::Toml(table), FrontMatterFields::default(), rest));
+            };
+            table.retain(|key, _| !is_front_matter_key(key));
+            Some((FrontMatter::Toml(table), fields, rest))
+        }
+    }
+}
+
+// The delimiter, the lines between a leading `---` or `+++` and the matching
+// closing line (`...` also closes YAML), and the text after
+fn split_front_matter(text: &str) -> Option<(&'static str, &str, &str)> {
+    let (delimiter, rest) = ["---", "+++"].into_iter().find_map(|delimiter| {
+        let rest = text.strip_prefix(delimiter)?;
+        let rest = rest
+            .strip_prefix('\n')
+            .or_else(|| rest.strip_prefix("\r\n"))?;
+        Some((delimiter, rest))
+    })?;
+
+    let mut offset = 0;
+    for line in rest.split_inclusive('\n') {
+        let trimmed = line.trim_end();
+        if trimmed == delimiter || (delimiter == "---" && trimmed == "...") {
+            return Some((delimiter, &rest[..offset], &rest[offset + line.len()..]));
+        }
+        offset += line.len();
+    }
+    None
+}
+
+fn is_empty_yaml(value: &serde_yaml::Value) -> bool {
+    match value {
+        serde_yaml::Value::Null => true,
+        serde_yaml::Value::Sequence(items) => items.is_empty(),
+        _ => false,
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum Scalar {
+    Text(String),
+    Integer(i64),
+    Float(f64),
+    Bool(bool),
+}
+
+// `mood: 4` and `mood: "4"` are the same
+fn scalar<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Option<String>, D::Error> {
+    Ok(
+        Option::<Scalar>::deserialize(deserializer)?.map(|value| match value {
+            Scalar::Text(text) => text,
+            Scalar::Integer(number) => number.to_string(),
+            Scalar::Float(number) => number.to_string(),
+            Scalar::Bool(flag) => flag.to_string(),
+        }),
+    )
+}
+
+// Writes `mood: 4` rather than `mood: '4'`
+fn number_or_text<S: Serializer>(value: &Option<String>, serializer: S) -> Result<S::Ok, S::Error> {
+    match value.as_deref().map(|text| (text, text.parse::<i64>())) {
+        Some((_, Ok(number))) => serializer.serialize_i64(number),
+        Some((text, Err(_))) => serializer.serialize_str(text),
+        None => serializer.serialize_none(),
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum TagList {
+    List(Vec<String>),
+    Text(String),
+}
+
+// `tags: [a, b]`, a `- item` list or `tags: a, b`
+fn tag_list<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Vec<String>, D::Error> {
+    Ok(match Option::<TagList>::deserialize(deserializer)? {
+        Some(TagList::List(tags)) => tags,
+        Some(TagList::Text(text)) => text.split(',').map(str::to_string).collect(),
+        None => Vec::new(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn yaml_fields_are_read_and_other_keys_kept() {
+        let text =
+            "---\ntitle: Lake day\nmood: 4\ntags: swim, Outdoors\ncustom: [1, 2]\n---\nBody\n";
+        let (front_matter, fields, rest) = parse_front_matter(text).unwrap();
+
+        assert_eq!(fields.title.as_deref(), Some("Lake day"));
+        assert_eq!(fields.mood.as_deref(), Some("4"));
+        assert_eq!(fields.tags, ["swim", " Outdoors"]);
+        assert_eq!(rest, "Body\n");
+
+        let FrontMatter::Yaml(kept) = &front_matter else {
+            panic!("expected YAML, got {:?}", front_matter);
+        };
+        assert_eq!(kept.len(), 1);
+        assert!(kept.contains_key("custom"));
+
+        let mut metadata = EntryMetadata::default();
+        fields.apply(&mut metadata);
+        assert_eq!(metadata.tags, ["outdoors", "swim"]);
+        assert_eq!(
+            front_matter.render(&metadata, 1).unwrap(),
+            "---\ntitle: Lake day\nmood: 4\ntags:\n- outdoors\n- swim\nword_count: 1\ncustom:\n- 1\n- 2\n---\n"
+        );
+    }
+
+    #[test]
+    fn toml_round_trips() {
+        let text = "+++\ntitle = \"Lake day\"\nenergy = 3\ncustom = \"kept\"\n+++\nBody";
+        let (front_matter, fields, rest) = parse_front_matter(text).unwrap();
+        assert_eq!(fields.energy.as_deref(), Some("3"));
+        assert_eq!(rest, "Body");
+
+        let mut metadata = EntryMetadata::default();
+        fields.apply(&mut metadata);
+        let rendered = front_matter.render(&metadata, 1).unwrap();
+        assert_eq!(
+            rendered,
+            "+++\ncustom = \"kept\"\nenergy = 3\ntitle = \"Lake day\"\nword_count = 1\n+++\n"
+        );
+
+        let (again, fields, _) = parse_front_matter(&format!("{}Body", rendered)).unwrap();
+        assert_eq!(again, front_matter);
+        assert_eq!(fields.title.as_deref(), Some("Lake day"));
+    }
+
+    #[test]
+    fn blocks_that_dont_parse_stay_in_the_text() {
+        assert!(parse_front_matter("---\ntitle: [unclosed\n---\nBody").is_none());
+        assert!(parse_front_matter("+++\nnot toml\n+++\nBody").is_none());
+        assert!(parse_front_matter("---\ntitle: Never closed\nBody").is_none());
+        assert!(parse_front_matter("Body\n---\ntitle: Later\n---\n").is_none());
+
+        let (_, fields, rest) = parse_front_matter("---\ntitle: Dots\n...\nBody").unwrap();
+        assert_eq!(fields.title.as_deref(), Some("Dots"));
+        assert_eq!(rest, "Body");
+    }
+
+    #[test]
+    fn values_that_dont_fit_are_kept_untouched() {
+        let (front_matter, fields, _) =
+            parse_front_matter("---\nmood:\n  morning: 2\n---\nBody").unwrap();
+        assert!(fields.mood.is_none());
+        let FrontMatter::Yaml(kept) = front_matter else {
+            panic!("expected YAML");
+        };
+        assert!(kept.contains_key("mood"));
+    }
+
+    #[test]
+    fn new_entries_get_yaml_once_they_have_fields() {
+        let mut metadata = EntryMetadata {
+            tags: vec!["swim".to_string()],
+            ..EntryMetadata::default()
+        };
+        assert!(entry_front_matter(&metadata).is_none());
+
+        metadata.weather = Some("sunny".to_string());
+        assert_eq!(
+            entry_front_matter(&metadata),
+            Some(FrontMatter::Yaml(serde_yaml::Mapping::new()))
+        );
+    }
+}
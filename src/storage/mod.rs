@@ -0,0 +1,469 @@
+mod backup;
+mod crypto;
+mod entry;
+mod format;
+mod front_matter;
+mod mood;
+mod tags;
+mod trash;
+
+pub use backup::*;
+pub use crypto::*;
+pub use entry::*;
+pub use format::*;
+pub use front_matter::*;
+pub use mood::*;
+pub use tags::*;
+pub use trash::*;
+
+use crate::config::{Config, FileFormat};
+use anyhow::{anyhow, Result};
+use std::fs;
+use std::path::{Path, PathBuf};
+use time::Date;
+
+// A journal file holding the entries of one day
+pub struct DayFile {
+    pub path: PathBuf,
+    pub date: String,
+    pub day: Date,
+    pub format: FileFormat,
+}
+
+impl DayFile {
+    pub fn read(&self) -> Result<Vec<JournalEntry>> {
+        read_day_file(&self.path, &self.format, &self.date, self.day)
+    }
+}
+
+// Day files of every format in the journal directory whose names match the
+// configured date format. A day can have files in several formats.
+pub fn list_day_files(config: &Config) -> Result<Vec<DayFile>> {
+    let mut day_files = Vec::new();
+
+    if !config.journal_directory.exists() {
+        return Ok(day_files);
+    }
+
+    let date_format = config.date_formatter()?;
+
+    for entry in fs::read_dir(&config.journal_directory)? {
+        let path = entry?.path();
+
+        let Some((stem, format)) = parse_file_name(&path) else {
+            continue;
+        };
+        let Some(day) = date_format.parse(stem) else {
+            continue;
+        };
+
+        day_files.push(DayFile {
+            date: stem.to_string(),
+            day,
+            format,
+            path,
+        });
+    }
+
+    Ok(day_files)
+}
+
+// The stem and format of a journal file name: "2024-05-04.md", or
+// "2024-05-04.md.enc" when it is encrypted
+pub fn parse_file_name(path: &Path) -> Option<(&str, FileFormat)> {
+    let name = path.file_name()?.to_str()?;
+    let name = name
+        .strip_suffix(ENCRYPTED_EXTENSION)
+        .and_then(|name| name.strip_suffix('.'))
+        .unwrap_or(name);
+    let (stem, extension) = name.rsplit_once('.')?;
+    Some((stem, FileFormat::from_extension(extension)?))
+}
+
+// "2024-05-04.md", with the encrypted extension in an encrypted journal
+pub fn journal_file_name(stem: &str, format: &FileFormat, encrypted: bool) -> String {
+    if encrypted {
+        format!("{}.{}.{}", stem, format.extension(), ENCRYPTED_EXTENSION)
+    } else {
+        format!("{}.{}", stem, format.extension())
+    }
+}
+
+// Entries of one day from its files of every format, in time order
+pub fn read_day(config: &Config, day: Date) -> Result<Vec<JournalEntry>> {
+    let mut entries = Vec::new();
+    for day_file in list_day_files(config)?
+        .iter()
+        .filter(|day_file| day_file.day == day)
+    {
+        entries.extend(day_file.read()?);
+    }
+    entries.sort_by_key(|entry| entry.timestamp());
+    Ok(entries)
+}
+
+pub fn read_day_file(
+    path: &Path,
+    format: &FileFormat,
+    date: &str,
+    day: Date,
+) -> Result<Vec<JournalEntry>> {
+    let content = read_file(path)?;
+    let filename = path
+        .file_name()
+        .map(|name| name.to_string_lossy().to_string())
+        .unwrap_or_default();
+
+    let mut entries = parse_day_file(&content, format, date, day);
+    for entry in &mut entries {
+        entry.filename = filename.clone();
+    }
+    Ok(entries)
+}
+
+// Writes a day file, snapshotting the version it replaces first when
+// `auto_backup` is on
+pub fn write_day_file(
+    config: &Config,
+    path: &Path,
+    format: &FileFormat,
+    entries: &[JournalEntry],
+) -> Result<()> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    snapshot_file(config, path)?;
+
+    write_file(path, &render_day_file(format, entries)?)
+}
+
+// The day file an entry was loaded from
+pub fn entry_path(config: &Config, entry: &JournalEntry) -> PathBuf {
+    config.journal_directory.join(&entry.filename)
+}
+
+// Adds an entry to the day file for its date, keeping the day in time order.
+// Tags are extracted from its text.
+pub fn insert_entry(config: &Config, entry: JournalEntry) -> Result<PathBuf> {
+    insert_entry_as(config, entry, &config.file_format)
+}
+
+// Adds an entry to the day file of the given format for its date
+pub(super) fn insert_entry_as(
+    config: &Config,
+    mut entry: JournalEntry,
+    format: &FileFormat,
+) -> Result<PathBuf> {
+    let path =
+        config
+            .journal_directory
+            .join(journal_file_name(&entry.date, format, is_encrypted(config)));
+    let mut entries = if path.exists() {
+        read_day_file(&path, format, &entry.date, entry.day)?
+    } else {
+        Vec::new()
+    };
+
+    entry.filename = path
+        .file_name()
+        .map(|name| name.to_string_lossy().to_string())
+        .unwrap_or_default();
+    entry.update_tags(None);
+    entries.push(entry);
+    entries.sort_by_key(|entry| entry.timestamp());
+
+    write_day_file(config, &path, format, &entries)?;
+    Ok(path)
+}
+
+// Format of the day file an entry was loaded from
+fn entry_format(config: &Config, entry: &JournalEntry) -> FileFormat {
+    entry.format().unwrap_or_else(|| config.file_format.clone())
+}
+
+// Replaces the stored entry with the same id, re-extracting its tags
+pub fn update_entry(config: &Config, entry: &JournalEntry) -> Result<PathBuf> {
+    let path = entry_path(config, entry);
+    let format = entry_format(config, entry);
+    let mut entries = read_day_file(&path, &format, &entry.date, entry.day)?;
+
+    let stored = entries
+        .iter_mut()
+        .find(|stored| stored.id == entry.id)
+        .ok_or_else(|| anyhow!("Entry {} is no longer in {}", entry.id, path.display()))?;
+    let previous_body = std::mem::take(&mut stored.body);
+    *stored = entry.clone();
+    stored.update_tags(Some(&previous_body));
+
+    write_day_file(config, &path, &format, &entries)?;
+    Ok(path)
+}
+
+// Removes an entry from its day file; the file is deleted with its last entry
+pub fn remove_entry(config: &Config, entry: &JournalEntry) -> Result<PathBuf> {
+    let path = entry_path(config, entry);
+    let format = entry_format(config, entry);
+    let mut entries = read_day_file(&path, &format, &entry.date, entry.day)?;
+
+    let count = entries.len();
+    entries.retain(|stored| stored.id != entry.id);
+    if entries.len() == count {
+        return Err(anyhow!(
+            "Entry {} is no longer in {}",
+            entry.id,
+            path.display()
+        ));
+    }
+
+    if entries.is_empty() {
+        snapshot_file(config, &path)?;
+        fs::remove_file(&path)?;
+    } else {
+        write_day_file(config, &path, &format, &entries)?;
+    }
+    Ok(path)
+}
+
+// Moves an entry to another day, into a file of the configured format.
+// `created_at` keeps the time it was written.
+pub fn move_entry(config: &Config, entry: &JournalEntry, day: Date) -> Result<PathBuf> {
+    let date = config.date_formatter()?.format(day)?;
+    if date == entry.date {
+        return Ok(entry_path(config, entry));
+    }
+
+    let moved = JournalEntry {
+        date,
+        day,
+        ..entry.clone()
+    };
+    // Write the new copy first so a failure can't lose the entry
+    let path = insert_entry(config, moved)?;
+    remove_entry(config, entry)?;
+    Ok(path)
+}
+
+// Renames a day file to another date, merging it into that day's entries
+pub fn move_day_file(config: &Config, day_file: &DayFile, day: Date) -> Result<PathBuf> {
+    let date = config.date_formatter()?.format(day)?;
+    if date == day_file.date {
+        return Ok(day_file.path.clone());
+    }
+
+    let path = day_file.path.with_file_name(journal_file_name(
+        &date,
+        &day_file.format,
+        is_encrypted_path(&day_file.path),
+    ));
+
+    if !path.exists() {
+        fs::rename(&day_file.path, &path)?;
+        return Ok(path);
+    }
+
+    let mut entries = read_day_file(&path, &day_file.format, &date, day)?;
+    for entry in day_file.read()? {
+        entries.push(JournalEntry {
+            date: date.clone(),
+            day,
+            ..entry
+        });
+    }
+    entries.sort_by_key(|entry| entry.timestamp());
+
+    write_day_file(config, &path, &day_file.format, &entries)?;
+    fs::remove_file(&day_file.path)?;
+    Ok(path)
+}
+
+// Renames the day files named with a previous date format to the configured
+// one, merging them into the day's file when it already exists. Trashed
+// entries are read under either format, so they are left as they are. When a
+// file can't be renamed, those already renamed are given their previous names
+// back.
+pub fn rename_day_files(config: &Config, previous_format: &str) -> Result<usize> {
+    let previous = Config {
+        date_format: previous_format.to_string(),
+        ..config.clone()
+    };
+    let mut renamed = 0;
+    for day_file in list_day_files(&previous)? {
+        match move_day_file(config, &day_file, day_file.day) {
+            Ok(path) if path != day_file.path => renamed += 1,
+            Ok(_) => {}
+            Err(e) => {
+                if renamed > 0 {
+                    let _ = rename_day_files(&previous, &config.date_format);
+                }
+                return Err(e);
+            }
+        }
+    }
+    Ok(renamed)
+}
+
+// Rewrites a day file in another format, merging it into the file of that
+// format for the same day when there is one. Entries keep their ids,
+// timestamps and metadata, and an encrypted file stays encrypted.
+pub fn convert_day_file(
+    config: &Config,
+    day_file: &DayFile,
+    format: &FileFormat,
+) -> Result<PathBuf> {
+    let path = day_file.path.with_file_name(journal_file_name(
+        &day_file.date,
+        format,
+        is_encrypted_path(&day_file.path),
+    ));
+    if *format == day_file.format {
+        return Ok(path);
+    }
+
+    let mut entries = if path.exists() {
+        read_day_file(&path, format, &day_file.date, day_file.day)?
+    } else {
+        Vec::new()
+    };
+    for entry in read_day_file(
+        &day_file.path,
+        &day_file.format,
+        &day_file.date,
+        day_file.day,
+    )? {
+        if !entries.iter().any(|existing| existing.id == entry.id) {
+            entries.push(entry);
+        }
+    }
+    entries.sort_by_key(|entry| entry.timestamp());
+
+    // The converted file is written before the original goes away
+    write_day_file(config, &path, format, &entries)?;
+    fs::remove_file(&day_file.path)?;
+    Ok(path)
+}
+
+// A journal in its own temporary directory for tests, removed when dropped.
+// Backups go next to it rather than inside it.
+#[cfg(test)]
+pub(crate) struct TempJournal {
+    pub config: Config,
+    root: PathBuf,
+}
+
+#[cfg(test)]
+impl TempJournal {
+    pub fn new(name: &str) -> Self {
+        let root =
+            std::env::temp_dir().join(format!("rusty_diary-test-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&root);
+        let mut config = Config {
+            journal_directory: root.join("journal"),
+            ..Config::default()
+        };
+        config.backup.directory = Some(root.join("backups"));
+        fs::create_dir_all(&config.journal_directory).unwrap();
+        Self { config, root }
+    }
+
+    // Adds an entry written at `hour` on 2024-05-04
+    pub fn add(&self, hour: u8, body: &str) -> JournalEntry {
+        let day = Date::from_calendar_date(2024, time::Month::May, 4).unwrap();
+        let created_at = day.with_hms(hour, 0, 0).unwrap().assume_utc();
+        let mut entry = JournalEntry::new("2024-05-04", day, created_at, body);
+        let path = insert_entry(&self.config, entry.clone()).unwrap();
+        entry.filename = path.file_name().unwrap().to_string_lossy().to_string();
+        entry
+    }
+}
+
+#[cfg(test)]
+impl Drop for TempJournal {
+    fn drop(&mut self) {
+        let _ = fs::remove_dir_all(&self.root);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn day_files_are_renamed_to_a_new_date_format() {
+        let journal = TempJournal::new("rename");
+        journal.add(6, "Lake swim");
+        let updated = Config {
+            date_format: "%Y%m%d".to_string(),
+            ..journal.config.clone()
+        };
+
+        assert_eq!(rename_day_files(&updated, "%Y-%m-%d").unwrap(), 1);
+        let day_files = list_day_files(&updated).unwrap();
+        assert_eq!(day_files.len(), 1);
+        assert_eq!(day_files[0].date, "20240504");
+        assert_eq!(day_files[0].read().unwrap()[0].body, "Lake swim");
+    }
+
+    #[test]
+    fn a_failed_rename_keeps_the_previous_names() {
+        let journal = TempJournal::new("rename-failed");
+        journal.add(6, "Lake swim");
+        let directory = &journal.config.journal_directory;
+        // Merging into a file that can't be read fails
+        fs::write(directory.join("2024-05-05.md.enc"), "damaged").unwrap();
+        fs::write(directory.join("20240505.md.enc"), "damaged").unwrap();
+        let updated = Config {
+            date_format: "%Y%m%d".to_string(),
+            ..journal.config.clone()
+        };
+
+        assert!(rename_day_files(&updated, "%Y-%m-%d").is_err());
+        assert!(directory.join("2024-05-04.md").exists());
+        assert!(!directory.join("20240504.md").exists());
+    }
+
+    #[test]
+    fn entries_move_to_another_day() {
+        let journal = TempJournal::new("move-entry");
+        let config = &journal.config;
+        let lake = journal.add(6, "Lake swim");
+        journal.add(7, "Heron at dawn");
+        let day = Date::from_calendar_date(2024, time::Month::May, 6).unwrap();
+
+        let path = move_entry(config, &lake, day).unwrap();
+        assert_eq!(path, config.journal_directory.join("2024-05-06.md"));
+        let moved = read_day(config, day).unwrap();
+        assert_eq!(moved.len(), 1);
+        assert_eq!(
+            (moved[0].id.as_str(), moved[0].date.as_str()),
+            (lake.id.as_str(), "2024-05-06")
+        );
+        assert_eq!(moved[0].created_at, lake.created_at);
+        assert_eq!(read_day(config, lake.day).unwrap()[0].body, "Heron at dawn");
+    }
+
+    #[test]
+    fn renamed_day_files_merge_into_the_days_file() {
+        let journal = TempJournal::new("move-day-file");
+        let config = &journal.config;
+        let lake = journal.add(6, "Lake swim");
+        journal.add(7, "Heron at dawn");
+        let day = Date::from_calendar_date(2024, time::Month::May, 6).unwrap();
+        move_entry(config, &lake, day).unwrap();
+
+        let day_file = list_day_files(config)
+            .unwrap()
+            .into_iter()
+            .find(|day_file| day_file.day == lake.day)
+            .unwrap();
+        move_day_file(config, &day_file, day).unwrap();
+
+        assert!(read_day(config, lake.day).unwrap().is_empty());
+        let bodies: Vec<String> = read_day(config, day)
+            .unwrap()
+            .into_iter()
+            .map(|entry| entry.body)
+            .collect();
+        assert_eq!(bodies, ["Lake swim", "Heron at dawn"]);
+        assert_eq!(list_day_files(config).unwrap().len(), 1);
+    }
+}
@@ -0,0 +1,254 @@
+use super::JournalEntry;
+use std::collections::BTreeMap;
+use time::{Date, Weekday};
+
+// The emoji scale, from 1 (worst) to 5 (best). Ratings are stored as numbers.
+pub const MOOD_EMOJI: [&str; 5] = ["😞", "😕", "😐", "🙂", "😄"];
+pub const ENERGY_EMOJI: [&str; 5] = ["🪫", "🥱", "🚶", "🏃", "⚡"];
+
+pub const MAX_RATING: u8 = 5;
+
+// A rating typed by the user or read from an entry: `1` to `5`, or an emoji
+// of the scale
+pub fn parse_rating(input: &str, scale: &[&str; 5]) -> Option<u8> {
+    let input = input.trim();
+    if let Ok(rating) = input.parse::<u8>() {
+        return (1..=MAX_RATING).contains(&rating).then_some(rating);
+    }
+    scale
+        .iter()
+        .position(|emoji| input.starts_with(emoji))
+        .map(|index| index as u8 + 1)
+}
+
+// "4 🙂"
+pub fn rating_label(rating: u8, scale: &[&str; 5]) -> String {
+    let emoji = scale[(rating.clamp(1, MAX_RATING) - 1) as usize];
+    format!("{} {}", rating, emoji)
+}
+
+impl JournalEntry {
+    pub fn mood_rating(&self) -> Option<u8> {
+        self.metadata
+            .mood
+            .as_deref()
+            .and_then(|mood| parse_rating(mood, &MOOD_EMOJI))
+    }
+
+    pub fn energy_rating(&self) -> Option<u8> {
+        self.metadata
+            .energy
+            .as_deref()
+            .and_then(|energy| parse_rating(energy, &ENERGY_EMOJI))
+    }
+}
+
+// Average ratings of the entries that have them
+#[derive(Debug, Default)]
+pub struct MoodInsights {
+    // Average mood and energy per day, oldest first
+    pub days: Vec<DayMood>,
+    // Average mood per weekday, Monday first, with the number of entries
+    pub weekdays: [(f64, usize); 7],
+    // Average mood of the entries carrying each tag or mention, with the
+    // number of entries; most used first
+    pub labels: Vec<(String, f64, usize)>,
+    // Average mood over all rated entries
+    pub overall: Option<f64>,
+}
+
+#[derive(Debug)]
+pub struct DayMood {
+    pub day: Date,
+    pub mood: Option<f64>,
+    pub energy: Option<f64>,
+}
+
+impl MoodInsights {
+    pub fn new<'a>(entries: impl IntoIterator<Item = &'a JournalEntry>) -> Self {
+        let mut days: BTreeMap<Date, (Average, Average)> = BTreeMap::new();
+        let mut weekdays = [Average::default(); 7];
+        let mut labels: BTreeMap<String, Average> = BTreeMap::new();
+        let mut overall = Average::default();
+
+        for entry in entries {
+            let mood = entry.mood_rating();
+            let energy = entry.energy_rating();
+            if mood.is_none() && energy.is_none() {
+                continue;
+            }
+
+            let (day_mood, day_energy) = days.entry(entry.day).or_default();
+            day_energy.add(energy);
+            let Some(mood) = mood else {
+                continue;
+            };
+            day_mood.add(Some(mood));
+            overall.add(Some(mood));
+            weekdays[weekday_index(entry.day.weekday())].add(Some(mood));
+            for label in entry.labels() {
+                labels.entry(label).or_default().add(Some(mood));
+            }
+        }
+
+        let mut labels: Vec<(String, f64, usize)> = labels
+            .into_iter()
+            .filter_map(|(label, average)| Some((label, average.value()?, average.count)))
+            .collect();
+        labels.sort_by_key(|(_, _, count)| std::cmp::Reverse(*count));
+
+        Self {
+            days: days
+                .into_iter()
+                .map(|(day, (mood, energy))| DayMood {
+                    day,
+                    mood: mood.value(),
+                    energy: energy.value(),
+                })
+                .collect(),
+            weekdays: weekdays.map(|average| (average.value().unwrap_or(0.0), average.count)),
+            labels,
+            overall: overall.value(),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.days.is_empty()
+    }
+}
+
+#[derive(Debug, Default, Clone, Copy)]
+struct Average {
+    sum: f64,
+    count: usize,
+}
+
+impl Average {
+    fn add(&mut self, rating: Option<u8>) {
+        if let Some(rating) = rating {
+            self.sum += f64::from(rating);
+            self.count += 1;
+        }
+    }
+
+    fn value(&self) -> Option<f64> {
+        (self.count > 0).then(|| self.sum / self.count as f64)
+    }
+}
+
+fn weekday_index(weekday: Weekday) -> usize {
+    weekday.number_days_from_monday() as usize
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use time::Month;
+
+    fn may(day: u8) -> Date {
+        Date::from_calendar_date(2024, Month::May, day).unwrap()
+    }
+
+    fn entry(day: u8, mood: Option<&str>, energy: Option<&str>, tags: &[&str]) -> JournalEntry {
+        let day = may(day);
+        let mut entry =
+            JournalEntry::new(&day.to_string(), day, day.midnight().assume_utc(), "Entry");
+        entry.metadata.mood = mood.map(str::to_string);
+        entry.metadata.energy = energy.map(str::to_string);
+        entry.metadata.tags = tags.iter().map(|tag| tag.to_string()).collect();
+        entry
+    }
+
+    #[test]
+    fn ratings_are_numbers_or_emoji_of_the_scale() {
+        assert_eq!(parse_rating("1", &MOOD_EMOJI), Some(1));
+        assert_eq!(parse_rating(" 5 ", &MOOD_EMOJI), Some(5));
+        assert_eq!(parse_rating("🙂", &MOOD_EMOJI), Some(4));
+        assert_eq!(parse_rating("😄 great day", &MOOD_EMOJI), Some(5));
+        assert_eq!(parse_rating("⚡", &ENERGY_EMOJI), Some(5));
+        assert_eq!(parse_rating("⚡", &MOOD_EMOJI), None);
+    }
+
+    #[test]
+    fn out_of_range_and_non_numeric_ratings_are_ignored() {
+        for input in ["0", "6", "255", "256", "-1", "3.5", "", "good", "four"] {
+            assert_eq!(parse_rating(input, &MOOD_EMOJI), None, "{:?}", input);
+        }
+
+        assert_eq!(entry(4, Some("9"), Some("tired"), &[]).mood_rating(), None);
+        assert_eq!(
+            entry(4, Some("9"), Some("tired"), &[]).energy_rating(),
+            None
+        );
+    }
+
+    #[test]
+    fn labels_clamp_out_of_range_ratings() {
+        assert_eq!(rating_label(4, &MOOD_EMOJI), "4 🙂");
+        assert_eq!(rating_label(0, &MOOD_EMOJI), "0 😞");
+        assert_eq!(rating_label(7, &ENERGY_EMOJI), "7 ⚡");
+    }
+
+    #[test]
+    fn days_are_averaged_and_days_without_a_check_in_are_left_out() {
+        let entries = [
+            entry(4, Some("2"), Some("4"), &[]),
+            entry(4, Some("😄"), None, &[]),
+            // No check-in on the 5th, an unreadable one on the 6th
+            entry(5, None, None, &[]),
+            entry(6, Some("great"), None, &[]),
+            entry(7, None, Some("🥱"), &[]),
+            entry(8, Some("3"), Some("3"), &[]),
+        ];
+
+        let insights = MoodInsights::new(&entries);
+
+        let days: Vec<(Date, Option<f64>, Option<f64>)> = insights
+            .days
+            .iter()
+            .map(|day| (day.day, day.mood, day.energy))
+            .collect();
+        assert_eq!(
+            days,
+            [
+                (may(4), Some(3.5), Some(4.0)),
+                (may(7), None, Some(2.0)),
+                (may(8), Some(3.0), Some(3.0)),
+            ]
+        );
+        assert_eq!(insights.overall, Some(10.0 / 3.0));
+        assert!(!insights.is_empty());
+    }
+
+    #[test]
+    fn weekdays_and_labels_only_count_rated_entries() {
+        let entries = [
+            // Saturday
+            entry(4, Some("5"), None, &["hike"]),
+            entry(4, None, Some("5"), &["hike"]),
+            // Monday
+            entry(6, Some("1"), None, &["work"]),
+            entry(13, Some("2"), None, &["work", "hike"]),
+        ];
+
+        let insights = MoodInsights::new(&entries);
+
+        assert_eq!(insights.weekdays[0], (1.5, 2));
+        assert_eq!(insights.weekdays[5], (5.0, 1));
+        assert_eq!(insights.weekdays[6], (0.0, 0));
+        assert_eq!(
+            insights.labels,
+            [("#hike".to_string(), 3.5, 2), ("#work".to_string(), 1.5, 2)]
+        );
+    }
+
+    #[test]
+    fn entries_without_ratings_give_no_insights() {
+        let insights = MoodInsights::new(&[entry(4, None, None, &["hike"])]);
+
+        assert!(insights.is_empty());
+        assert_eq!(insights.overall, None);
+        assert!(insights.labels.is_empty());
+        assert_eq!(insights.weekdays, [(0.0, 0); 7]);
+    }
+}
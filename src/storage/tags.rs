@@ -0,0 +1,236 @@
+use super::{parse_front_matter, JournalEntry};
+use std::collections::{BTreeMap, BTreeSet};
+
+// Tags and mentions found in an entry's text, lowercased and sorted
+#[derive(Debug, Default, PartialEq)]
+pub struct ExtractedTags {
+    pub tags: Vec<String>,
+    pub mentions: Vec<String>,
+}
+
+// Selected tag labels (`#tag` or `@person`); entries match when they carry all
+// of them, or any of them when `match_any` is set
+#[derive(Debug, Clone, Default)]
+pub struct TagFilter {
+    pub labels: BTreeSet<String>,
+    pub match_any: bool,
+}
+
+impl TagFilter {
+    pub fn is_empty(&self) -> bool {
+        self.labels.is_empty()
+    }
+
+    pub fn matches(&self, entry: &JournalEntry) -> bool {
+        if self.labels.is_empty() {
+            return true;
+        }
+        let labels: BTreeSet<String> = entry.labels().collect();
+        if self.match_any {
+            self.labels.iter().any(|label| labels.contains(label))
+        } else {
+            self.labels.iter().all(|label| labels.contains(label))
+        }
+    }
+
+    pub fn toggle(&mut self, label: &str) {
+        if !self.labels.remove(label) {
+            self.labels.insert(label.to_string());
+        }
+    }
+
+    // "#work AND @alice"
+    pub fn describe(&self) -> String {
+        let separator = if self.match_any { " OR " } else { " AND " };
+        self.labels
+            .iter()
+            .map(String::as_str)
+            .collect::<Vec<_>>()
+            .join(separator)
+    }
+}
+
+// The label for a tag typed by the user: `work` and `#Work` are `#work`,
+// `@alice` stays a mention
+pub fn tag_label(input: &str) -> String {
+    let input = input.trim().to_lowercase();
+    if input.starts_with('@') || input.starts_with('#') {
+        input
+    } else {
+        format!("#{}", input)
+    }
+}
+
+// Every tag and mention with the number of entries carrying it, most used first
+pub fn tag_counts(entries: &[JournalEntry]) -> Vec<(String, usize)> {
+    let mut counts: BTreeMap<String, usize> = BTreeMap::new();
+    for entry in entries {
+        for label in entry.labels() {
+            *counts.entry(label).or_default() += 1;
+        }
+    }
+
+    let mut counts: Vec<(String, usize)> = counts.into_iter().collect();
+    counts.sort_by_key(|(_, count)| std::cmp::Reverse(*count));
+    counts
+}
+
+// Finds `#tags` and `@mentions` in the text and the `tags` of a front matter
+// block at its start. Code blocks, inline code, headings,
+// `C#`-style words, URL fragments and e-mail addresses are skipped, and tags
+// must contain a letter so `#1` isn't one.
+pub fn extract_tags(text: &str) -> ExtractedTags {
+    let mut tags = BTreeSet::new();
+    let mut mentions = BTreeSet::new();
+
+    let text = match parse_front_matter(text) {
+        Some((_, fields, rest)) => {
+            tags.extend(fields.tags.iter().filter_map(|tag| normalize_tag(tag)));
+            rest
+        }
+        None => text,
+    };
+
+    let mut fence: Option<&str> = None;
+    for line in text.lines() {
+        let trimmed = line.trim_start();
+        if let Some(open) = fence {
+            if trimmed.starts_with(open) {
+                fence = None;
+            }
+            continue;
+        }
+        if let Some(marker) = ["```", "~~~"]
+            .into_iter()
+            .find(|marker| trimmed.starts_with(marker))
+        {
+            fence = Some(marker);
+            continue;
+        }
+
+        scan_line(line, &mut tags, &mut mentions);
+    }
+
+    ExtractedTags {
+        tags: tags.into_iter().collect(),
+        mentions: mentions.into_iter().collect(),
+    }
+}
+
+pub(super) fn normalize_tag(tag: &str) -> Option<String> {
+    let tag = tag.trim().trim_matches(['"', '\'']).trim_start_matches('#');
+    (!tag.is_empty()).then(|| tag.to_lowercase())
+}
+
+fn scan_line(line: &str, tags: &mut BTreeSet<String>, mentions: &mut BTreeSet<String>) {
+    let mut in_code = false;
+    let mut previous: Option<char> = None;
+    let mut chars = line.char_indices().peekable();
+
+    while let Some((index, c)) = chars.next() {
+        if c == '`' {
+            in_code = !in_code;
+        }
+        let starts_word = previous.is_none_or(|p| !p.is_alphanumeric() && !"_&/#@".contains(p));
+        previous = Some(c);
+        if in_code || !starts_word || (c != '#' && c != '@') {
+            continue;
+        }
+
+        let start = index + c.len_utf8();
+        let mut end = start;
+        while let Some((index, next)) = chars.next_if(|(_, next)| is_tag_char(*next)) {
+            end = index + next.len_utf8();
+            previous = Some(next);
+        }
+
+        let name = line[start..end].trim_end_matches(['-', '/']);
+        if !name.chars().any(char::is_alphabetic) {
+            continue;
+        }
+        let name = name.to_lowercase();
+        if c == '#' {
+            tags.insert(name);
+        } else {
+            mentions.insert(name);
+        }
+    }
+}
+
+fn is_tag_char(c: char) -> bool {
+    c.is_alphanumeric() || matches!(c, '_' | '-' | '/')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use time::{Date, Month, OffsetDateTime};
+
+    fn entry(body: &str) -> JournalEntry {
+        let day = Date::from_calendar_date(2024, Month::May, 4).unwrap();
+        let mut entry = JournalEntry::new("2024-05-04", day, OffsetDateTime::UNIX_EPOCH, body);
+        entry.update_tags(None);
+        entry
+    }
+
+    #[test]
+    fn tags_and_mentions_are_lowercased_sorted_and_unique() {
+        let extracted =
+            extract_tags("#Work on #work/project-x- with @Sam, then #run (#Run) @alex.");
+        assert_eq!(extracted.tags, ["run", "work", "work/project-x"]);
+        assert_eq!(extracted.mentions, ["alex", "sam"]);
+    }
+
+    #[test]
+    fn code_headings_and_lookalikes_are_skipped() {
+        let text = "# Heading\n\
+                    ## Another #real\n\
+                    `#inline` and C# and issue #12\n\
+                    https://example.com/page#section mail me@example.com\n\
+                    ```\n#fenced @nobody\n```\n\
+                    ~~~\n#tilde\n~~~\n\
+                    done #after";
+        let extracted = extract_tags(text);
+        assert_eq!(extracted.tags, ["after", "real"]);
+        assert!(extracted.mentions.is_empty());
+    }
+
+    #[test]
+    fn front_matter_tags_are_included() {
+        let text = "---\ntags: [\"#Travel\", food]\n---\nArrived in #lisbon";
+        assert_eq!(extract_tags(text).tags, ["food", "lisbon", "travel"]);
+    }
+
+    #[test]
+    fn filters_match_all_or_any_label() {
+        let entry = entry("#work with @alice");
+        let mut filter = TagFilter::default();
+        assert!(filter.matches(&entry));
+
+        filter.toggle(&tag_label("Work"));
+        filter.toggle(&tag_label("@alice"));
+        filter.toggle(&tag_label("#home"));
+        assert_eq!(filter.describe(), "#home AND #work AND @alice");
+        assert!(!filter.matches(&entry));
+
+        filter.match_any = true;
+        assert!(filter.matches(&entry));
+
+        filter.toggle("#work");
+        filter.toggle("@alice");
+        assert!(!filter.matches(&entry));
+    }
+
+    #[test]
+    fn counts_are_most_used_first() {
+        let entries = [entry("#run @sam"), entry("#run"), entry("#swim #run")];
+        assert_eq!(
+            tag_counts(&entries),
+            [
+                ("#run".to_string(), 3),
+                ("#swim".to_string(), 1),
+                ("@sam".to_string(), 1)
+            ]
+        );
+    }
+}
@@ -0,0 +1,198 @@
+use super::{
+    insert_entry_as, is_encrypted, journal_file_name, parse_day_file, parse_file_name, read_file,
+    remove_entry, render_day_file, write_file, JournalEntry,
+};
+use crate::config::Config;
+use anyhow::{anyhow, Result};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+const TRASH_DIRECTORY: &str = ".trash";
+// Separates the entry's date from its id in trashed file names
+const NAME_SEPARATOR: &str = "__";
+
+// A deleted entry, kept as a one-entry day file named `<date>__<id>.<ext>`,
+// encrypted like the day files
+pub struct TrashedEntry {
+    pub path: PathBuf,
+    pub entry: JournalEntry,
+    pub deleted_at: Option<SystemTime>,
+}
+
+pub fn trash_directory(config: &Config) -> PathBuf {
+    config.journal_directory.join(TRASH_DIRECTORY)
+}
+
+// Moves an entry out of its day file into the trash
+pub fn trash_entry(config: &Config, entry: &JournalEntry) -> Result<PathBuf> {
+    let directory = trash_directory(config);
+    fs::create_dir_all(&directory)?;
+
+    let format = entry.format().unwrap_or_else(|| config.file_format.clone());
+    let path = directory.join(journal_file_name(
+        &format!("{}{}{}", entry.date, NAME_SEPARATOR, entry.id),
+        &format,
+        is_encrypted(config),
+    ));
+    let content = render_day_file(&format, std::slice::from_ref(entry))?;
+    write_file(&path, &content)?;
+
+    if let Err(e) = remove_entry(config, entry) {
+        let _ = fs::remove_file(&path);
+        return Err(e);
+    }
+    Ok(path)
+}
+
+// Trashed entries, most recently deleted first
+pub fn list_trash(config: &Config) -> Result<Vec<TrashedEntry>> {
+    let directory = trash_directory(config);
+    let mut trashed = Vec::new();
+
+    if !directory.exists() {
+        return Ok(trashed);
+    }
+
+    for file in fs::read_dir(&directory)? {
+        let file = file?;
+        if let Some(entry) = read_trashed_entry(config, &file.path()) {
+            trashed.push(TrashedEntry {
+                path: file.path(),
+                entry,
+                deleted_at: file.metadata().and_then(|m| m.modified()).ok(),
+            });
+        }
+    }
+
+    trashed.sort_by_key(|trashed| std::cmp::Reverse(trashed.deleted_at));
+    Ok(trashed)
+}
+
+// Puts a trashed entry back into the day file it was deleted from, which has
+// the format it was trashed in
+pub fn restore_entry(config: &Config, trashed: &TrashedEntry) -> Result<PathBuf> {
+    let format = trashed
+        .entry
+        .format()
+        .unwrap_or_else(|| config.file_format.clone());
+    let path = insert_entry_as(config, trashed.entry.clone(), &format)?;
+    fs::remove_file(&trashed.path)?;
+    Ok(path)
+}
+
+pub fn purge_entry(trashed: &TrashedEntry) -> Result<()> {
+    fs::remove_file(&trashed.path)
+        .map_err(|e| anyhow!("Failed to delete {}: {}", trashed.path.display(), e))
+}
+
+// Entries trashed in another file format, or under another date format, are
+// still restored; the date is re-derived from the entry when the name doesn't parse
+fn read_trashed_entry(config: &Config, path: &Path) -> Option<JournalEntry> {
+    let (stem, format) = parse_file_name(path)?;
+    let (date, _) = stem.rsplit_once(NAME_SEPARATOR)?;
+
+    let date_format = config.date_formatter().ok()?;
+    let content = read_file(path).ok()?;
+
+    let day = date_format.parse(date);
+    let mut entry = parse_day_file(&content, &format, date, day.unwrap_or(time::Date::MIN))
+        .into_iter()
+        .next()?;
+
+    if day.is_none() {
+        entry.day = entry.timestamp()?.date();
+        entry.date = date_format.format(entry.day).ok()?;
+    }
+    // The day file it was deleted from
+    entry.filename = journal_file_name(&entry.date, &format, is_encrypted(config));
+    Some(entry)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::FileFormat;
+    use crate::storage::{list_day_files, read_day, TempJournal};
+    use time::{Date, Month};
+
+    fn day() -> Date {
+        Date::from_calendar_date(2024, Month::May, 4).unwrap()
+    }
+
+    fn bodies(config: &Config) -> Vec<String> {
+        read_day(config, day())
+            .unwrap()
+            .into_iter()
+            .map(|entry| entry.body)
+            .collect()
+    }
+
+    #[test]
+    fn entries_are_restored_into_the_format_they_were_deleted_from() {
+        let journal = TempJournal::new("trash-restore");
+        let config = &journal.config;
+        journal.add(6, "Lake swim");
+        let json = Config {
+            file_format: FileFormat::Json,
+            ..config.clone()
+        };
+        let created_at = day().with_hms(7, 0, 0).unwrap().assume_utc();
+        insert_entry_as(
+            &json,
+            JournalEntry::new("2024-05-04", day(), created_at, "Heron at dawn"),
+            &FileFormat::Json,
+        )
+        .unwrap();
+
+        let heron = read_day(config, day()).unwrap().remove(1);
+        assert_eq!(heron.filename, "2024-05-04.json");
+        trash_entry(config, &heron).unwrap();
+        assert_eq!(bodies(config), ["Lake swim"]);
+        assert_eq!(list_day_files(config).unwrap().len(), 1);
+
+        let trashed = list_trash(config).unwrap();
+        assert_eq!(trashed.len(), 1);
+        assert_eq!(trashed[0].entry.id, heron.id);
+        assert_eq!(trashed[0].entry.filename, "2024-05-04.json");
+
+        let path = restore_entry(config, &trashed[0]).unwrap();
+        assert_eq!(path, config.journal_directory.join("2024-05-04.json"));
+        assert_eq!(bodies(config), ["Lake swim", "Heron at dawn"]);
+        assert!(list_trash(config).unwrap().is_empty());
+    }
+
+    #[test]
+    fn purged_entries_are_gone() {
+        let journal = TempJournal::new("trash-purge");
+        let config = &journal.config;
+        let entry = journal.add(6, "Lake swim");
+        journal.add(7, "Heron at dawn");
+
+        trash_entry(config, &entry).unwrap();
+        let trashed = list_trash(config).unwrap();
+        purge_entry(&trashed[0]).unwrap();
+
+        assert!(list_trash(config).unwrap().is_empty());
+        assert!(fs::read_dir(trash_directory(config))
+            .unwrap()
+            .next()
+            .is_none());
+        assert_eq!(bodies(config), ["Heron at dawn"]);
+    }
+
+    #[test]
+    fn entries_trashed_under_another_date_format_are_listed() {
+        let journal = TempJournal::new("trash-date-format");
+        let entry = journal.add(6, "Lake swim");
+        trash_entry(&journal.config, &entry).unwrap();
+
+        let config = Config {
+            date_format: "%d.%m.%Y".to_string(),
+            ..journal.config.clone()
+        };
+        let trashed = list_trash(&config).unwrap();
+        assert_eq!(trashed[0].entry.date, "04.05.2024");
+        assert_eq!(trashed[0].entry.day, day());
+    }
+}
@@ -1,71 +1,712 @@
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
 use ratatui::{
-    layout::{Alignment, Rect},
-    style::{Color, Modifier, Style},
+    layout::Rect,
+    style::{Modifier, Style},
     text::{Line, Span},
-    widgets::{Block, Borders, Paragraph},
+    widgets::{Block, Paragraph},
+    Frame,
 };
+use std::cell::Cell;
+use unicode_segmentation::UnicodeSegmentation;
+use unicode_width::UnicodeWidthStr;
 
-pub struct StatusBar;
+// Undo steps kept per editor
+const UNDO_LIMIT: usize = 200;
 
-impl StatusBar {
-    pub fn render(title: &str, message: &str, style: Style) -> Paragraph {
-        Paragraph::new(message)
-            .style(style)
-            .alignment(Alignment::Center)
-            .block(
-                Block::default()
-                    .borders(Borders::ALL)
-                    .title(title)
-                    .title_style(style)
-            )
-    }
+// A multi-line text editor. The cursor always sits on a grapheme cluster
+// boundary, lines are soft-wrapped to the width they were last rendered at, and
+// edits can be undone and redone.
+pub struct TextEditor {
+    lines: Vec<String>,
+    cursor: Position,
+    // Other end of the selection, when there is one
+    anchor: Option<Position>,
+    // Display column kept while moving up and down through shorter lines
+    preferred_column: Option<usize>,
+    undo: Vec<Snapshot>,
+    redo: Vec<Snapshot>,
+    last_edit: Option<EditKind>,
+    // Set while rendering, which only has shared access
+    width: Cell<usize>,
+    height: Cell<usize>,
+    scroll: Cell<usize>,
 }
 
-pub struct TitleBar;
+// A row and a byte offset into that row
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+struct Position {
+    row: usize,
+    column: usize,
+}
 
-impl TitleBar {
-    pub fn render(title: &str, subtitle: Option<&str>, color: Color) -> Paragraph {
-        let mut lines = vec![
-            Line::from(vec![
-                Span::styled(title, Style::default().fg(color).add_modifier(Modifier::BOLD))
-            ])
-        ];
-        
-        if let Some(sub) = subtitle {
-            lines.push(Line::from(vec![
-                Span::styled(sub, Style::default().fg(Color::Gray))
-            ]));
-        }
+#[derive(Clone)]
+struct Snapshot {
+    lines: Vec<String>,
+    cursor: Position,
+}
+
+// Consecutive edits of the same kind are undone together
+#[derive(Clone, Copy, PartialEq)]
+enum EditKind {
+    Insert,
+    Delete,
+    Other,
+}
+
+// A wrapped piece of a line: its row and byte range
+#[derive(Clone, Copy)]
+struct VisualRow {
+    row: usize,
+    start: usize,
+    end: usize,
+}
 
-        Paragraph::new(lines)
-            .alignment(Alignment::Center)
-            .block(Block::default().borders(Borders::ALL))
+impl Default for TextEditor {
+    fn default() -> Self {
+        Self {
+            lines: vec![String::new()],
+            cursor: Position { row: 0, column: 0 },
+            anchor: None,
+            preferred_column: None,
+            undo: Vec::new(),
+            redo: Vec::new(),
+            last_edit: None,
+            width: Cell::new(80),
+            height: Cell::new(10),
+            scroll: Cell::new(0),
+        }
     }
 }
 
-pub struct InputField;
+impl TextEditor {
+    pub fn from_text(text: &str) -> Self {
+        let mut editor = Self {
+            lines: text.split('\n').map(str::to_string).collect(),
+            ..Self::default()
+        };
+        editor.move_to_end();
+        editor
+    }
+
+    pub fn text(&self) -> String {
+        self.lines.join("\n")
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.lines.len() == 1 && self.lines[0].is_empty()
+    }
+
+    pub fn clear(&mut self) {
+        *self = Self::default();
+    }
+
+    // Applies a key press. Returns false for keys the editor doesn't use.
+    pub fn handle_key(&mut self, key: KeyEvent) -> bool {
+        let control = key.modifiers.contains(KeyModifiers::CONTROL);
+        let alt = key.modifiers.contains(KeyModifiers::ALT);
+        let shift = key.modifiers.contains(KeyModifiers::SHIFT);
 
-impl InputField {
-    pub fn render(content: &str, is_focused: bool, title: &str) -> Paragraph {
-        let style = if is_focused {
-            Style::default().fg(Color::Yellow)
+        match key.code {
+            KeyCode::Char('z') if control => self.undo(),
+            KeyCode::Char('y') if control => self.redo(),
+            KeyCode::Char('Z') if control => self.redo(),
+            KeyCode::Char('a') if control => {
+                self.anchor = Some(Position { row: 0, column: 0 });
+                self.move_to_end();
+            }
+            KeyCode::Char('w') if control => self.delete_word_before(),
+            KeyCode::Backspace if control || alt => self.delete_word_before(),
+            KeyCode::Char('b') if alt => self.move_cursor(shift, Self::word_left),
+            KeyCode::Char('f') if alt => self.move_cursor(shift, Self::word_right),
+            KeyCode::Char(_) if control || alt => return false,
+            KeyCode::Char(c) => self.insert_char(c),
+            KeyCode::Enter => self.insert_char('\n'),
+            KeyCode::Tab => self.insert_str("    "),
+            KeyCode::Backspace => self.delete_before(),
+            KeyCode::Delete => self.delete_after(),
+            KeyCode::Left if control || alt => self.move_cursor(shift, Self::word_left),
+            KeyCode::Right if control || alt => self.move_cursor(shift, Self::word_right),
+            KeyCode::Left => self.move_cursor(shift, Self::grapheme_left),
+            KeyCode::Right => self.move_cursor(shift, Self::grapheme_right),
+            KeyCode::Up => self.move_vertically(shift, -1),
+            KeyCode::Down => self.move_vertically(shift, 1),
+            KeyCode::PageUp => self.move_vertically(shift, -(self.page_height() as isize)),
+            KeyCode::PageDown => self.move_vertically(shift, self.page_height() as isize),
+            KeyCode::Home if control => self.move_cursor(shift, |editor| {
+                editor.cursor = Position { row: 0, column: 0 };
+            }),
+            KeyCode::End if control => self.move_cursor(shift, Self::move_to_end),
+            KeyCode::Home => self.move_cursor(shift, |editor| editor.cursor.column = 0),
+            KeyCode::End => self.move_cursor(shift, |editor| {
+                editor.cursor.column = editor.lines[editor.cursor.row].len();
+            }),
+            _ => return false,
+        }
+        true
+    }
+
+    // Draws the visible part of the text inside `block`, with the terminal
+    // cursor placed when `focused`
+    pub fn render(&self, f: &mut Frame, area: Rect, block: Block, style: Style, focused: bool) {
+        let inner = block.inner(area);
+        let width = inner.width.max(1) as usize;
+        let height = inner.height as usize;
+        self.width.set(width);
+        self.height.set(height);
+
+        let rows = self.visual_rows(width);
+        let (cursor_row, cursor_x) = self.cursor_visual(&rows);
+
+        // Scroll just enough to keep the cursor in view
+        let mut scroll = self.scroll.get().min(rows.len().saturating_sub(1));
+        if cursor_row < scroll {
+            scroll = cursor_row;
+        } else if height > 0 && cursor_row >= scroll + height {
+            scroll = cursor_row + 1 - height;
+        }
+        self.scroll.set(scroll);
+
+        let selection = self.selection();
+        let selected_style = style.add_modifier(Modifier::REVERSED);
+        let lines: Vec<Line> = rows
+            .iter()
+            .skip(scroll)
+            .take(height)
+            .map(|visual| {
+                let text = &self.lines[visual.row][visual.start..visual.end];
+                let Some((from, to)) =
+                    selection.and_then(|selection| selected_range(selection, visual))
+                else {
+                    return Line::from(text);
+                };
+
+                let from = from - visual.start;
+                let to = to - visual.start;
+                Line::from(vec![
+                    Span::raw(&text[..from]),
+                    Span::styled(&text[from..to], selected_style),
+                    Span::raw(&text[to..]),
+                ])
+            })
+            .collect();
+
+        f.render_widget(Paragraph::new(lines).style(style).block(block), area);
+
+        if focused && cursor_row >= scroll && cursor_row < scroll + height {
+            f.set_cursor_position((
+                inner.x + cursor_x.min(width - 1) as u16,
+                inner.y + (cursor_row - scroll) as u16,
+            ));
+        }
+    }
+
+    fn insert_char(&mut self, c: char) {
+        // Typing is undone a word at a time
+        let new_word = c.is_whitespace() || self.last_edit != Some(EditKind::Insert);
+        self.begin_edit(if new_word {
+            EditKind::Other
         } else {
-            Style::default()
+            EditKind::Insert
+        });
+        self.last_edit = Some(EditKind::Insert);
+        self.delete_selection();
+        self.insert_text(c.encode_utf8(&mut [0; 4]));
+    }
+
+    fn insert_str(&mut self, text: &str) {
+        self.begin_edit(EditKind::Other);
+        self.delete_selection();
+        self.insert_text(text);
+    }
+
+    fn insert_text(&mut self, text: &str) {
+        let Position { row, column } = self.cursor;
+        let rest = self.lines[row].split_off(column);
+
+        let mut pieces = text.split('\n');
+        if let Some(first) = pieces.next() {
+            self.lines[row].push_str(first);
+        }
+        let mut row = row;
+        for piece in pieces {
+            row += 1;
+            self.lines.insert(row, piece.to_string());
+        }
+
+        self.cursor = Position {
+            row,
+            column: self.lines[row].len(),
         };
+        self.lines[row].push_str(&rest);
+        self.preferred_column = None;
+    }
 
-        let block_title = if is_focused {
-            format!("{} (Editing...)", title)
-        } else {
-            title.to_string()
+    fn delete_before(&mut self) {
+        self.begin_edit(EditKind::Delete);
+        if self.delete_selection() {
+            return;
+        }
+
+        let end = self.cursor;
+        self.grapheme_left();
+        self.delete_range(self.cursor, end);
+    }
+
+    fn delete_after(&mut self) {
+        self.begin_edit(EditKind::Delete);
+        if self.delete_selection() {
+            return;
+        }
+
+        let start = self.cursor;
+        self.grapheme_right();
+        self.delete_range(start, self.cursor);
+    }
+
+    fn delete_word_before(&mut self) {
+        self.begin_edit(EditKind::Other);
+        if self.delete_selection() {
+            return;
+        }
+
+        let end = self.cursor;
+        self.word_left();
+        self.delete_range(self.cursor, end);
+    }
+
+    fn delete_selection(&mut self) -> bool {
+        match self.selection() {
+            Some((start, end)) => {
+                self.delete_range(start, end);
+                true
+            }
+            None => {
+                self.anchor = None;
+                false
+            }
+        }
+    }
+
+    fn delete_range(&mut self, start: Position, end: Position) {
+        if start < end {
+            let tail = self.lines[end.row][end.column..].to_string();
+            self.lines.drain(start.row + 1..=end.row);
+            self.lines[start.row].truncate(start.column);
+            self.lines[start.row].push_str(&tail);
+        }
+        self.cursor = start;
+        self.anchor = None;
+        self.preferred_column = None;
+    }
+
+    // Records the state before an edit, unless it continues the previous one
+    fn begin_edit(&mut self, kind: EditKind) {
+        if kind == EditKind::Other || self.last_edit != Some(kind) || self.anchor.is_some() {
+            self.undo.push(self.snapshot());
+            if self.undo.len() > UNDO_LIMIT {
+                self.undo.remove(0);
+            }
+        }
+        self.redo.clear();
+        self.last_edit = Some(kind);
+    }
+
+    fn snapshot(&self) -> Snapshot {
+        Snapshot {
+            lines: self.lines.clone(),
+            cursor: self.cursor,
+        }
+    }
+
+    fn undo(&mut self) {
+        if let Some(snapshot) = self.undo.pop() {
+            self.redo.push(self.snapshot());
+            self.restore(snapshot);
+        }
+    }
+
+    fn redo(&mut self) {
+        if let Some(snapshot) = self.redo.pop() {
+            self.undo.push(self.snapshot());
+            self.restore(snapshot);
+        }
+    }
+
+    fn restore(&mut self, snapshot: Snapshot) {
+        self.lines = snapshot.lines;
+        self.cursor = snapshot.cursor;
+        self.anchor = None;
+        self.preferred_column = None;
+        self.last_edit = None;
+    }
+
+    // Runs a cursor movement, extending the selection when `select` is set
+    fn move_cursor(&mut self, select: bool, movement: impl FnOnce(&mut Self)) {
+        let start = self.cursor;
+        movement(self);
+        self.anchor = match (select, self.anchor) {
+            (true, None) => Some(start),
+            (true, anchor) => anchor,
+            (false, _) => None,
         };
+        self.preferred_column = None;
+        self.last_edit = None;
+    }
 
-        Paragraph::new(content)
-            .style(style)
-            .block(
-                Block::default()
-                    .borders(Borders::ALL)
-                    .title(block_title)
-                    .title_style(style)
-            )
+    fn grapheme_left(&mut self) {
+        let line = &self.lines[self.cursor.row];
+        match line[..self.cursor.column].graphemes(true).next_back() {
+            Some(grapheme) => self.cursor.column -= grapheme.len(),
+            None if self.cursor.row > 0 => {
+                self.cursor.row -= 1;
+                self.cursor.column = self.lines[self.cursor.row].len();
+            }
+            None => {}
+        }
     }
-}
\ No newline at end of file
+
+    fn grapheme_right(&mut self) {
+        let line = &self.lines[self.cursor.row];
+        match line[self.cursor.column..].graphemes(true).next() {
+            Some(grapheme) => self.cursor.column += grapheme.len(),
+            None if self.cursor.row + 1 < self.lines.len() => {
+                self.cursor.row += 1;
+                self.cursor.column = 0;
+            }
+            None => {}
+        }
+    }
+
+    // To the start of the previous word, crossing line breaks
+    fn word_left(&mut self) {
+        if self.cursor.column == 0 {
+            self.grapheme_left();
+            return;
+        }
+
+        let line = &self.lines[self.cursor.row];
+        let mut column = self.cursor.column;
+        let mut seen_word = false;
+        for (start, grapheme) in line[..column].grapheme_indices(true).rev() {
+            let word = is_word(grapheme);
+            if seen_word && !word {
+                break;
+            }
+            seen_word |= word;
+            column = start;
+        }
+        self.cursor.column = column;
+    }
+
+    // To the end of the next word, crossing line breaks
+    fn word_right(&mut self) {
+        let line = &self.lines[self.cursor.row];
+        if self.cursor.column == line.len() {
+            self.grapheme_right();
+            return;
+        }
+
+        let mut column = self.cursor.column;
+        let mut seen_word = false;
+        for (start, grapheme) in line[column..].grapheme_indices(true) {
+            let word = is_word(grapheme);
+            if seen_word && !word {
+                break;
+            }
+            seen_word |= word;
+            column = self.cursor.column + start + grapheme.len();
+        }
+        self.cursor.column = column;
+    }
+
+    fn move_to_end(&mut self) {
+        let row = self.lines.len() - 1;
+        self.cursor = Position {
+            row,
+            column: self.lines[row].len(),
+        };
+    }
+
+    // Moves by wrapped rows, keeping the display column where possible
+    fn move_vertically(&mut self, select: bool, offset: isize) {
+        let rows = self.visual_rows(self.width.get());
+        let (current, x) = self.cursor_visual(&rows);
+        let x = self.preferred_column.unwrap_or(x);
+        let target = current.saturating_add_signed(offset).min(rows.len() - 1);
+
+        self.move_cursor(select, |editor| {
+            if offset < 0 && current == 0 {
+                editor.cursor.column = 0;
+            } else if offset > 0 && current == rows.len() - 1 {
+                editor.cursor.column = editor.lines[editor.cursor.row].len();
+            } else {
+                editor.cursor = editor.position_at(&rows, target, x);
+            }
+        });
+        self.preferred_column = Some(x);
+    }
+
+    fn page_height(&self) -> usize {
+        self.height.get().max(1)
+    }
+
+    // Soft-wraps every line to `width` columns, breaking after whitespace when possible
+    fn visual_rows(&self, width: usize) -> Vec<VisualRow> {
+        let width = width.max(1);
+        let mut rows = Vec::new();
+
+        for (row, line) in self.lines.iter().enumerate() {
+            let mut start = 0;
+            let mut used = 0;
+            let mut last_break = None;
+
+            for (index, grapheme) in line.grapheme_indices(true) {
+                let grapheme_width = grapheme.width();
+                if used + grapheme_width > width && index > start {
+                    let end = last_break.filter(|&end| end > start).unwrap_or(index);
+                    rows.push(VisualRow { row, start, end });
+                    used = line[end..index].width();
+                    start = end;
+                    last_break = None;
+                }
+                used += grapheme_width;
+                if grapheme.chars().all(char::is_whitespace) {
+                    last_break = Some(index + grapheme.len());
+                }
+            }
+            rows.push(VisualRow {
+                row,
+                start,
+                end: line.len(),
+            });
+        }
+        rows
+    }
+
+    // The wrapped row and display column of the cursor
+    fn cursor_visual(&self, rows: &[VisualRow]) -> (usize, usize) {
+        // At a wrap point the cursor belongs to the start of the next row
+        let index = rows
+            .iter()
+            .rposition(|visual| visual.row == self.cursor.row && visual.start <= self.cursor.column)
+            .unwrap_or(0);
+        let visual = rows[index];
+        let x = self.lines[visual.row][visual.start..self.cursor.column].width();
+        (index, x)
+    }
+
+    // The position in a wrapped row closest to display column `x`
+    fn position_at(&self, rows: &[VisualRow], index: usize, x: usize) -> Position {
+        let visual = rows[index];
+        let line = &self.lines[visual.row];
+        let last_in_line = rows
+            .get(index + 1)
+            .is_none_or(|next| next.row != visual.row);
+
+        let mut column = visual.start;
+        let mut used = 0;
+        for (start, grapheme) in line[visual.start..visual.end].grapheme_indices(true) {
+            let end = visual.start + start + grapheme.len();
+            // The end of a wrapped row is the start of the next one
+            if used + grapheme.width() > x || (end == visual.end && !last_in_line) {
+                break;
+            }
+            used += grapheme.width();
+            column = end;
+        }
+        Position {
+            row: visual.row,
+            column,
+        }
+    }
+
+    // The selected range in text order
+    fn selection(&self) -> Option<(Position, Position)> {
+        let anchor = self.anchor?;
+        match anchor.cmp(&self.cursor) {
+            std::cmp::Ordering::Less => Some((anchor, self.cursor)),
+            std::cmp::Ordering::Greater => Some((self.cursor, anchor)),
+            std::cmp::Ordering::Equal => None,
+        }
+    }
+}
+
+// The byte range of a wrapped row covered by a selection
+fn selected_range(
+    (start, end): (Position, Position),
+    visual: &VisualRow,
+) -> Option<(usize, usize)> {
+    if visual.row < start.row || visual.row > end.row {
+        return None;
+    }
+    let from = if visual.row == start.row {
+        start.column.max(visual.start)
+    } else {
+        visual.start
+    };
+    let to = if visual.row == end.row {
+        end.column.min(visual.end)
+    } else {
+        visual.end
+    };
+    (from < to).then_some((from, to))
+}
+
+fn is_word(grapheme: &str) -> bool {
+    grapheme.chars().any(|c| c.is_alphanumeric() || c == '_')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn press(editor: &mut TextEditor, code: KeyCode, modifiers: KeyModifiers) {
+        assert!(editor.handle_key(KeyEvent::new(code, modifiers)));
+    }
+
+    fn type_text(editor: &mut TextEditor, text: &str) {
+        for c in text.chars() {
+            press(editor, KeyCode::Char(c), KeyModifiers::NONE);
+        }
+    }
+
+    // The text of each wrapped row
+    fn wrapped(editor: &TextEditor, width: usize) -> Vec<&str> {
+        editor
+            .visual_rows(width)
+            .iter()
+            .map(|visual| &editor.lines[visual.row][visual.start..visual.end])
+            .collect()
+    }
+
+    #[test]
+    fn backspace_removes_whole_graphemes() {
+        let mut editor = TextEditor::default();
+        // A precomposed and a combined accent, an emoji with a skin tone and
+        // wide characters, typed a char at a time
+        type_text(&mut editor, "é e\u{301} 👍🏽 日本");
+        assert_eq!(editor.text(), "é e\u{301} 👍🏽 日本");
+
+        for expected in [
+            "é e\u{301} 👍🏽 日",
+            "é e\u{301} 👍🏽 ",
+            "é e\u{301} 👍🏽",
+            "é e\u{301} ",
+            "é e\u{301}",
+            "é ",
+            "é",
+            "",
+        ] {
+            press(&mut editor, KeyCode::Backspace, KeyModifiers::NONE);
+            assert_eq!(editor.text(), expected);
+        }
+        press(&mut editor, KeyCode::Backspace, KeyModifiers::NONE);
+        assert!(editor.is_empty());
+    }
+
+    #[test]
+    fn cursor_moves_by_graphemes_and_across_lines() {
+        let mut editor = TextEditor::from_text("ab\ne\u{301}👍🏽c");
+        for _ in 0..3 {
+            press(&mut editor, KeyCode::Left, KeyModifiers::NONE);
+        }
+        type_text(&mut editor, "x");
+        assert_eq!(editor.text(), "ab\nxe\u{301}👍🏽c");
+
+        press(&mut editor, KeyCode::Right, KeyModifiers::NONE);
+        type_text(&mut editor, "y");
+        assert_eq!(editor.text(), "ab\nxe\u{301}y👍🏽c");
+
+        press(&mut editor, KeyCode::Home, KeyModifiers::NONE);
+        press(&mut editor, KeyCode::Left, KeyModifiers::NONE);
+        type_text(&mut editor, "!");
+        assert_eq!(editor.text(), "ab!\nxe\u{301}y👍🏽c");
+
+        press(&mut editor, KeyCode::Delete, KeyModifiers::NONE);
+        assert_eq!(editor.text(), "ab!xe\u{301}y👍🏽c");
+    }
+
+    #[test]
+    fn lines_wrap_by_display_width() {
+        let editor = TextEditor::from_text("日本語です\nab cd ef\nabcdefgh");
+        assert_eq!(
+            wrapped(&editor, 5),
+            ["日本", "語で", "す", "ab ", "cd ef", "abcde", "fgh"]
+        );
+
+        // The cursor's column counts wide characters twice
+        let editor = TextEditor::from_text("日本語");
+        let rows = editor.visual_rows(4);
+        assert_eq!(editor.cursor_visual(&rows), (1, 2));
+    }
+
+    #[test]
+    fn up_and_down_keep_the_display_column() {
+        let mut editor = TextEditor::from_text("日本語\nabcdef");
+        editor.width.set(80);
+        press(&mut editor, KeyCode::Home, KeyModifiers::NONE);
+        for _ in 0..4 {
+            press(&mut editor, KeyCode::Right, KeyModifiers::NONE);
+        }
+        press(&mut editor, KeyCode::Up, KeyModifiers::NONE);
+        type_text(&mut editor, "|");
+        assert_eq!(editor.text(), "日本|語\nabcdef");
+    }
+
+    #[test]
+    fn typing_replaces_the_selection() {
+        let mut editor = TextEditor::from_text("hello wörld");
+        press(&mut editor, KeyCode::Home, KeyModifiers::NONE);
+        press(
+            &mut editor,
+            KeyCode::Right,
+            KeyModifiers::CONTROL | KeyModifiers::SHIFT,
+        );
+        type_text(&mut editor, "Hi");
+        assert_eq!(editor.text(), "Hi wörld");
+
+        press(&mut editor, KeyCode::End, KeyModifiers::NONE);
+        for _ in 0..4 {
+            press(&mut editor, KeyCode::Left, KeyModifiers::SHIFT);
+        }
+        assert_eq!(
+            editor.selection(),
+            Some((
+                Position { row: 0, column: 4 },
+                Position { row: 0, column: 9 }
+            ))
+        );
+        press(&mut editor, KeyCode::Backspace, KeyModifiers::NONE);
+        assert_eq!(editor.text(), "Hi w");
+
+        press(&mut editor, KeyCode::Char('a'), KeyModifiers::CONTROL);
+        type_text(&mut editor, "new");
+        assert_eq!(editor.text(), "new");
+    }
+
+    #[test]
+    fn undo_and_redo_go_a_word_at_a_time() {
+        let mut editor = TextEditor::default();
+        type_text(&mut editor, "one two");
+        for _ in 0..3 {
+            press(&mut editor, KeyCode::Backspace, KeyModifiers::NONE);
+        }
+        assert_eq!(editor.text(), "one ");
+
+        let undo = KeyCode::Char('z');
+        press(&mut editor, undo, KeyModifiers::CONTROL);
+        assert_eq!(editor.text(), "one two");
+        press(&mut editor, undo, KeyModifiers::CONTROL);
+        assert_eq!(editor.text(), "one");
+        press(&mut editor, undo, KeyModifiers::CONTROL);
+        assert_eq!(editor.text(), "");
+
+        press(&mut editor, KeyCode::Char('y'), KeyModifiers::CONTROL);
+        assert_eq!(editor.text(), "one");
+
+        // A new edit drops what could be redone
+        type_text(&mut editor, "!");
+        press(&mut editor, KeyCode::Char('y'), KeyModifiers::CONTROL);
+        assert_eq!(editor.text(), "one!");
+    }
+}
@@ -0,0 +1,278 @@
+use pulldown_cmark::{CodeBlockKind, Event, HeadingLevel, Options, Parser, Tag, TagEnd};
+use ratatui::{
+    style::{Color, Modifier, Style},
+    text::{Line, Span},
+};
+
+const RULE_WIDTH: usize = 40;
+
+// Renders Markdown as styled lines: headings, emphasis, lists, task checkboxes,
+// block quotes, code and links. Unsupported syntax is shown as plain text.
+pub fn render_markdown(text: &str) -> Vec<Line<'static>> {
+    let options = Options::ENABLE_STRIKETHROUGH | Options::ENABLE_TASKLISTS;
+    let mut renderer = Renderer::default();
+
+    for event in Parser::new_ext(text, options) {
+        renderer.handle(event);
+    }
+    renderer.finish()
+}
+
+#[derive(Default)]
+struct Renderer {
+    lines: Vec<Line<'static>>,
+    // The line being built
+    spans: Vec<Span<'static>>,
+    // Inline styles that are open, innermost last
+    styles: Vec<Style>,
+    // One entry per open list, holding the next number of ordered lists
+    lists: Vec<Option<u64>>,
+    // Bullet of a list item whose first line hasn't started yet
+    item_marker: Option<Span<'static>>,
+    quote_depth: usize,
+    code_block: Option<Style>,
+    // Target and text of the open link
+    link: Option<(String, String)>,
+    // Set after a block ends to the quote depth it ended in; the next block
+    // starts after an empty line
+    gap: Option<usize>,
+}
+
+impl Renderer {
+    fn handle(&mut self, event: Event) {
+        match event {
+            Event::Start(tag) => self.start(tag),
+            Event::End(tag) => self.end(tag),
+            Event::Text(text) => match self.code_block {
+                Some(style) => {
+                    for line in text.trim_end_matches('\n').split('\n') {
+                        self.start_line();
+                        self.spans.push(Span::styled(format!("  {}", line), style));
+                        self.flush_line();
+                    }
+                }
+                None => self.push_text(&text, self.style()),
+            },
+            Event::Code(code) => {
+                let style = self.style().patch(code_style());
+                self.push_text(&code, style);
+            }
+            Event::Html(html) => {
+                for line in html.trim_end_matches('\n').split('\n') {
+                    self.push_text(line, Style::default().fg(Color::DarkGray));
+                    self.flush_line();
+                }
+            }
+            Event::InlineHtml(html) => self.push_text(&html, Style::default().fg(Color::DarkGray)),
+            Event::SoftBreak => self.push_text(" ", self.style()),
+            Event::HardBreak => self.flush_line(),
+            Event::Rule => {
+                self.start_line();
+                self.spans.push(Span::styled(
+                    "─".repeat(RULE_WIDTH),
+                    Style::default().fg(Color::DarkGray),
+                ));
+                self.end_block();
+            }
+            Event::TaskListMarker(checked) => {
+                let indent = "  ".repeat(self.lists.len().saturating_sub(1));
+                self.item_marker = Some(if checked {
+                    Span::styled(format!("{}☑ ", indent), Style::default().fg(Color::Green))
+                } else {
+                    Span::styled(format!("{}☐ ", indent), Style::default().fg(Color::Yellow))
+                });
+            }
+            _ => {}
+        }
+    }
+
+    fn start(&mut self, tag: Tag) {
+        match tag {
+            Tag::Heading { level, .. } => {
+                self.flush_line();
+                self.styles.push(heading_style(level));
+            }
+            Tag::BlockQuote(_) => {
+                self.flush_line();
+                self.quote_depth += 1;
+                self.styles.push(
+                    Style::default()
+                        .fg(Color::Gray)
+                        .add_modifier(Modifier::ITALIC),
+                );
+            }
+            Tag::CodeBlock(kind) => {
+                self.flush_line();
+                if let CodeBlockKind::Fenced(language) = kind {
+                    if !language.is_empty() {
+                        self.start_line();
+                        self.spans.push(Span::styled(
+                            format!("  {}", language),
+                            Style::default().fg(Color::DarkGray),
+                        ));
+                        self.flush_line();
+                    }
+                }
+                self.code_block = Some(code_style());
+            }
+            Tag::List(start) => {
+                self.flush_line();
+                self.lists.push(start);
+            }
+            Tag::Item => {
+                self.flush_line();
+                let indent = "  ".repeat(self.lists.len().saturating_sub(1));
+                let marker = match self.lists.last_mut() {
+                    Some(Some(number)) => {
+                        *number += 1;
+                        format!("{}{}. ", indent, *number - 1)
+                    }
+                    _ => format!("{}• ", indent),
+                };
+                self.item_marker = Some(Span::styled(marker, Style::default().fg(Color::Cyan)));
+            }
+            Tag::Emphasis => self.push_style(Style::default().add_modifier(Modifier::ITALIC)),
+            Tag::Strong => self.push_style(Style::default().add_modifier(Modifier::BOLD)),
+            Tag::Strikethrough => {
+                self.push_style(Style::default().add_modifier(Modifier::CROSSED_OUT))
+            }
+            Tag::Link { dest_url, .. } => {
+                self.link = Some((dest_url.to_string(), String::new()));
+                self.push_style(
+                    Style::default()
+                        .fg(Color::Blue)
+                        .add_modifier(Modifier::UNDERLINED),
+                );
+            }
+            Tag::Image { dest_url, .. } => {
+                self.link = Some((dest_url.to_string(), String::new()));
+                self.push_text("🖼 ", self.style());
+                self.push_style(Style::default().fg(Color::Blue));
+            }
+            _ => {}
+        }
+    }
+
+    fn end(&mut self, tag: TagEnd) {
+        match tag {
+            TagEnd::Paragraph | TagEnd::Heading(_) => {
+                if matches!(tag, TagEnd::Heading(_)) {
+                    self.styles.pop();
+                }
+                self.end_block();
+            }
+            TagEnd::BlockQuote(_) => {
+                self.flush_line();
+                self.styles.pop();
+                self.quote_depth -= 1;
+                self.gap = Some(self.quote_depth);
+            }
+            TagEnd::HtmlBlock => self.end_block(),
+            TagEnd::CodeBlock => {
+                self.code_block = None;
+                self.end_block();
+            }
+            TagEnd::List(_) => {
+                self.flush_line();
+                self.lists.pop();
+                if self.lists.is_empty() {
+                    self.end_block();
+                }
+            }
+            TagEnd::Item => self.flush_line(),
+            TagEnd::Emphasis | TagEnd::Strong | TagEnd::Strikethrough => {
+                self.styles.pop();
+            }
+            TagEnd::Link | TagEnd::Image => {
+                self.styles.pop();
+                if let Some((url, text)) = self.link.take() {
+                    if !url.is_empty() && url != text {
+                        self.push_text(
+                            &format!(" ({})", url),
+                            Style::default().fg(Color::DarkGray),
+                        );
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn style(&self) -> Style {
+        self.styles
+            .iter()
+            .fold(Style::default(), |style, inner| style.patch(*inner))
+    }
+
+    fn push_style(&mut self, style: Style) {
+        self.styles.push(style);
+    }
+
+    fn push_text(&mut self, text: &str, style: Style) {
+        self.start_line();
+        if let Some((_, link_text)) = self.link.as_mut() {
+            link_text.push_str(text);
+        }
+        self.spans.push(Span::styled(text.to_string(), style));
+    }
+
+    // Starts a line with the quote bars and list indentation it sits in
+    fn start_line(&mut self) {
+        if !self.spans.is_empty() {
+            return;
+        }
+
+        // Items of a list follow each other without gaps
+        if let Some(depth) = self.gap.take() {
+            if self.lists.is_empty() && !self.lines.is_empty() {
+                let depth = depth.min(self.quote_depth);
+                self.lines.push(Line::from(quote_bars(depth)));
+            }
+        }
+
+        if self.quote_depth > 0 {
+            self.spans.push(quote_bars(self.quote_depth));
+        }
+        match self.item_marker.take() {
+            Some(marker) => self.spans.push(marker),
+            None if !self.lists.is_empty() => {
+                self.spans.push(Span::raw("  ".repeat(self.lists.len())));
+            }
+            None => {}
+        }
+    }
+
+    fn flush_line(&mut self) {
+        if !self.spans.is_empty() {
+            self.lines.push(Line::from(std::mem::take(&mut self.spans)));
+        }
+    }
+
+    fn end_block(&mut self) {
+        self.flush_line();
+        self.gap = Some(self.quote_depth);
+    }
+
+    fn finish(mut self) -> Vec<Line<'static>> {
+        self.flush_line();
+        self.lines
+    }
+}
+
+fn heading_style(level: HeadingLevel) -> Style {
+    let style = Style::default().add_modifier(Modifier::BOLD);
+    match level {
+        HeadingLevel::H1 => style.fg(Color::Magenta).add_modifier(Modifier::UNDERLINED),
+        HeadingLevel::H2 => style.fg(Color::Cyan),
+        HeadingLevel::H3 => style.fg(Color::Blue),
+        _ => style,
+    }
+}
+
+fn quote_bars(depth: usize) -> Span<'static> {
+    Span::styled("│ ".repeat(depth), Style::default().fg(Color::DarkGray))
+}
+
+fn code_style() -> Style {
+    Style::default().fg(Color::Yellow).bg(Color::Black)
+}
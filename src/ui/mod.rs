@@ -1,13 +1,45 @@
+pub mod components;
+mod markdown;
 mod screens;
 
 use crate::app::{App, AppScreen};
+use crossterm::{
+    cursor::Show,
+    event::{DisableMouseCapture, EnableMouseCapture},
+    execute,
+    terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen},
+};
 use ratatui::Frame;
+use std::io;
+
+// Puts the terminal into raw mode on the alternate screen for the TUI
+pub fn enter_terminal() -> io::Result<()> {
+    enable_raw_mode()?;
+    execute!(io::stdout(), EnterAlternateScreen, EnableMouseCapture)
+}
+
+// Gives the terminal back, e.g. on exit or while an external editor runs
+pub fn leave_terminal() -> io::Result<()> {
+    disable_raw_mode()?;
+    execute!(
+        io::stdout(),
+        LeaveAlternateScreen,
+        DisableMouseCapture,
+        Show
+    )
+}
 
 pub fn render_ui(f: &mut Frame, app: &App) {
     match app.current_screen {
+        AppScreen::Unlock => screens::render_unlock_screen(f, app),
         AppScreen::Main => screens::render_main_screen(f, app),
         AppScreen::AddJournal => screens::render_add_journal_screen(f, app),
         AppScreen::ViewJournals => screens::render_view_journals_screen(f, app),
+        AppScreen::Trash => screens::render_trash_screen(f, app),
+        AppScreen::Calendar => screens::render_calendar_screen(f, app),
+        AppScreen::Insights => screens::render_insights_screen(f, app),
+        AppScreen::Stats => screens::render_stats_screen(f, app),
+        AppScreen::Review => screens::render_review_screen(f, app),
         AppScreen::Settings => screens::render_settings_screen(f, app),
         AppScreen::Help => screens::render_help_screen(f, app),
         AppScreen::Quit => {}
@@ -1,13 +1,1168 @@
-use crate::app::{App, InputMode, MessageType};
+use crate::app::{App, CheckInField, InputMode, JournalAction, MessageType};
+use crate::config::{FileFormat, LlmBackendKind};
+use crate::search::SearchQuery;
+use crate::stats::{JournalStats, Streak};
+use crate::storage::{
+    rating_label, JournalEntry, MoodInsights, ENERGY_EMOJI, MAX_RATING, MOOD_EMOJI,
+};
+use crate::ui::markdown::render_markdown;
+use crate::utils::word_diff;
 use ratatui::{
-    layout::{Alignment, Constraint, Direction, Layout, Rect},
+    layout::{Alignment, Constraint, Direction, Layout, Margin, Rect},
     style::{Color, Modifier, Style},
+    symbols::Marker,
     text::{Line, Span},
-    widgets::{Block, Borders, Clear, List, ListItem, Paragraph, Wrap},
+    widgets::{
+        Axis, Bar, BarChart, BarGroup, Block, Borders, Cell, Chart, Clear, Dataset, GraphType,
+        List, ListItem, ListState, Paragraph, Row, Scrollbar, ScrollbarOrientation, ScrollbarState,
+        Sparkline, Table, Wrap,
+    },
     Frame,
 };
+use std::collections::HashMap;
+use time::{Date, Month};
+
+const SPINNER: [&str; 10] = ["⠋", "⠙", "⠹", "⠸", "⠼", "⠴", "⠦", "⠧", "⠇", "⠏"];
+
+pub fn render_main_screen(f: &mut Frame, app: &App) {
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Length(3),
+            Constraint::Min(0),
+            Constraint::Length(3),
+        ])
+        .split(f.area());
+
+    // Title
+    let title = Paragraph::new("🦀 Rusty Diary")
+        .style(
+            Style::default()
+                .fg(Color::Cyan)
+                .add_modifier(Modifier::BOLD),
+        )
+        .alignment(Alignment::Center)
+        .block(Block::default().borders(Borders::ALL));
+    f.render_widget(title, chunks[0]);
+
+    // Menu
+    let menu_items = [
+        "📝 Add Journal Entry",
+        "📖 View Journal Entries",
+        "📅 Calendar",
+        "📈 Insights",
+        "📊 Statistics",
+        "⚙️ Settings",
+        "❓ Help",
+        "🚪 Quit",
+    ];
+
+    let items: Vec<ListItem> = menu_items
+        .iter()
+        .enumerate()
+        .map(|(i, item)| {
+            let style = if i == app.selected_menu_item {
+                Style::default()
+                    .bg(Color::Yellow)
+                    .fg(Color::Black)
+                    .add_modifier(Modifier::BOLD)
+            } else {
+                Style::default()
+            };
+            ListItem::new(*item).style(style)
+        })
+        .collect();
+
+    let menu = List::new(items)
+        .block(Block::default().title("Main Menu").borders(Borders::ALL))
+        .highlight_style(Style::default().add_modifier(Modifier::ITALIC));
+
+    f.render_widget(menu, chunks[1]);
+
+    // Instructions
+    let instructions = Paragraph::new("Use ↑↓ to navigate, Enter to select, q to quit")
+        .style(Style::default().fg(Color::Gray))
+        .alignment(Alignment::Center)
+        .block(Block::default().borders(Borders::ALL));
+    f.render_widget(instructions, chunks[2]);
+}
+
+// Passphrase prompt of an encrypted journal, shown at startup
+pub fn render_unlock_screen(f: &mut Frame, app: &App) {
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Length(3),
+            Constraint::Min(0),
+            Constraint::Length(3),
+        ])
+        .split(f.area());
+
+    let title = Paragraph::new("🔒 Unlock Journal")
+        .style(
+            Style::default()
+                .fg(Color::Cyan)
+                .add_modifier(Modifier::BOLD),
+        )
+        .alignment(Alignment::Center)
+        .block(Block::default().borders(Borders::ALL));
+    f.render_widget(title, chunks[0]);
+
+    let area = centered_rect(60, 100, chunks[1]);
+    let rows = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Min(0),
+            Constraint::Length(2),
+            Constraint::Length(3),
+            Constraint::Length(2),
+            Constraint::Min(0),
+        ])
+        .split(area);
+
+    let info = Paragraph::new(format!(
+        "The journal in {} is encrypted.",
+        app.config.get_journal_directory_display()
+    ))
+    .style(Style::default().fg(Color::Gray))
+    .alignment(Alignment::Center)
+    .wrap(Wrap { trim: true });
+    f.render_widget(info, rows[1]);
+
+    let masked = "•".repeat(app.passphrase_input.chars().count());
+    let input = Paragraph::new(format!("{}█", masked)).block(
+        Block::default()
+            .borders(Borders::ALL)
+            .title("Passphrase")
+            .border_style(Style::default().fg(Color::Yellow)),
+    );
+    f.render_widget(input, rows[2]);
+
+    if let Some(message) = &app.message {
+        let error = Paragraph::new(message.as_str())
+            .style(Style::default().fg(Color::Red))
+            .alignment(Alignment::Center)
+            .wrap(Wrap { trim: true });
+        f.render_widget(error, rows[3]);
+    }
+
+    let instructions = Paragraph::new("Enter to unlock, Esc to quit")
+        .style(Style::default().fg(Color::Gray))
+        .alignment(Alignment::Center)
+        .block(Block::default().borders(Borders::ALL));
+    f.render_widget(instructions, chunks[2]);
+}
+
+pub fn render_add_journal_screen(f: &mut Frame, app: &App) {
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Length(3),
+            Constraint::Min(8),
+            Constraint::Length(3),
+            Constraint::Length(3),
+        ])
+        .split(f.area());
+
+    // Title
+    let title = match app.entry_day {
+        Some(day) => format!("📝 Add Journal Entry for {}", format_day(app, day)),
+        None => "📝 Add Journal Entry".to_string(),
+    };
+    let title = Paragraph::new(title)
+        .style(
+            Style::default()
+                .fg(Color::Green)
+                .add_modifier(Modifier::BOLD),
+        )
+        .alignment(Alignment::Center)
+        .block(Block::default().borders(Borders::ALL));
+    f.render_widget(title, chunks[0]);
+
+    // Input area
+    let input_style = match app.input_mode {
+        InputMode::Normal => Style::default(),
+        InputMode::Editing => Style::default().fg(Color::Yellow),
+    };
+
+    let mut input_block = Block::default()
+        .borders(Borders::ALL)
+        .title(match app.input_mode {
+            InputMode::Normal => "Journal Entry (Press 'e' to edit)",
+            InputMode::Editing => "Journal Entry (Press Esc when done)",
+        })
+        .title_style(input_style);
+    let ratings: Vec<String> = [
+        ("Mood", app.entry_mood, &MOOD_EMOJI),
+        ("Energy", app.entry_energy, &ENERGY_EMOJI),
+    ]
+    .into_iter()
+    .filter_map(|(name, rating, scale)| Some(format!("{} {}", name, rating_label(rating?, scale))))
+    .collect();
+    if !ratings.is_empty() {
+        input_block = input_block.title_bottom(
+            Line::from(format!(" {} ('m' to change) ", ratings.join(" · "))).right_aligned(),
+        );
+    }
+    if app.config.file_format == FileFormat::Markdown && !app.processing {
+        if let Some(prompt) = app.prompt_names.get(app.selected_prompt) {
+            input_block = input_block
+                .title(Line::from(format!(" Prompt: {} ('p' to change) ", prompt)).right_aligned());
+        }
+    }
+
+    // The LLM's response streams in next to the entry while it is being formatted
+    let input_area = if app.processing {
+        let columns = Layout::default()
+            .direction(Direction::Horizontal)
+            .constraints([Constraint::Percentage(50), Constraint::Percentage(50)])
+            .split(chunks[1]);
+        render_llm_preview(f, app, columns[1]);
+        columns[0]
+    } else {
+        chunks[1]
+    };
+
+    app.editor.render(
+        f,
+        input_area,
+        input_block,
+        input_style,
+        app.input_mode == InputMode::Editing,
+    );
+
+    // Status/Message area
+    if let Some(ref message) = app.message {
+        let message_style = match app.message_type {
+            MessageType::Success => Style::default().fg(Color::Green),
+            MessageType::Error => Style::default().fg(Color::Red),
+            MessageType::Warning => Style::default().fg(Color::Yellow),
+            MessageType::Info => Style::default().fg(Color::Blue),
+        };
+
+        let status = Paragraph::new(message.as_str())
+            .style(message_style)
+            .block(Block::default().borders(Borders::ALL).title("Status"))
+            .wrap(Wrap { trim: true });
+        f.render_widget(status, chunks[2]);
+    }
+
+    // Instructions
+    let instructions = match app.input_mode {
+        InputMode::Normal => {
+            if app.processing {
+                "Formatting with the LLM, press Esc to cancel"
+            } else if app.editor.is_empty() {
+                "Press 'e' to start editing, 'E' for external editor, 'd' to change the date, 'm' for mood, 'q' to quit"
+            } else {
+                "Press 's' to save, 'e' to edit, 'E' for external editor, 'd' to change the date, 'm' for mood, 'q' to quit"
+            }
+        }
+        InputMode::Editing => {
+            "Type your journal entry · Enter new line · Shift+arrows select · Ctrl+Z/Ctrl+Y undo/redo · Esc when done"
+        }
+    };
+
+    let instruction_widget = Paragraph::new(instructions)
+        .style(Style::default().fg(Color::Gray))
+        .alignment(Alignment::Center)
+        .block(Block::default().borders(Borders::ALL));
+    f.render_widget(instruction_widget, chunks[3]);
+
+    if let Some(input) = &app.entry_date_input {
+        render_date_input(f, app, "Entry Date", input);
+    }
+    if app.check_in.is_some() {
+        render_check_in(f, app);
+    }
+}
+
+// Popup rating mood and energy on the emoji scales
+fn render_check_in(f: &mut Frame, app: &App) {
+    let Some(check_in) = &app.check_in else {
+        return;
+    };
+
+    let row = |name: &str, field: CheckInField, rating: Option<u8>, scale: &[&str; 5]| {
+        let selected = check_in.field == field;
+        let mut spans = vec![Span::styled(
+            format!("{} {:<8}", if selected { "▶" } else { " " }, name),
+            if selected {
+                Style::default()
+                    .fg(Color::Yellow)
+                    .add_modifier(Modifier::BOLD)
+            } else {
+                Style::default()
+            },
+        )];
+        for (index, emoji) in scale.iter().enumerate() {
+            let value = index as u8 + 1;
+            let style = if rating == Some(value) {
+                Style::default().bg(Color::Yellow).fg(Color::Black)
+            } else {
+                Style::default().fg(Color::Gray)
+            };
+            spans.push(Span::styled(format!(" {} {} ", value, emoji), style));
+        }
+        Line::from(spans)
+    };
+
+    let lines = vec![
+        Line::from(""),
+        row("Mood", CheckInField::Mood, app.entry_mood, &MOOD_EMOJI),
+        Line::from(""),
+        row(
+            "Energy",
+            CheckInField::Energy,
+            app.entry_energy,
+            &ENERGY_EMOJI,
+        ),
+        Line::from(""),
+        Line::from(Span::styled(
+            "1-5 or ←→ rate · ↑↓ switch · Backspace clear · Enter next · Esc close",
+            Style::default().fg(Color::Gray),
+        )),
+    ];
+
+    let area = centered_rect(70, 40, f.area());
+    f.render_widget(Clear, area);
+    let title = if check_in.then_save {
+        "How are you? (Enter to save)"
+    } else {
+        "How are you?"
+    };
+    let widget = Paragraph::new(lines).alignment(Alignment::Center).block(
+        Block::default()
+            .borders(Borders::ALL)
+            .title(title)
+            .title_style(Style::default().fg(Color::Yellow)),
+    );
+    f.render_widget(widget, area);
+}
+
+// Popup for typing a date, see `commands::parse_entry_date`
+fn render_date_input(f: &mut Frame, app: &App, title: &str, input: &str) {
+    let area = centered_rect(80, 20, f.area());
+    f.render_widget(Clear, area);
+    let widget = Paragraph::new(input.to_string())
+        .style(Style::default().fg(Color::Yellow))
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .title(format!(
+                    "{} ({}, 'yesterday', '3 days ago', 'last friday'; Enter to apply, Esc to cancel)",
+                    title, app.config.date_format
+                ))
+                .title_style(Style::default().fg(Color::Yellow)),
+        );
+    f.render_widget(widget, area);
+}
+
+fn render_llm_preview(f: &mut Frame, app: &App, area: Rect) {
+    let spinner = SPINNER[app.spinner_frame % SPINNER.len()];
+    let title = match &app.pending_entry {
+        Some(pending) => format!(" {} {} ({}) ", spinner, pending.job.model(), pending.prompt),
+        None => format!(" {} LLM ", spinner),
+    };
+
+    let text = if app.llm_preview.is_empty() {
+        "Waiting for the first tokens..."
+    } else {
+        app.llm_preview.as_str()
+    };
+
+    // Keep the newest tokens in view
+    let inner_width = area.width.saturating_sub(2).max(1) as usize;
+    let inner_height = area.height.saturating_sub(2) as usize;
+    let lines: usize = text
+        .lines()
+        .map(|line| line.chars().count().max(1).div_ceil(inner_width))
+        .sum();
+    let scroll = lines.saturating_sub(inner_height) as u16;
+
+    let preview = Paragraph::new(text)
+        .style(Style::default().fg(Color::Cyan))
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .title(title)
+                .title_style(Style::default().fg(Color::Yellow)),
+        )
+        .wrap(Wrap { trim: false })
+        .scroll((scroll, 0));
+    f.render_widget(preview, area);
+}
+
+pub fn render_review_screen(f: &mut Frame, app: &App) {
+    let Some(review) = &app.review else {
+        return;
+    };
+
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Length(3),
+            Constraint::Min(8),
+            Constraint::Length(3),
+        ])
+        .split(f.area());
+
+    let title = Paragraph::new("🔍 Review Formatted Entry")
+        .style(
+            Style::default()
+                .fg(Color::Magenta)
+                .add_modifier(Modifier::BOLD),
+        )
+        .alignment(Alignment::Center)
+        .block(Block::default().borders(Borders::ALL));
+    f.render_widget(title, chunks[0]);
+
+    let columns = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([Constraint::Percentage(50), Constraint::Percentage(50)])
+        .split(chunks[1]);
+
+    // Words the LLM dropped are shown in red on the left, words it added in
+    // green on the right
+    let diff = word_diff(&review.entry.body, &review.formatted);
+    let removed_style = Style::default()
+        .fg(Color::Red)
+        .add_modifier(Modifier::CROSSED_OUT);
+    let added_style = Style::default()
+        .fg(Color::Green)
+        .add_modifier(Modifier::BOLD);
+
+    let raw = Paragraph::new(highlight_ranges(
+        &review.entry.body,
+        &diff.removed,
+        removed_style,
+    ))
+    .block(
+        Block::default()
+            .borders(Borders::ALL)
+            .title(format!("Raw Entry ({} removed)", diff.removed.len())),
+    )
+    .wrap(Wrap { trim: false })
+    .scroll((review.scroll, 0));
+    f.render_widget(raw, columns[0]);
+
+    let formatted_title = format!(
+        "Formatted - {} ({}, {} added){}",
+        review.model,
+        review.prompt,
+        diff.added.len(),
+        if review.editor.is_some() {
+            " (Editing...)"
+        } else {
+            ""
+        }
+    );
+    let formatted_style = if review.editor.is_some() {
+        Style::default().fg(Color::Yellow)
+    } else {
+        Style::default()
+    };
+    let formatted_block = Block::default()
+        .borders(Borders::ALL)
+        .title(formatted_title)
+        .title_style(formatted_style);
+
+    if let Some(editor) = &review.editor {
+        editor.render(f, columns[1], formatted_block, formatted_style, true);
+    } else {
+        let formatted = Paragraph::new(highlight_ranges(
+            &review.formatted,
+            &diff.added,
+            added_style,
+        ))
+        .block(formatted_block)
+        .wrap(Wrap { trim: false })
+        .scroll((review.scroll, 0));
+        f.render_widget(formatted, columns[1]);
+    }
+
+    let instructions = if review.editor.is_some() {
+        "Editing the formatted entry, Esc when done".to_string()
+    } else {
+        format!(
+            "a/Enter accept · r save raw · t retry with '{}' · p change prompt · e edit · ↑↓ scroll · Esc back",
+            app.prompt_names
+                .get(app.selected_prompt)
+                .map_or(review.prompt.as_str(), String::as_str)
+        )
+    };
+    let instruction_widget = Paragraph::new(instructions)
+        .style(Style::default().fg(Color::Gray))
+        .alignment(Alignment::Center)
+        .block(Block::default().borders(Borders::ALL));
+    f.render_widget(instruction_widget, chunks[2]);
+}
+
+pub fn render_view_journals_screen(f: &mut Frame, app: &App) {
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Length(3),
+            Constraint::Min(0),
+            Constraint::Length(3),
+        ])
+        .split(f.area());
+
+    // Title
+    let title = Paragraph::new("📖 Journal Entries")
+        .style(
+            Style::default()
+                .fg(Color::Blue)
+                .add_modifier(Modifier::BOLD),
+        )
+        .alignment(Alignment::Center)
+        .block(Block::default().borders(Borders::ALL));
+    f.render_widget(title, chunks[0]);
+
+    let mut content_area = chunks[1];
+    if app.tag_sidebar {
+        let sidebar_chunks = Layout::default()
+            .direction(Direction::Horizontal)
+            .constraints([Constraint::Length(26), Constraint::Min(0)])
+            .split(chunks[1]);
+        render_tag_sidebar(f, app, sidebar_chunks[0]);
+        content_area = sidebar_chunks[1];
+    }
+
+    // Journal list
+    if app.journal_entries.is_empty() {
+        let text = if app.all_journal_entries.is_empty() {
+            "No journal entries found.\n\nPress 'r' to refresh or 'q' to go back."
+        } else {
+            "No entries have these tags.\n\nPress Esc to clear the tag filter."
+        };
+        let empty_message = Paragraph::new(text)
+            .style(Style::default().fg(Color::Gray))
+            .alignment(Alignment::Center)
+            .block(Block::default().borders(Borders::ALL).title("No Entries"));
+        f.render_widget(empty_message, content_area);
+    } else {
+        let horizontal_chunks = Layout::default()
+            .direction(Direction::Horizontal)
+            .constraints([Constraint::Percentage(30), Constraint::Percentage(70)])
+            .split(content_area);
+
+        // Journal list
+        let items: Vec<ListItem> = app
+            .journal_entries
+            .iter()
+            .enumerate()
+            .map(|(i, entry)| {
+                let style = if i == app.selected_journal {
+                    Style::default()
+                        .bg(Color::Blue)
+                        .fg(Color::White)
+                        .add_modifier(Modifier::BOLD)
+                } else if app.search_matches.contains(&i) {
+                    Style::default().fg(Color::Yellow)
+                } else {
+                    Style::default()
+                };
+                let mut label = match entry.time_label() {
+                    Some(time) => format!("📅 {} {}", entry.date, time),
+                    None => format!("📅 {}", entry.date),
+                };
+                if entry.metadata.pending_formatting {
+                    label.push_str(" ⏳");
+                }
+                // Days can mix formats, so each entry shows the one it's stored in
+                let mut label = Line::from(label);
+                if let Some(format) = entry.format() {
+                    label.push_span(Span::styled(
+                        format!(" {}", format.extension()),
+                        Style::default().fg(Color::Gray),
+                    ));
+                }
+                match app.search_snippets.get(&i) {
+                    Some(snippet) => ListItem::new(vec![
+                        label,
+                        Line::from(Span::styled(
+                            format!("   {}", snippet),
+                            Style::default().fg(Color::Gray),
+                        )),
+                    ])
+                    .style(style),
+                    None => ListItem::new(label).style(style),
+                }
+            })
+            .collect();
+
+        let list_title = if app.tag_filter.is_empty() {
+            "Entries".to_string()
+        } else {
+            format!("Entries · {}", app.tag_filter.describe())
+        };
+        let journal_list = List::new(items).block(
+            Block::default()
+                .borders(Borders::ALL)
+                .title(list_title)
+                .border_style(focus_style(!app.preview_focused && !app.tag_sidebar)),
+        );
+        let mut list_state = ListState::default().with_selected(Some(app.selected_journal));
+        f.render_stateful_widget(journal_list, horizontal_chunks[0], &mut list_state);
+
+        // Journal content preview, or the entry being edited in place
+        if let (Some(editor), Some(selected_entry)) = (
+            &app.journal_editor,
+            app.journal_entries.get(app.selected_journal),
+        ) {
+            let editor_style = Style::default().fg(Color::Yellow);
+            let editor_block = Block::default()
+                .borders(Borders::ALL)
+                .title(format!(
+                    "Editing - {} ({})",
+                    selected_entry.date, selected_entry.filename
+                ))
+                .title_style(editor_style);
+            editor.render(f, horizontal_chunks[1], editor_block, editor_style, true);
+        } else if let Some(selected_entry) = app.journal_entries.get(app.selected_journal) {
+            render_entry_preview(f, app, selected_entry, horizontal_chunks[1]);
+        }
+    }
+
+    // Instructions
+    let search_status = if app.search_editing || !app.search_input.is_empty() {
+        let position = app
+            .search_matches
+            .iter()
+            .position(|&index| index == app.selected_journal)
+            .map_or(0, |position| position + 1);
+        Some(if app.search_editing {
+            format!(
+                "/{}  ({} matches, Enter to confirm, Esc to cancel)",
+                app.search_input,
+                app.search_matches.len()
+            )
+        } else {
+            format!(
+                "Search: {}  ({}/{}, 'n'/'N' next/previous, Esc to clear)",
+                app.search_input,
+                position,
+                app.search_matches.len()
+            )
+        })
+    } else {
+        None
+    };
+
+    let message = match (&app.message, &app.pending_job) {
+        (Some(message), Some(_)) => Some(format!(
+            "{} {}",
+            SPINNER[app.spinner_frame % SPINNER.len()],
+            message
+        )),
+        (message, _) => message.clone(),
+    };
+    let instructions = match (&search_status, &message) {
+        _ if app.journal_editor.is_some() => "Editing entry · Ctrl+S to save · Esc to discard changes",
+        _ if app.tag_sidebar => {
+            "Tags: ↑↓ move · Space toggle · 'a' AND/OR · 'c' clear · Esc close"
+        }
+        (Some(status), _) => status.as_str(),
+        (None, Some(message)) => message.as_str(),
+        (None, None) => {
+            "↑↓ navigate, '/' search, 'T' tags, 'e' edit, 'd' delete, 'm' move, 'R' rename day, 't' trash, 'f' format pending, 'q' back"
+        }
+    };
+
+    let instruction_style = match app.message_type {
+        _ if search_status.is_some() => Style::default().fg(Color::Yellow),
+        MessageType::Success => Style::default().fg(Color::Green),
+        MessageType::Error => Style::default().fg(Color::Red),
+        MessageType::Warning => Style::default().fg(Color::Yellow),
+        _ => Style::default().fg(Color::Gray),
+    };
+
+    let instruction_widget = Paragraph::new(instructions)
+        .style(instruction_style)
+        .alignment(Alignment::Center)
+        .block(Block::default().borders(Borders::ALL));
+    f.render_widget(instruction_widget, chunks[2]);
+
+    render_journal_action(f, app);
+}
+
+// Scrollable preview of an entry; Markdown entries are rendered
+fn render_entry_preview(f: &mut Frame, app: &App, entry: &JournalEntry, area: Rect) {
+    let query = SearchQuery::parse(&app.search_input);
+    let mut lines = front_matter_lines(entry);
+    lines.extend(if entry.format() == Some(FileFormat::Markdown) {
+        highlight_spans(render_markdown(&entry.body), &query)
+    } else {
+        highlight_matches(&entry.body, &query)
+    });
+
+    let mut block = Block::default()
+        .borders(Borders::ALL)
+        .title(format!("Content - {} ({})", entry.date, entry.filename))
+        .border_style(focus_style(app.preview_focused));
+    let labels: Vec<String> = entry.labels().collect();
+    if !labels.is_empty() {
+        block = block.title_bottom(Line::from(Span::styled(
+            format!(" {} ", labels.join(" ")),
+            Style::default().fg(Color::Cyan),
+        )));
+    }
+    let inner = block.inner(area);
+    let content = Paragraph::new(lines)
+        .block(block)
+        .wrap(Wrap { trim: false });
+
+    // Remember the size so scrolling stops at the last line
+    let max_scroll = (content.line_count(inner.width) as u16).saturating_sub(inner.height + 2);
+    app.preview_height.set(inner.height);
+    app.preview_max_scroll.set(max_scroll);
+    let scroll = app.preview_scroll.min(max_scroll);
+
+    f.render_widget(content.scroll((scroll, 0)), area);
+
+    if max_scroll > 0 {
+        let mut scrollbar_state =
+            ScrollbarState::new(max_scroll as usize).position(scroll as usize);
+        f.render_stateful_widget(
+            Scrollbar::new(ScrollbarOrientation::VerticalRight),
+            area.inner(Margin {
+                vertical: 1,
+                horizontal: 0,
+            }),
+            &mut scrollbar_state,
+        );
+    }
+}
+
+// The title, then mood, energy, location and weather from an entry's front
+// matter
+fn front_matter_lines(entry: &JournalEntry) -> Vec<Line<'static>> {
+    let metadata = &entry.metadata;
+    let mut lines = Vec::new();
+    if let Some(title) = &metadata.title {
+        lines.push(Line::from(Span::styled(
+            title.clone(),
+            Style::default().add_modifier(Modifier::BOLD),
+        )));
+    }
+
+    let rating = |rating: Option<u8>, value: &Option<String>, scale| {
+        rating
+            .map(|rating| rating_label(rating, scale))
+            .or_else(|| value.clone())
+    };
+    let details: Vec<String> = [
+        (
+            "Mood",
+            rating(entry.mood_rating(), &metadata.mood, &MOOD_EMOJI),
+        ),
+        (
+            "Energy",
+            rating(entry.energy_rating(), &metadata.energy, &ENERGY_EMOJI),
+        ),
+        ("Location", metadata.location.clone()),
+        ("Weather", metadata.weather.clone()),
+    ]
+    .into_iter()
+    .filter_map(|(label, value)| Some(format!("{}: {}", label, value?)))
+    .collect();
+    if !details.is_empty() {
+        lines.push(Line::from(Span::styled(
+            details.join(" · "),
+            Style::default().fg(Color::Gray),
+        )));
+    }
+
+    if !lines.is_empty() {
+        lines.push(Line::default());
+    }
+    lines
+}
+
+// Tags and mentions with their entry counts; checked ones filter the list
+fn render_tag_sidebar(f: &mut Frame, app: &App, area: Rect) {
+    let mode = if app.tag_filter.match_any {
+        "OR"
+    } else {
+        "AND"
+    };
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .title(format!("Tags ({})", mode))
+        .border_style(focus_style(true));
+
+    if app.tag_counts.is_empty() {
+        let empty = Paragraph::new("No tags yet.\n\nWrite #tags or @names in your entries.")
+            .style(Style::default().fg(Color::Gray))
+            .wrap(Wrap { trim: true })
+            .block(block);
+        f.render_widget(empty, area);
+        return;
+    }
+
+    let items: Vec<ListItem> = app
+        .tag_counts
+        .iter()
+        .map(|(label, count)| {
+            let checkbox = if app.tag_filter.labels.contains(label) {
+                Span::styled("☑ ", Style::default().fg(Color::Green))
+            } else {
+                Span::raw("☐ ")
+            };
+            let color = if label.starts_with('@') {
+                Color::Magenta
+            } else {
+                Color::Cyan
+            };
+            ListItem::new(Line::from(vec![
+                checkbox,
+                Span::styled(label.clone(), Style::default().fg(color)),
+                Span::styled(format!(" {}", count), Style::default().fg(Color::Gray)),
+            ]))
+        })
+        .collect();
+
+    let list = List::new(items).block(block).highlight_style(
+        Style::default()
+            .bg(Color::Blue)
+            .add_modifier(Modifier::BOLD),
+    );
+    let mut state = ListState::default().with_selected(Some(app.selected_tag));
+    f.render_stateful_widget(list, area, &mut state);
+}
+
+// Border of the pane that keys go to
+fn focus_style(focused: bool) -> Style {
+    if focused {
+        Style::default().fg(Color::Cyan)
+    } else {
+        Style::default()
+    }
+}
+
+// Number of entries and words written on a day
+#[derive(Clone, Copy, Default)]
+struct DayActivity {
+    entries: usize,
+    words: usize,
+}
+
+const HEAT_COLORS: [Color; 5] = [
+    Color::DarkGray,
+    Color::Indexed(22),
+    Color::Indexed(28),
+    Color::Indexed(34),
+    Color::Indexed(40),
+];
+
+pub fn render_calendar_screen(f: &mut Frame, app: &App) {
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Length(3),
+            Constraint::Min(0),
+            Constraint::Length(3),
+        ])
+        .split(f.area());
+
+    // Title
+    let title = Paragraph::new("📅 Calendar")
+        .style(
+            Style::default()
+                .fg(Color::Blue)
+                .add_modifier(Modifier::BOLD),
+        )
+        .alignment(Alignment::Center)
+        .block(Block::default().borders(Borders::ALL));
+    f.render_widget(title, chunks[0]);
+
+    let mut activity: HashMap<Date, DayActivity> = HashMap::new();
+    for entry in &app.all_journal_entries {
+        let day = activity.entry(entry.day).or_default();
+        day.entries += 1;
+        day.words += entry.word_count();
+    }
+    // Colors are relative to the busiest day of the journal
+    let value = |day: &DayActivity| {
+        if app.calendar_by_length {
+            day.words
+        } else {
+            day.entries
+        }
+    };
+    let max = activity.values().map(value).max().unwrap_or(0);
+    let heat = |date: Date| {
+        let value = activity.get(&date).map_or(0, value);
+        if value == 0 {
+            0
+        } else {
+            (value * 4).div_ceil(max).clamp(1, 4)
+        }
+    };
+
+    if app.calendar_year_view {
+        let rows = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([Constraint::Length(12), Constraint::Min(0)])
+            .split(chunks[1]);
+        render_year_heatmap(f, app, &heat, rows[0]);
+        render_calendar_day(f, app, activity.get(&app.calendar_day), rows[1]);
+    } else {
+        let columns = Layout::default()
+            .direction(Direction::Horizontal)
+            .constraints([Constraint::Length(44), Constraint::Min(0)])
+            .split(chunks[1]);
+        render_month_grid(f, app, &activity, &heat, columns[0]);
+        render_calendar_day(f, app, activity.get(&app.calendar_day), columns[1]);
+    }
+
+    // Instructions
+    let (instructions, instruction_style) = match &app.message {
+        Some(message) => (message.as_str(), Style::default().fg(Color::Yellow)),
+        None if app.calendar_year_view => (
+            "hjkl move, PgUp/PgDn year, Enter open, 'a' new entry, 'y' month view, 'c' words/entries, 't' today, 'q' back",
+            Style::default().fg(Color::Gray),
+        ),
+        None => (
+            "hjkl move, PgUp/PgDn month, Enter open, 'a' new entry, 'y' year view, 'c' words/entries, 't' today, 'q' back",
+            Style::default().fg(Color::Gray),
+        ),
+    };
+    let instruction_widget = Paragraph::new(instructions)
+        .style(instruction_style)
+        .alignment(Alignment::Center)
+        .block(Block::default().borders(Borders::ALL));
+    f.render_widget(instruction_widget, chunks[2]);
+}
+
+fn render_month_grid(
+    f: &mut Frame,
+    app: &App,
+    activity: &HashMap<Date, DayActivity>,
+    heat: &dyn Fn(Date) -> usize,
+    area: Rect,
+) {
+    let selected = app.calendar_day;
+    let today = app.today();
+    let Ok(first) = Date::from_calendar_date(selected.year(), selected.month(), 1) else {
+        return;
+    };
+    let length = selected.month().length(selected.year());
+    let offset = first.weekday().number_days_from_monday() as usize;
+
+    let mut cells: Vec<Cell> = (0..offset).map(|_| Cell::from("")).collect();
+    for day in 1..=length {
+        let Ok(date) = Date::from_calendar_date(selected.year(), selected.month(), day) else {
+            continue;
+        };
+        let level = heat(date);
+        let mut style = match level {
+            0 if date > today => Style::default().fg(Color::DarkGray),
+            0 => Style::default(),
+            _ => Style::default().bg(HEAT_COLORS[level]).fg(Color::White),
+        };
+        if date == today {
+            style = style.fg(Color::Yellow).add_modifier(Modifier::UNDERLINED);
+        }
+        if date == selected {
+            style = style.add_modifier(Modifier::REVERSED | Modifier::BOLD);
+        }
+
+        // A dot per entry under the day number
+        let entries = activity.get(&date).map_or(0, |day| day.entries);
+        let dots = match entries {
+            0 => String::new(),
+            1..=3 => " ".to_string() + &"•".repeat(entries),
+            _ => " •••+".to_string(),
+        };
+        cells.push(
+            Cell::from(vec![Line::from(format!(" {:>2}", day)), Line::from(dots)]).style(style),
+        );
+    }
+
+    let rows: Vec<Row> = cells
+        .chunks(7)
+        .map(|week| Row::new(week.to_vec()).height(2))
+        .collect();
+    let header =
+        Row::new(["Mon", "Tue", "Wed", "Thu", "Fri", "Sat", "Sun"].map(|day| format!(" {}", day)))
+            .style(Style::default().add_modifier(Modifier::BOLD))
+            .bottom_margin(1);
+
+    let table = Table::new(rows, [Constraint::Length(5); 7])
+        .header(header)
+        .column_spacing(1)
+        .block(Block::default().borders(Borders::ALL).title(format!(
+            "{} {}",
+            selected.month(),
+            selected.year()
+        )));
+    f.render_widget(table, area);
+}
+
+// Contribution-graph style overview: one column per week, one row per weekday
+fn render_year_heatmap(f: &mut Frame, app: &App, heat: &dyn Fn(Date) -> usize, area: Rect) {
+    let selected = app.calendar_day;
+    let year = selected.year();
+    let Ok(january) = Date::from_calendar_date(year, Month::January, 1) else {
+        return;
+    };
+    let start = january - time::Duration::days(january.weekday().number_days_from_monday() as i64);
+    let weeks = (Date::from_calendar_date(year, Month::December, 31)
+        .map_or(0, |end| (end - start).whole_days())
+        / 7
+        + 1) as usize;
+
+    // Two columns per week when there is room, one otherwise
+    let label_width = 4;
+    let inner_width = area.width.saturating_sub(2) as usize;
+    let cell_width = if inner_width >= label_width + weeks * 2 {
+        2
+    } else {
+        1
+    };
+
+    // Month names above the week they start in
+    let mut header = vec![' '; label_width + weeks * cell_width + 3];
+    for month in 1..=12u8 {
+        let Ok(month) = Month::try_from(month) else {
+            continue;
+        };
+        let Ok(first) = Date::from_calendar_date(year, month, 1) else {
+            continue;
+        };
+        let week = ((first - start).whole_days() / 7) as usize;
+        let position = label_width + week * cell_width;
+        for (i, c) in month.to_string().chars().take(3).enumerate() {
+            header[position + i] = c;
+        }
+    }
+    let mut lines = vec![Line::from(
+        header
+            .into_iter()
+            .collect::<String>()
+            .trim_end()
+            .to_string(),
+    )];
+
+    let today = app.today();
+    for (row, label) in ["Mon", "", "Wed", "", "Fri", "", "Sun"].iter().enumerate() {
+        let mut spans = vec![Span::raw(format!("{:<width$}", label, width = label_width))];
+        for week in 0..weeks {
+            let date = start + time::Duration::days((week * 7 + row) as i64);
+            let cell = if date.year() != year {
+                Span::raw(" ".repeat(cell_width))
+            } else {
+                let mut style = Style::default().fg(HEAT_COLORS[heat(date)]);
+                if date > today {
+                    style = Style::default().fg(Color::Black);
+                }
+                if date == selected {
+                    style = style.bg(Color::Yellow);
+                }
+                Span::styled(format!("{:<width$}", "■", width = cell_width), style)
+            };
+            spans.push(cell);
+        }
+        lines.push(Line::from(spans));
+    }
 
-pub fn render_main_screen(f: &mut Frame, app: &App) {
+    let mut legend = vec![Span::raw(format!(
+        "{:<width$}Less ",
+        "",
+        width = label_width
+    ))];
+    for color in HEAT_COLORS {
+        legend.push(Span::styled("■ ", Style::default().fg(color)));
+    }
+    legend.push(Span::raw(if app.calendar_by_length {
+        "More (words per day)"
+    } else {
+        "More (entries per day)"
+    }));
+    lines.push(Line::default());
+    lines.push(Line::from(legend));
+
+    let heatmap = Paragraph::new(lines).block(
+        Block::default()
+            .borders(Borders::ALL)
+            .title(year.to_string()),
+    );
+    f.render_widget(heatmap, area);
+}
+
+// The selected day's entries next to the calendar
+fn render_calendar_day(f: &mut Frame, app: &App, activity: Option<&DayActivity>, area: Rect) {
+    let day = app.calendar_day;
+    let mut lines = Vec::new();
+
+    match activity {
+        Some(activity) => {
+            lines.push(Line::from(Span::styled(
+                format!(
+                    "{} {}, {} words",
+                    activity.entries,
+                    if activity.entries == 1 {
+                        "entry"
+                    } else {
+                        "entries"
+                    },
+                    activity.words
+                ),
+                Style::default().add_modifier(Modifier::BOLD),
+            )));
+            lines.push(Line::default());
+            for entry in app
+                .all_journal_entries
+                .iter()
+                .filter(|entry| entry.day == day)
+            {
+                let first_line = entry
+                    .body
+                    .lines()
+                    .map(|line| line.trim_start_matches('#').trim())
+                    .find(|line| !line.is_empty())
+                    .unwrap_or("");
+                lines.push(Line::from(vec![
+                    Span::styled(
+                        format!(
+                            "{} ",
+                            entry.time_label().unwrap_or_else(|| "--:--".to_string())
+                        ),
+                        Style::default().fg(Color::Cyan),
+                    ),
+                    Span::raw(first_line.to_string()),
+                ]));
+            }
+            lines.push(Line::default());
+            lines.push(Line::from(Span::styled(
+                "Enter to read, 'a' to add another entry",
+                Style::default().fg(Color::Gray),
+            )));
+        }
+        None if day > app.today() => lines.push(Line::from(Span::styled(
+            "This day hasn't happened yet.",
+            Style::default().fg(Color::Gray),
+        ))),
+        None => lines.push(Line::from(Span::styled(
+            "No entries. Press Enter to write one for this day.",
+            Style::default().fg(Color::Gray),
+        ))),
+    }
+
+    let details = Paragraph::new(lines)
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .title(format_day(app, day)),
+        )
+        .wrap(Wrap { trim: true });
+    f.render_widget(details, area);
+}
+
+// Insights screen: mood and energy over time, mood by weekday and by tag
+pub fn render_insights_screen(f: &mut Frame, app: &App) {
     let chunks = Layout::default()
         .direction(Direction::Vertical)
         .constraints([
@@ -17,69 +1172,259 @@ pub fn render_main_screen(f: &mut Frame, app: &App) {
         ])
         .split(f.area());
 
-    // Title
-    let title = Paragraph::new("🦀 Rusty Diary")
+    let title = Paragraph::new("📈 Insights")
         .style(
             Style::default()
-                .fg(Color::Cyan)
+                .fg(Color::Magenta)
                 .add_modifier(Modifier::BOLD),
         )
         .alignment(Alignment::Center)
         .block(Block::default().borders(Borders::ALL));
     f.render_widget(title, chunks[0]);
 
-    // Menu
-    let menu_items = vec![
-        "📝 Add Journal Entry",
-        "📖 View Journal Entries",
-        "⚙️ Settings",
-        "❓ Help",
-        "🚪 Quit",
+    let today = app.today();
+    let start = app.insights_start();
+    let no_insights = MoodInsights::default();
+    let insights = app.mood_insights.as_ref().unwrap_or(&no_insights);
+    let range = match app.insights_days {
+        Some(days) => format!("last {} days", days),
+        None => "all time".to_string(),
+    };
+
+    let instructions = match &app.message {
+        Some(message) if app.message_type == MessageType::Error => message.clone(),
+        _ => format!("'r' range: {} · q back", range),
+    };
+    let instruction_widget = Paragraph::new(instructions)
+        .style(Style::default().fg(Color::Gray))
+        .alignment(Alignment::Center)
+        .block(Block::default().borders(Borders::ALL));
+    f.render_widget(instruction_widget, chunks[2]);
+
+    if insights.is_empty() {
+        let empty = Paragraph::new(format!(
+            "No mood or energy ratings ({}).\n\nPress 'm' on the Add Journal screen to rate an entry, or set `mood` and `energy` in its front matter.",
+            range
+        ))
+        .style(Style::default().fg(Color::Gray))
+        .alignment(Alignment::Center)
+        .wrap(Wrap { trim: true })
+        .block(Block::default().borders(Borders::ALL));
+        f.render_widget(empty, chunks[1]);
+        return;
+    }
+
+    let rows = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Percentage(50),
+            Constraint::Length(4),
+            Constraint::Min(6),
+        ])
+        .split(chunks[1]);
+    let columns = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([Constraint::Percentage(45), Constraint::Percentage(55)])
+        .split(rows[2]);
+
+    let first = start.unwrap_or(insights.days[0].day);
+    render_mood_chart(f, app, insights, first, today, rows[0]);
+    render_mood_sparkline(f, app, insights, today, rows[1]);
+    render_weekday_moods(f, insights, columns[0]);
+    render_tag_moods(f, insights, columns[1]);
+}
+
+// Daily average mood and energy from `first` to `last`
+fn render_mood_chart(
+    f: &mut Frame,
+    app: &App,
+    insights: &MoodInsights,
+    first: Date,
+    last: Date,
+    area: Rect,
+) {
+    let x = |day: Date| (day - first).whole_days() as f64;
+    let mood: Vec<(f64, f64)> = insights
+        .days
+        .iter()
+        .filter_map(|day| Some((x(day.day), day.mood?)))
+        .collect();
+    let energy: Vec<(f64, f64)> = insights
+        .days
+        .iter()
+        .filter_map(|day| Some((x(day.day), day.energy?)))
+        .collect();
+
+    let datasets = vec![
+        Dataset::default()
+            .name("Mood")
+            .marker(Marker::Braille)
+            .graph_type(GraphType::Line)
+            .style(Style::default().fg(Color::Yellow))
+            .data(&mood),
+        Dataset::default()
+            .name("Energy")
+            .marker(Marker::Braille)
+            .graph_type(GraphType::Line)
+            .style(Style::default().fg(Color::Cyan))
+            .data(&energy),
     ];
 
-    let items: Vec<ListItem> = menu_items
+    let date = |day: Date| {
+        app.config
+            .date_formatter()
+            .and_then(|formatter| formatter.format(day))
+            .unwrap_or_else(|_| day.to_string())
+    };
+    let span = x(last).max(1.0);
+    let title = match insights.overall {
+        Some(overall) => format!("Mood over time · average {:.1}", overall),
+        None => "Energy over time".to_string(),
+    };
+
+    let chart = Chart::new(datasets)
+        .block(Block::default().borders(Borders::ALL).title(title))
+        .hidden_legend_constraints((Constraint::Ratio(1, 2), Constraint::Ratio(1, 2)))
+        .x_axis(
+            Axis::default()
+                .style(Style::default().fg(Color::Gray))
+                .bounds([0.0, span])
+                .labels([date(first), date(last)]),
+        )
+        .y_axis(
+            Axis::default()
+                .style(Style::default().fg(Color::Gray))
+                .bounds([1.0, f64::from(MAX_RATING)])
+                .labels(["1", "3", "5"]),
+        );
+    f.render_widget(chart, area);
+}
+
+// One bar per day of the range up to today, as many as fit; days without a
+// mood are empty
+fn render_mood_sparkline(
+    f: &mut Frame,
+    app: &App,
+    insights: &MoodInsights,
+    today: Date,
+    area: Rect,
+) {
+    let fits = area.width.saturating_sub(2) as i64;
+    let width = app.insights_days.map_or(fits, |days| days.min(fits));
+    let data: Vec<u64> = (0..width)
+        .rev()
+        .map(|back| {
+            let day = today.checked_sub(time::Duration::days(back));
+            insights
+                .days
+                .iter()
+                .find(|mood| Some(mood.day) == day)
+                .and_then(|mood| mood.mood)
+                .map_or(0, |mood| mood.round() as u64)
+        })
+        .collect();
+
+    let sparkline = Sparkline::default()
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .title(format!("Mood, last {} days", width)),
+        )
+        .style(Style::default().fg(Color::Yellow))
+        .max(u64::from(MAX_RATING))
+        .data(&data);
+    f.render_widget(sparkline, area);
+}
+
+// Bars are scaled by ten so averages keep a decimal
+fn mood_bar(label: String, mood: f64, text: String) -> Bar<'static> {
+    Bar::default()
+        .label(Line::from(label))
+        .value((mood * 10.0).round() as u64)
+        .text_value(text)
+}
+
+fn render_weekday_moods(f: &mut Frame, insights: &MoodInsights, area: Rect) {
+    const WEEKDAYS: [&str; 7] = ["Mon", "Tue", "Wed", "Thu", "Fri", "Sat", "Sun"];
+    let bars: Vec<Bar> = WEEKDAYS
         .iter()
-        .enumerate()
-        .map(|(i, item)| {
-            let style = if i == app.selected_menu_item {
-                Style::default()
-                    .bg(Color::Yellow)
-                    .fg(Color::Black)
-                    .add_modifier(Modifier::BOLD)
+        .zip(insights.weekdays)
+        .map(|(name, (mood, count))| {
+            let text = if count == 0 {
+                "-".to_string()
             } else {
-                Style::default()
+                format!("{:.1}", mood)
             };
-            ListItem::new(*item).style(style)
+            mood_bar(name.to_string(), mood, text)
         })
         .collect();
 
-    let menu = List::new(items)
-        .block(Block::default().title("Main Menu").borders(Borders::ALL))
-        .highlight_style(Style::default().add_modifier(Modifier::ITALIC));
+    let bar_width = (area.width.saturating_sub(2) / 7)
+        .saturating_sub(1)
+        .clamp(3, 8);
+    let chart = BarChart::default()
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .title("Mood by weekday"),
+        )
+        .data(BarGroup::default().bars(&bars))
+        .bar_width(bar_width)
+        .bar_gap(1)
+        .max(u64::from(MAX_RATING) * 10)
+        .bar_style(Style::default().fg(Color::Yellow))
+        .value_style(Style::default().fg(Color::Black).bg(Color::Yellow));
+    f.render_widget(chart, area);
+}
 
-    f.render_widget(menu, chunks[1]);
+// Average mood of the most used tags and mentions
+fn render_tag_moods(f: &mut Frame, insights: &MoodInsights, area: Rect) {
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .title("Mood by tag (entries)");
+    if insights.labels.is_empty() {
+        let empty = Paragraph::new("No rated entries have #tags or @mentions yet.")
+            .style(Style::default().fg(Color::Gray))
+            .block(block);
+        f.render_widget(empty, area);
+        return;
+    }
 
-    // Instructions
-    let instructions = Paragraph::new("Use ↑↓ to navigate, Enter to select, q to quit")
-        .style(Style::default().fg(Color::Gray))
-        .alignment(Alignment::Center)
-        .block(Block::default().borders(Borders::ALL));
-    f.render_widget(instructions, chunks[2]);
+    let rows = area.height.saturating_sub(2) as usize;
+    let bars: Vec<Bar> = insights
+        .labels
+        .iter()
+        .take(rows)
+        .map(|(label, mood, count)| {
+            mood_bar(label.clone(), *mood, format!("{:.1} ({})", mood, count))
+        })
+        .collect();
+
+    let chart = BarChart::default()
+        .block(block)
+        .direction(Direction::Horizontal)
+        .data(BarGroup::default().bars(&bars))
+        .bar_width(1)
+        .bar_gap(0)
+        .max(u64::from(MAX_RATING) * 10)
+        .bar_style(Style::default().fg(Color::Cyan))
+        .value_style(Style::default().fg(Color::Black).bg(Color::Cyan));
+    f.render_widget(chart, area);
 }
 
-pub fn render_add_journal_screen(f: &mut Frame, app: &App) {
+// Statistics screen: counts, averages and streaks, words per month, entries
+// by hour and the most used words
+pub fn render_stats_screen(f: &mut Frame, app: &App) {
     let chunks = Layout::default()
         .direction(Direction::Vertical)
         .constraints([
             Constraint::Length(3),
-            Constraint::Min(8),
-            Constraint::Length(3),
+            Constraint::Min(0),
             Constraint::Length(3),
         ])
         .split(f.area());
 
-    // Title
-    let title = Paragraph::new("📝 Add Journal Entry")
+    let title = Paragraph::new("📊 Statistics")
         .style(
             Style::default()
                 .fg(Color::Green)
@@ -89,88 +1434,180 @@ pub fn render_add_journal_screen(f: &mut Frame, app: &App) {
         .block(Block::default().borders(Borders::ALL));
     f.render_widget(title, chunks[0]);
 
-    // Input area
-    let input_style = match app.input_mode {
-        InputMode::Normal => Style::default(),
-        InputMode::Editing => Style::default().fg(Color::Yellow),
+    let instructions = match &app.message {
+        Some(message) if app.message_type == MessageType::Error => message.clone(),
+        _ => "q back · `rusty_diary stats --json` prints these for scripts".to_string(),
+    };
+    let instruction_widget = Paragraph::new(instructions)
+        .style(Style::default().fg(Color::Gray))
+        .alignment(Alignment::Center)
+        .block(Block::default().borders(Borders::ALL));
+    f.render_widget(instruction_widget, chunks[2]);
+
+    let Some(stats) = app.journal_stats.as_ref().filter(|stats| stats.entries > 0) else {
+        let empty = Paragraph::new("No journal entries yet.")
+            .style(Style::default().fg(Color::Gray))
+            .alignment(Alignment::Center)
+            .block(Block::default().borders(Borders::ALL));
+        f.render_widget(empty, chunks[1]);
+        return;
     };
 
-    let input_block = Block::default()
-        .borders(Borders::ALL)
-        .title(match app.input_mode {
-            InputMode::Normal => "Journal Entry (Press 'e' to edit)",
-            InputMode::Editing => "Journal Entry (Press Esc when done)",
+    let rows = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Length(11), Constraint::Min(6)])
+        .split(chunks[1]);
+    let top = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([Constraint::Percentage(60), Constraint::Percentage(40)])
+        .split(rows[0]);
+    let bottom = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([Constraint::Percentage(60), Constraint::Percentage(40)])
+        .split(rows[1]);
+
+    render_stats_summary(f, app, stats, top[0]);
+    render_top_words(f, stats, top[1]);
+    render_month_words(f, stats, bottom[0]);
+    render_hour_entries(f, stats, bottom[1]);
+}
+
+fn render_stats_summary(f: &mut Frame, app: &App, stats: &JournalStats, area: Rect) {
+    let date = |day: Option<Date>| {
+        day.map_or("-".to_string(), |day| {
+            app.config
+                .date_formatter()
+                .and_then(|formatter| formatter.format(day))
+                .unwrap_or_else(|_| day.to_string())
         })
-        .title_style(input_style);
+    };
+    let streak = |streak: &Streak| match streak.days {
+        0 => "none".to_string(),
+        1 => format!("1 day, {}", date(streak.end)),
+        days => format!(
+            "{} days, {} to {}",
+            days,
+            date(streak.start),
+            date(streak.end)
+        ),
+    };
+    let hour = stats.most_active_hour.map_or("-".to_string(), |hour| {
+        format!("{:02}:00 ({} entries)", hour, stats.hours[hour as usize])
+    });
 
-    let input = Paragraph::new(app.current_input.as_str())
-        .style(input_style)
-        .block(input_block)
-        .wrap(Wrap { trim: true });
+    let rows = [
+        ("Entries", stats.entries.to_string()),
+        ("Days written", stats.days_written.to_string()),
+        ("Words", stats.words.to_string()),
+        ("Words/entry", format!("{:.0}", stats.average_words)),
+        (
+            "Words/day",
+            format!(
+                "{:.0} ({:.0} a week, {:.0} a month)",
+                stats.words_per_day, stats.words_per_week, stats.words_per_month
+            ),
+        ),
+        ("Current streak", streak(&stats.current_streak)),
+        ("Longest streak", streak(&stats.longest_streak)),
+        ("Most active hour", hour),
+        (
+            "Written",
+            format!("{} to {}", date(stats.first_day), date(stats.last_day)),
+        ),
+    ]
+    .map(|(label, value)| {
+        Row::new(vec![
+            Cell::from(label).style(Style::default().fg(Color::Gray)),
+            Cell::from(value),
+        ])
+    });
 
-    f.render_widget(input, chunks[1]);
+    let table = Table::new(rows, [Constraint::Length(17), Constraint::Min(0)])
+        .block(Block::default().borders(Borders::ALL).title("Summary"));
+    f.render_widget(table, area);
+}
 
-    // Set cursor position when editing
-    if app.input_mode == InputMode::Editing {
-        f.set_cursor_position((
-            chunks[1].x + (app.cursor_position % (chunks[1].width as usize - 2)) as u16 + 1,
-            chunks[1].y + (app.cursor_position / (chunks[1].width as usize - 2)) as u16 + 1,
-        ));
+fn render_top_words(f: &mut Frame, stats: &JournalStats, area: Rect) {
+    let block = Block::default().borders(Borders::ALL).title("Top words");
+    if stats.top_words.is_empty() {
+        let empty = Paragraph::new("Not enough words yet.")
+            .style(Style::default().fg(Color::Gray))
+            .block(block);
+        f.render_widget(empty, area);
+        return;
     }
 
-    // Status/Message area
-    if let Some(ref message) = app.message {
-        let message_style = match app.message_type {
-            MessageType::Success => Style::default().fg(Color::Green),
-            MessageType::Error => Style::default().fg(Color::Red),
-            MessageType::Warning => Style::default().fg(Color::Yellow),
-            MessageType::Info => Style::default().fg(Color::Blue),
-        };
+    let rows: Vec<Row> = stats
+        .top_words
+        .iter()
+        .map(|word| {
+            Row::new(vec![
+                Cell::from(word.word.clone()),
+                Cell::from(word.count.to_string()).style(Style::default().fg(Color::Gray)),
+            ])
+        })
+        .collect();
+    let table = Table::new(rows, [Constraint::Min(0), Constraint::Length(6)]).block(block);
+    f.render_widget(table, area);
+}
 
-        let status = Paragraph::new(message.as_str())
-            .style(message_style)
-            .block(Block::default().borders(Borders::ALL).title("Status"))
-            .wrap(Wrap { trim: true });
-        f.render_widget(status, chunks[2]);
-    }
+// Words written in each of the last twelve months
+fn render_month_words(f: &mut Frame, stats: &JournalStats, area: Rect) {
+    let bars: Vec<Bar> = stats
+        .months
+        .iter()
+        .map(|month| {
+            // "2024-05" is labeled "05"
+            let label = month.month.get(5..).unwrap_or(&month.month).to_string();
+            Bar::default()
+                .label(Line::from(label))
+                .value(month.words as u64)
+        })
+        .collect();
 
-    // Instructions
-    let instructions = match app.input_mode {
-        InputMode::Normal => {
-            if app.processing {
-                "Processing..."
-            } else if app.current_input.is_empty() {
-                "Press 'e' to start editing, 'q' to quit"
-            } else {
-                "Press 's' to save, 'e' to edit, 'q' to quit"
-            }
-        }
-        InputMode::Editing => "Type your journal entry, Press Esc when done",
-    };
+    let bar_width = (area.width.saturating_sub(2) / bars.len().max(1) as u16)
+        .saturating_sub(1)
+        .clamp(2, 6);
+    let chart = BarChart::default()
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .title("Words per month"),
+        )
+        .data(BarGroup::default().bars(&bars))
+        .bar_width(bar_width)
+        .bar_gap(1)
+        .bar_style(Style::default().fg(Color::Green))
+        .value_style(Style::default().fg(Color::Black).bg(Color::Green));
+    f.render_widget(chart, area);
+}
 
-    let instruction_widget = Paragraph::new(instructions)
-        .style(Style::default().fg(Color::Gray))
-        .alignment(Alignment::Center)
-        .block(Block::default().borders(Borders::ALL));
-    f.render_widget(instruction_widget, chunks[3]);
+// Entries written in each hour of the day, midnight first
+fn render_hour_entries(f: &mut Frame, stats: &JournalStats, area: Rect) {
+    let data: Vec<u64> = stats.hours.iter().map(|count| *count as u64).collect();
+    let sparkline = Sparkline::default()
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .title("Entries by hour, 00 to 23"),
+        )
+        .style(Style::default().fg(Color::Cyan))
+        .data(&data);
+    f.render_widget(sparkline, area);
+}
 
-    // Show processing overlay if processing
-    if app.processing {
-        let area = centered_rect(50, 20, f.area());
-        f.render_widget(Clear, area);
-        let processing = Paragraph::new("🔄 Processing with LLM...")
-            .style(
-                Style::default()
-                    .fg(Color::Yellow)
-                    .add_modifier(Modifier::BOLD),
-            )
-            .alignment(Alignment::Center)
-            .block(Block::default().borders(Borders::ALL).title("Please Wait"));
-        f.render_widget(processing, area);
-    }
+// "Saturday, 2024-05-04" using the configured date format
+fn format_day(app: &App, day: Date) -> String {
+    let date = app
+        .config
+        .date_formatter()
+        .and_then(|formatter| formatter.format(day))
+        .unwrap_or_else(|_| day.to_string());
+    format!("{}, {}", day.weekday(), date)
 }
 
-pub fn render_view_journals_screen(f: &mut Frame, app: &App) {
+// Trash screen: deleted entries that can be restored to their day
+pub fn render_trash_screen(f: &mut Frame, app: &App) {
     let chunks = Layout::default()
         .direction(Direction::Vertical)
         .constraints([
@@ -181,7 +1618,7 @@ pub fn render_view_journals_screen(f: &mut Frame, app: &App) {
         .split(f.area());
 
     // Title
-    let title = Paragraph::new("📖 Journal Entries")
+    let title = Paragraph::new("🗑️ Trash")
         .style(
             Style::default()
                 .fg(Color::Blue)
@@ -191,13 +1628,11 @@ pub fn render_view_journals_screen(f: &mut Frame, app: &App) {
         .block(Block::default().borders(Borders::ALL));
     f.render_widget(title, chunks[0]);
 
-    // Journal list
-    if app.journal_entries.is_empty() {
-        let empty_message =
-            Paragraph::new("No journal entries found.\n\nPress 'r' to refresh or 'q' to go back.")
-                .style(Style::default().fg(Color::Gray))
-                .alignment(Alignment::Center)
-                .block(Block::default().borders(Borders::ALL).title("No Entries"));
+    if app.trash_entries.is_empty() {
+        let empty_message = Paragraph::new("The trash is empty.\n\nPress 'q' to go back.")
+            .style(Style::default().fg(Color::Gray))
+            .alignment(Alignment::Center)
+            .block(Block::default().borders(Borders::ALL).title("No Entries"));
         f.render_widget(empty_message, chunks[1]);
     } else {
         let horizontal_chunks = Layout::default()
@@ -205,13 +1640,12 @@ pub fn render_view_journals_screen(f: &mut Frame, app: &App) {
             .constraints([Constraint::Percentage(30), Constraint::Percentage(70)])
             .split(chunks[1]);
 
-        // Journal list
         let items: Vec<ListItem> = app
-            .journal_entries
+            .trash_entries
             .iter()
             .enumerate()
-            .map(|(i, entry)| {
-                let style = if i == app.selected_journal {
+            .map(|(i, trashed)| {
+                let style = if i == app.selected_trash {
                     Style::default()
                         .bg(Color::Blue)
                         .fg(Color::White)
@@ -219,22 +1653,28 @@ pub fn render_view_journals_screen(f: &mut Frame, app: &App) {
                 } else {
                     Style::default()
                 };
-                ListItem::new(format!("📅 {}", entry.date)).style(style)
+                let label = match trashed.entry.time_label() {
+                    Some(time) => format!("📅 {} {}", trashed.entry.date, time),
+                    None => format!("📅 {}", trashed.entry.date),
+                };
+                ListItem::new(label).style(style)
             })
             .collect();
 
-        let journal_list =
-            List::new(items).block(Block::default().borders(Borders::ALL).title("Entries"));
-        f.render_widget(journal_list, horizontal_chunks[0]);
+        let trash_list = List::new(items).block(
+            Block::default()
+                .borders(Borders::ALL)
+                .title("Deleted Entries"),
+        );
+        let mut list_state = ListState::default().with_selected(Some(app.selected_trash));
+        f.render_stateful_widget(trash_list, horizontal_chunks[0], &mut list_state);
 
-        // Journal content preview
-        if let Some(selected_entry) = app.journal_entries.get(app.selected_journal) {
-            let content = Paragraph::new(selected_entry.content.as_str())
-                .style(Style::default())
+        if let Some(trashed) = app.trash_entries.get(app.selected_trash) {
+            let content = Paragraph::new(trashed.entry.body.as_str())
                 .block(
                     Block::default()
                         .borders(Borders::ALL)
-                        .title(format!("Content - {}", selected_entry.date)),
+                        .title(format!("Content - {}", trashed.entry.date)),
                 )
                 .wrap(Wrap { trim: true });
             f.render_widget(content, horizontal_chunks[1]);
@@ -242,23 +1682,62 @@ pub fn render_view_journals_screen(f: &mut Frame, app: &App) {
     }
 
     // Instructions
-    let instructions = if app.message.is_some() {
-        app.message.as_ref().unwrap()
-    } else {
-        "Use ↑↓ to navigate, 'r' to refresh, 'q' to go back"
-    };
-
-    let instruction_style = match app.message_type {
-        MessageType::Success => Style::default().fg(Color::Green),
-        MessageType::Error => Style::default().fg(Color::Red),
-        _ => Style::default().fg(Color::Gray),
+    let (instructions, instruction_style) = match &app.message {
+        Some(message) => (
+            message.as_str(),
+            match app.message_type {
+                MessageType::Success => Style::default().fg(Color::Green),
+                MessageType::Error => Style::default().fg(Color::Red),
+                MessageType::Warning => Style::default().fg(Color::Yellow),
+                MessageType::Info => Style::default().fg(Color::Gray),
+            },
+        ),
+        None => (
+            "Use ↑↓ to navigate, Enter or 'u' to restore, 'd' to delete permanently, 'q' to go back",
+            Style::default().fg(Color::Gray),
+        ),
     };
-
     let instruction_widget = Paragraph::new(instructions)
         .style(instruction_style)
         .alignment(Alignment::Center)
         .block(Block::default().borders(Borders::ALL));
     f.render_widget(instruction_widget, chunks[2]);
+
+    render_journal_action(f, app);
+}
+
+// Popup asking for the new date of a move/rename or to confirm a delete
+fn render_journal_action(f: &mut Frame, app: &App) {
+    let (title, text) = match &app.journal_action {
+        None => return,
+        Some(JournalAction::Redate(input)) => {
+            return render_date_input(f, app, "Move Entry to Date", input)
+        }
+        Some(JournalAction::RenameDay(input)) => {
+            return render_date_input(f, app, "Rename Day to Date", input)
+        }
+        Some(JournalAction::Delete) => (
+            "Delete Entry",
+            "Move the selected entry to the trash? (y/n)",
+        ),
+        Some(JournalAction::Purge) => (
+            "Delete Permanently",
+            "Delete the selected entry for good? This can't be undone. (y/n)",
+        ),
+    };
+
+    let area = centered_rect(80, 20, f.area());
+    f.render_widget(Clear, area);
+    let widget = Paragraph::new(text)
+        .style(Style::default().fg(Color::Yellow))
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .title(title)
+                .title_style(Style::default().fg(Color::Yellow)),
+        )
+        .wrap(Wrap { trim: true });
+    f.render_widget(widget, area);
 }
 
 pub fn render_help_screen(f: &mut Frame, _app: &App) {
@@ -308,24 +1787,64 @@ pub fn render_help_screen(f: &mut Frame, _app: &App) {
         )]),
         Line::from("  • Press 'e' to start editing"),
         Line::from("  • Press Esc to stop editing"),
+        Line::from("  • While editing: Enter adds a line, Shift+arrows select, Ctrl+Z/Ctrl+Y undo/redo"),
         Line::from("  • Press 's' to save your entry"),
+        Line::from("  • Press 'E' to write the entry in your external editor"),
+        Line::from("  • Press 'd' to write it for another day (e.g. yesterday, last friday)"),
+        Line::from("  • Press 'p' to choose the prompt used for LLM formatting"),
+        Line::from("  • Press 'm' to rate your mood and energy from 1 to 5 (😞 … 😄)"),
         Line::from("  • Your entry will be processed with LLM for formatting"),
+        Line::from("  • The LLM's response streams in beside your entry, Esc cancels it"),
+        Line::from("  • If the LLM is unreachable the entry is saved as written (see [llm] fallback)"),
+        Line::from("  • Review the formatted version before it is saved: a accept, r save raw, t retry, e edit"),
         Line::from(""),
         Line::from(vec![Span::styled(
             "View Journal Entries:",
             Style::default().add_modifier(Modifier::BOLD),
         )]),
         Line::from("  • Use ↑↓ to browse entries"),
+        Line::from("  • Press '/' to search, 'n'/'N' to jump between matches"),
+        Line::from("  • Search supports \"phrases\", AND, OR and -excluded terms"),
         Line::from("  • Press 'r' to refresh the list"),
-        Line::from("  • View content preview on the right"),
+        Line::from("  • Press 'e' to edit the selected entry, Ctrl+S to save it"),
+        Line::from("  • Press 'd' to delete, 'm' to move to another date, 'R' to rename the day"),
+        Line::from("  • Press 't' to open the trash and restore deleted entries"),
+        Line::from("  • Press 'T' to filter by #tags and @mentions: Space toggles, 'a' AND/OR"),
+        Line::from("  • Press 'E' to open the selected day file in your external editor"),
+        Line::from("  • Press 'f' to format entries marked ⏳ (saved while the LLM was unavailable)"),
+        Line::from("  • View content preview on the right, Markdown is rendered"),
+        Line::from("  • Press Tab to focus the preview, PgUp/PgDn or j/k to scroll it"),
+        Line::from(""),
+        Line::from(vec![Span::styled(
+            "Calendar:",
+            Style::default().add_modifier(Modifier::BOLD),
+        )]),
+        Line::from("  • Move with hjkl or the arrows, PgUp/PgDn for the previous/next month"),
+        Line::from("  • Enter opens the day, or starts an entry for a past day; 'a' adds one"),
+        Line::from("  • Press 'y' for the year heatmap, 'c' to color by words instead of entries"),
+        Line::from(""),
+        Line::from(vec![Span::styled(
+            "Insights:",
+            Style::default().add_modifier(Modifier::BOLD),
+        )]),
+        Line::from("  • Mood and energy over time, mood by weekday and mood by tag"),
+        Line::from("  • Press 'r' to switch between the last 30, 90 and 365 days and all time"),
+        Line::from(""),
+        Line::from(vec![Span::styled(
+            "Statistics:",
+            Style::default().add_modifier(Modifier::BOLD),
+        )]),
+        Line::from("  • Entries, words per day, week and month, writing streaks"),
+        Line::from("  • Words per month, entries by hour of the day and the most used words"),
         Line::from(""),
         Line::from(vec![Span::styled(
             "Features:",
             Style::default().add_modifier(Modifier::BOLD),
         )]),
         Line::from("  • Automatic date-based file naming"),
-        Line::from("  • LLM processing with Ollama"),
+        Line::from("  • LLM processing with Ollama or any OpenAI-compatible server"),
         Line::from("  • Markdown formatting"),
+        Line::from("  • Optional encryption: rusty_diary encrypt, unlocked at startup"),
         Line::from("  • Beautiful TUI interface"),
         Line::from(""),
         Line::from(vec![Span::styled(
@@ -350,6 +1869,98 @@ pub fn render_help_screen(f: &mut Frame, _app: &App) {
     f.render_widget(instructions, chunks[2]);
 }
 
+// Splits text into lines, highlighting the parts matched by a search query
+fn highlight_matches<'a>(text: &'a str, query: &SearchQuery) -> Vec<Line<'a>> {
+    let highlight = Style::default()
+        .bg(Color::Yellow)
+        .fg(Color::Black)
+        .add_modifier(Modifier::BOLD);
+
+    highlight_ranges(text, &query.match_ranges(text), highlight)
+}
+
+// Highlights search matches inside already styled lines
+fn highlight_spans<'a>(lines: Vec<Line<'a>>, query: &SearchQuery) -> Vec<Line<'a>> {
+    if query.is_empty() {
+        return lines;
+    }
+    let highlight = Style::default()
+        .bg(Color::Yellow)
+        .fg(Color::Black)
+        .add_modifier(Modifier::BOLD);
+
+    lines
+        .into_iter()
+        .map(|line| {
+            let mut spans = Vec::new();
+            for span in line.spans {
+                let ranges = query.match_ranges(&span.content);
+                if ranges.is_empty() {
+                    spans.push(span);
+                    continue;
+                }
+                let mut position = 0;
+                for (start, end) in ranges {
+                    if start < position {
+                        continue;
+                    }
+                    if start > position {
+                        spans.push(Span::styled(
+                            span.content[position..start].to_string(),
+                            span.style,
+                        ));
+                    }
+                    spans.push(Span::styled(
+                        span.content[start..end].to_string(),
+                        span.style.patch(highlight),
+                    ));
+                    position = end;
+                }
+                if position < span.content.len() {
+                    spans.push(Span::styled(
+                        span.content[position..].to_string(),
+                        span.style,
+                    ));
+                }
+            }
+            Line::from(spans)
+        })
+        .collect()
+}
+
+// Splits text into lines, styling the given byte ranges of the text
+fn highlight_ranges<'a>(text: &'a str, ranges: &[(usize, usize)], style: Style) -> Vec<Line<'a>> {
+    let mut line_start = 0;
+
+    text.split('\n')
+        .map(|line| {
+            let offset = line_start;
+            line_start += line.len() + 1;
+            let line = line.strip_suffix('\r').unwrap_or(line);
+
+            let mut spans = Vec::new();
+            let mut position = 0;
+            for &(start, end) in ranges {
+                // Clip the range to this line
+                let start = start.saturating_sub(offset).max(position);
+                let end = end.saturating_sub(offset).min(line.len());
+                if start >= end {
+                    continue;
+                }
+                if start > position {
+                    spans.push(Span::raw(&line[position..start]));
+                }
+                spans.push(Span::styled(&line[start..end], style));
+                position = end;
+            }
+            if position < line.len() {
+                spans.push(Span::raw(&line[position..]));
+            }
+            Line::from(spans)
+        })
+        .collect()
+}
+
 // Helper function to create a centered rectangle
 fn centered_rect(percent_x: u16, percent_y: u16, r: Rect) -> Rect {
     let popup_layout = Layout::default()
@@ -393,13 +2004,34 @@ pub fn render_settings_screen(f: &mut Frame, app: &App) {
     f.render_widget(title, chunks[0]);
 
     // Settings list
-    let settings_items = vec![
+    let settings_items = [
         format!(
             "Journal Directory: {}",
             app.config.get_journal_directory_display()
         ),
         format!("File Format: {}", app.config.file_format.display_name()),
         format!("Date Format: {}", app.config.date_format),
+        match app.config.journal_time_zone() {
+            Ok(time_zone) => format!("Time Zone: {} ({})", app.config.time_zone, time_zone.name()),
+            Err(_) => format!("Time Zone: {} (invalid)", app.config.time_zone),
+        },
+        format!(
+            "LLM Model: {} ({}, {})",
+            app.config.llm.model,
+            app.config.llm.backend.display_name(),
+            match app.config.llm.backend {
+                LlmBackendKind::Cli => app.config.llm.command.as_str(),
+                _ => app.config.llm.base_url.as_str(),
+            }
+        ),
+        format!(
+            "Mood Check-in: {}",
+            if app.config.mood_check_in {
+                "on (asked when saving)"
+            } else {
+                "off ('m' on the Add Journal screen)"
+            }
+        ),
     ];
 
     let items: Vec<ListItem> = settings_items
@@ -440,6 +2072,9 @@ pub fn render_settings_screen(f: &mut Frame, app: &App) {
         let input_title = match app.settings_selection {
             0 => "Edit Journal Directory",
             1 => "Edit File Format (md/txt/json)",
+            2 => "Edit Date Format (strftime, e.g. %Y-%m-%d)",
+            3 => "Edit Time Zone (local, IANA name like Europe/Berlin, or +HH:MM)",
+            4 => "Edit LLM Model (backend and URL are set in the [llm] section of config.toml)",
             _ => "Edit Setting",
         };
 
@@ -467,6 +2102,7 @@ pub fn render_settings_screen(f: &mut Frame, app: &App) {
         match app.message_type {
             MessageType::Success => Style::default().fg(Color::Green),
             MessageType::Error => Style::default().fg(Color::Red),
+            MessageType::Warning => Style::default().fg(Color::Yellow),
             _ => Style::default().fg(Color::Gray),
         }
     } else {
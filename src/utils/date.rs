@@ -0,0 +1,181 @@
+use anyhow::{anyhow, Result};
+use time::format_description::{self, OwnedFormatItem};
+use time::{Date, Duration, Month, Weekday};
+
+// Formats journal dates with the strftime-style `date_format` setting and parses
+// file names back into dates using the same pattern.
+pub struct DateFormat {
+    pattern: String,
+    items: OwnedFormatItem,
+}
+
+impl DateFormat {
+    pub fn new(pattern: &str) -> Result<Self> {
+        let items = format_description::parse_strftime_owned(pattern)
+            .map_err(|e| anyhow!("Invalid date format '{}': {}", pattern, e))?;
+        let date_format = Self {
+            pattern: pattern.to_string(),
+            items,
+        };
+
+        // The pattern is used for file names, so it has to identify a single day
+        // and must not produce path separators.
+        let sample = Date::from_calendar_date(2024, Month::January, 15)?;
+        let formatted = date_format.format(sample)?;
+        if formatted.contains('/') || formatted.contains('\\') {
+            return Err(anyhow!(
+                "Date format '{}' must not contain path separators",
+                pattern
+            ));
+        }
+        if date_format.parse(&formatted) != Some(sample) {
+            return Err(anyhow!(
+                "Date format '{}' must include the year, month and day",
+                pattern
+            ));
+        }
+
+        Ok(date_format)
+    }
+
+    pub fn format(&self, date: Date) -> Result<String> {
+        date.format(&self.items)
+            .map_err(|e| anyhow!("Failed to format date with '{}': {}", self.pattern, e))
+    }
+
+    pub fn parse(&self, text: &str) -> Option<Date> {
+        Date::parse(text, &self.items).ok()
+    }
+}
+
+// Moves a date by whole months, clamping the day to the length of the month
+pub fn add_months(date: Date, months: i32) -> Date {
+    let index = date.year() * 12 + date.month() as i32 - 1 + months;
+    let year = index.div_euclid(12);
+    let Ok(month) = Month::try_from(index.rem_euclid(12) as u8 + 1) else {
+        return date;
+    };
+    let day = date.day().min(month.length(year));
+    Date::from_calendar_date(year, month, day).unwrap_or(date)
+}
+
+// Parses a date typed by the user: the configured format, ISO 8601
+// ("2024-05-03"), "today", "yesterday", "N days ago", or a weekday. A bare
+// weekday ("friday") is its latest occurrence up to today, "last friday" the
+// one before today.
+pub fn parse_user_date(input: &str, today: Date, date_format: &DateFormat) -> Result<Date> {
+    let text = input.trim();
+    if let Some(date) = date_format.parse(text) {
+        return Ok(date);
+    }
+    let iso = format_description::parse("[year]-[month]-[day]")?;
+    if let Ok(date) = Date::parse(text, &iso) {
+        return Ok(date);
+    }
+
+    let lower = text.to_lowercase();
+    let words: Vec<&str> = lower.split_whitespace().collect();
+    let date = match words.as_slice() {
+        ["today"] => Some(today),
+        ["yesterday"] => today.checked_sub(Duration::days(1)),
+        [count, "day" | "days", "ago"] => count
+            .parse::<i64>()
+            .ok()
+            .and_then(|count| today.checked_sub(Duration::days(count))),
+        ["last", name] => parse_weekday(name).map(|weekday| today.prev_occurrence(weekday)),
+        [name] => parse_weekday(name).map(|weekday| {
+            if today.weekday() == weekday {
+                today
+            } else {
+                today.prev_occurrence(weekday)
+            }
+        }),
+        _ => None,
+    };
+
+    date.ok_or_else(|| {
+        let formats = if date_format.pattern == "%Y-%m-%d" {
+            date_format.pattern.clone()
+        } else {
+            format!("{}, YYYY-MM-DD", date_format.pattern)
+        };
+        anyhow!(
+            "'{}' isn't a date. Use {}, 'yesterday', 'N days ago' or a weekday like 'last friday'",
+            text,
+            formats
+        )
+    })
+}
+
+// "friday" or "fri"
+fn parse_weekday(name: &str) -> Option<Weekday> {
+    let mut weekday = Weekday::Monday;
+    for _ in 0..7 {
+        let full = weekday.to_string().to_lowercase();
+        if name == full || (name.len() >= 3 && full.starts_with(name)) {
+            return Some(weekday);
+        }
+        weekday = weekday.next();
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn day(year: i32, month: Month, day: u8) -> Date {
+        Date::from_calendar_date(year, month, day).unwrap()
+    }
+
+    #[test]
+    fn formats_and_parses_back_the_same_day() {
+        let sample = day(2024, Month::May, 4);
+        for (pattern, name) in [
+            ("%Y-%m-%d", "2024-05-04"),
+            ("%Y%m%d", "20240504"),
+            ("%d.%m.%Y", "04.05.2024"),
+            ("%B-%d-%Y", "May-04-2024"),
+        ] {
+            let date_format = DateFormat::new(pattern).unwrap();
+            assert_eq!(date_format.format(sample).unwrap(), name);
+            assert_eq!(date_format.parse(name), Some(sample));
+        }
+    }
+
+    #[test]
+    fn rejects_patterns_that_cannot_name_a_day_file() {
+        assert!(DateFormat::new("%Y-%m").is_err());
+        assert!(DateFormat::new("%Y/%m/%d").is_err());
+        assert!(DateFormat::new("%Q").is_err());
+    }
+
+    #[test]
+    fn parses_relative_dates() {
+        let date_format = DateFormat::new("%d.%m.%Y").unwrap();
+        // A Wednesday
+        let today = day(2024, Month::May, 8);
+        let parse = |input| parse_user_date(input, today, &date_format).unwrap();
+
+        assert_eq!(parse("07.05.2024"), day(2024, Month::May, 7));
+        assert_eq!(parse("2024-05-01"), day(2024, Month::May, 1));
+        assert_eq!(parse("yesterday"), day(2024, Month::May, 7));
+        assert_eq!(parse("3 days ago"), day(2024, Month::May, 5));
+        assert_eq!(parse("wed"), today);
+        assert_eq!(parse("last wednesday"), day(2024, Month::May, 1));
+        assert_eq!(parse("Friday"), day(2024, Month::May, 3));
+        assert!(parse_user_date("someday", today, &date_format).is_err());
+    }
+
+    #[test]
+    fn adding_months_clamps_the_day() {
+        assert_eq!(
+            add_months(day(2024, Month::January, 31), 1),
+            day(2024, Month::February, 29)
+        );
+        assert_eq!(
+            add_months(day(2024, Month::January, 15), -1),
+            day(2023, Month::December, 15)
+        );
+    }
+}
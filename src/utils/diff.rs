@@ -0,0 +1,114 @@
+// Beyond this many word pairs the changed middle is marked as a whole rather
+// than aligned word by word
+const MAX_DIFF_CELLS: usize = 4_000_000;
+
+// Byte ranges of the words removed from the old text and added in the new one
+pub struct WordDiff {
+    pub removed: Vec<(usize, usize)>,
+    pub added: Vec<(usize, usize)>,
+}
+
+// Word-level diff between two texts.
+//
+// Words are compared ignoring case and punctuation, so markup added around a
+// word (`**Monday**`, `# Monday`) doesn't count as a change, and words made only
+// of markup are never reported.
+pub fn word_diff(old: &str, new: &str) -> WordDiff {
+    let old_words = words(old);
+    let new_words = words(new);
+
+    // Skip the common prefix and suffix before aligning what's left
+    let prefix = old_words
+        .iter()
+        .zip(&new_words)
+        .take_while(|(a, b)| a.0 == b.0)
+        .count();
+    let suffix = old_words[prefix..]
+        .iter()
+        .rev()
+        .zip(new_words[prefix..].iter().rev())
+        .take_while(|(a, b)| a.0 == b.0)
+        .count();
+    let old_middle = &old_words[prefix..old_words.len() - suffix];
+    let new_middle = &new_words[prefix..new_words.len() - suffix];
+
+    let (old_kept, new_kept) = common_words(old_middle, new_middle);
+    let removed = old_middle
+        .iter()
+        .zip(old_kept)
+        .filter(|(_, kept)| !kept)
+        .map(|(word, _)| word.1)
+        .collect();
+    let added = new_middle
+        .iter()
+        .zip(new_kept)
+        .filter(|(_, kept)| !kept)
+        .map(|(word, _)| word.1)
+        .collect();
+    WordDiff { removed, added }
+}
+
+// Normalized words with their byte ranges in the text
+fn words(text: &str) -> Vec<(String, (usize, usize))> {
+    let mut words = Vec::new();
+    let mut start = None;
+
+    for (index, c) in text.char_indices().chain([(text.len(), ' ')]) {
+        match (c.is_whitespace(), start) {
+            (false, None) => start = Some(index),
+            (true, Some(word_start)) => {
+                let word: String = text[word_start..index]
+                    .chars()
+                    .filter(|c| c.is_alphanumeric())
+                    .flat_map(char::to_lowercase)
+                    .collect();
+                if !word.is_empty() {
+                    words.push((word, (word_start, index)));
+                }
+                start = None;
+            }
+            _ => {}
+        }
+    }
+    words
+}
+
+// Marks the words of a longest common subsequence on each side
+fn common_words(
+    old: &[(String, (usize, usize))],
+    new: &[(String, (usize, usize))],
+) -> (Vec<bool>, Vec<bool>) {
+    let mut old_kept = vec![false; old.len()];
+    let mut new_kept = vec![false; new.len()];
+    if old.is_empty() || new.is_empty() || old.len() * new.len() > MAX_DIFF_CELLS {
+        return (old_kept, new_kept);
+    }
+
+    // lengths[i][j]: LCS length of old[i..] and new[j..]
+    let width = new.len() + 1;
+    let mut lengths = vec![0u32; (old.len() + 1) * width];
+    for i in (0..old.len()).rev() {
+        for j in (0..new.len()).rev() {
+            lengths[i * width + j] = if old[i].0 == new[j].0 {
+                lengths[(i + 1) * width + j + 1] + 1
+            } else {
+                lengths[(i + 1) * width + j].max(lengths[i * width + j + 1])
+            };
+        }
+    }
+
+    let (mut i, mut j) = (0, 0);
+    while i < old.len() && j < new.len() {
+        if old[i].0 == new[j].0 {
+            old_kept[i] = true;
+            new_kept[j] = true;
+            i += 1;
+            j += 1;
+        } else if lengths[(i + 1) * width + j] >= lengths[i * width + j + 1] {
+            i += 1;
+        } else {
+            j += 1;
+        }
+    }
+    (old_kept, new_kept)
+}
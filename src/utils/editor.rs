@@ -0,0 +1,85 @@
+use anyhow::{anyhow, Result};
+use std::env;
+use std::fs;
+use std::io::Write;
+use std::path::Path;
+use std::process::Command;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+// The editor to launch: the configured command, then $VISUAL, then $EDITOR.
+// Commands may carry arguments, e.g. "code --wait".
+pub fn editor_command(configured: Option<&str>) -> Option<String> {
+    configured
+        .map(str::to_string)
+        .into_iter()
+        .chain(env::var("VISUAL").ok())
+        .chain(env::var("EDITOR").ok())
+        .map(|command| command.trim().to_string())
+        .find(|command| !command.is_empty())
+}
+
+// Opens a file in the editor and waits for it to exit
+pub fn open_in_editor(configured: Option<&str>, path: &Path) -> Result<()> {
+    let command = editor_command(configured).ok_or_else(|| {
+        anyhow!("No editor configured. Set editor_command in config.toml, $VISUAL or $EDITOR")
+    })?;
+
+    let mut parts = command.split_whitespace();
+    let program = parts.next().unwrap_or_default();
+    let status = Command::new(program)
+        .args(parts)
+        .arg(path)
+        .status()
+        .map_err(|e| anyhow!("Failed to run '{}': {}", command, e))?;
+
+    if status.success() {
+        Ok(())
+    } else {
+        Err(anyhow!("'{}' exited with {}", command, status))
+    }
+}
+
+// Lets the user edit text in the editor through a temporary file. The
+// extension lets the editor pick the right syntax highlighting. The text may
+// be a decrypted day file, so the file lives in a directory only the user can
+// open and both are removed however the editor exits.
+pub fn edit_text(configured: Option<&str>, text: &str, extension: &str) -> Result<String> {
+    let stamp = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |duration| duration.as_nanos());
+    let directory = env::temp_dir().join(format!("rusty_diary-{}-{}", std::process::id(), stamp));
+    private_dir_builder().create(&directory)?;
+
+    let path = directory.join(format!("entry.{}", extension));
+    let result = write_private(&path, text)
+        .and_then(|_| open_in_editor(configured, &path))
+        .and_then(|_| Ok(fs::read_to_string(&path)?));
+    let _ = fs::remove_dir_all(&directory);
+
+    // Editors usually end files with a newline the entry doesn't need
+    Ok(result?.trim_end_matches(['\n', '\r']).to_string())
+}
+
+fn private_dir_builder() -> fs::DirBuilder {
+    #[allow(unused_mut)]
+    let mut builder = fs::DirBuilder::new();
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::DirBuilderExt;
+        builder.mode(0o700);
+    }
+    builder
+}
+
+// Creates a new file readable and writable by the user only
+fn write_private(path: &Path, text: &str) -> Result<()> {
+    let mut options = fs::OpenOptions::new();
+    options.write(true).create_new(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(0o600);
+    }
+    options.open(path)?.write_all(text.as_bytes())?;
+    Ok(())
+}
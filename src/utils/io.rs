@@ -1,4 +1,6 @@
-use std::io::{self, Write, stdout};
+use crossterm::event::{self, Event, KeyCode, KeyEventKind, KeyModifiers};
+use crossterm::terminal;
+use std::io::{self, stdout, Write};
 
 pub fn get_string(prompt: &str) -> String {
     print!("{prompt}");
@@ -11,3 +13,41 @@ pub fn get_string(prompt: &str) -> String {
 
     input.trim().to_string()
 }
+
+// Reads a line from the terminal without echoing it, for passphrases. The
+// prompt goes to stderr so stdout stays clean for scripts; Ctrl+C cancels.
+pub fn get_passphrase(prompt: &str) -> io::Result<String> {
+    eprint!("{prompt}");
+    io::stderr().flush()?;
+
+    terminal::enable_raw_mode()?;
+    let result = read_hidden_line();
+    terminal::disable_raw_mode()?;
+    eprintln!();
+    result
+}
+
+fn read_hidden_line() -> io::Result<String> {
+    let mut line = String::new();
+    loop {
+        let Event::Key(key) = event::read()? else {
+            continue;
+        };
+        if key.kind != KeyEventKind::Press {
+            continue;
+        }
+        match key.code {
+            KeyCode::Enter => return Ok(line),
+            KeyCode::Char('c') | KeyCode::Char('d')
+                if key.modifiers.contains(KeyModifiers::CONTROL) =>
+            {
+                return Err(io::Error::new(io::ErrorKind::Interrupted, "Cancelled"));
+            }
+            KeyCode::Char(c) => line.push(c),
+            KeyCode::Backspace => {
+                line.pop();
+            }
+            _ => {}
+        }
+    }
+}
@@ -1,3 +1,11 @@
+mod date;
+mod diff;
+mod editor;
 mod io;
+mod time_zone;
 
+pub use date::*;
+pub use diff::*;
+pub use editor::*;
 pub use io::*;
+pub use time_zone::*;
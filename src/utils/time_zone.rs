@@ -0,0 +1,210 @@
+use anyhow::{anyhow, Result};
+use time::format_description::well_known::Rfc3339;
+use time::{OffsetDateTime, UtcOffset};
+use time_tz::{timezones, OffsetDateTimeExt, TimeZone, Tz};
+
+// The time zone journal dates and timestamps are recorded in. Configured as
+// "local", an IANA name such as "Europe/Berlin", or a fixed offset like "+05:30".
+#[derive(Clone, Copy)]
+pub enum JournalTimeZone {
+    Named(&'static Tz),
+    Fixed(UtcOffset),
+}
+
+impl JournalTimeZone {
+    pub fn from_setting(setting: &str) -> Result<Self> {
+        let setting = setting.trim();
+
+        if setting.is_empty() || setting.eq_ignore_ascii_case("local") {
+            return Ok(Self::local());
+        }
+        if setting.eq_ignore_ascii_case("utc") || setting == "Z" {
+            return Ok(Self::Fixed(UtcOffset::UTC));
+        }
+        if let Some(tz) = timezones::get_by_name(setting) {
+            return Ok(Self::Named(tz));
+        }
+
+        parse_fixed_offset(setting).map(Self::Fixed).ok_or_else(|| {
+            anyhow!(
+                "Unknown time zone '{}'. Use \"local\", an IANA name like \"Europe/Berlin\" or an offset like \"+05:30\"",
+                setting
+            )
+        })
+    }
+
+    pub fn local() -> Self {
+        // Prefer a named zone so daylight saving changes are followed
+        if let Some(tz) = std::env::var("TZ")
+            .ok()
+            .and_then(|name| timezones::get_by_name(name.trim_start_matches(':')))
+        {
+            return Self::Named(tz);
+        }
+        if let Ok(tz) = time_tz::system::get_timezone() {
+            return Self::Named(tz);
+        }
+
+        Self::Fixed(UtcOffset::current_local_offset().unwrap_or(UtcOffset::UTC))
+    }
+
+    pub fn now(&self) -> OffsetDateTime {
+        self.convert(OffsetDateTime::now_utc())
+    }
+
+    pub fn convert(&self, date_time: OffsetDateTime) -> OffsetDateTime {
+        match self {
+            Self::Named(tz) => date_time.to_timezone(*tz),
+            Self::Fixed(offset) => date_time.to_offset(*offset),
+        }
+    }
+
+    pub fn name(&self) -> String {
+        match self {
+            Self::Named(tz) => tz.name().to_string(),
+            Self::Fixed(offset) => format_offset(*offset),
+        }
+    }
+}
+
+pub fn format_timestamp(date_time: OffsetDateTime) -> String {
+    date_time
+        .replace_nanosecond(0)
+        .unwrap_or(date_time)
+        .format(&Rfc3339)
+        .unwrap_or_else(|_| date_time.to_string())
+}
+
+pub fn format_offset(offset: UtcOffset) -> String {
+    let (hours, minutes, _) = offset.as_hms();
+    let sign = if offset.is_negative() { '-' } else { '+' };
+    format!("{}{:02}:{:02}", sign, hours.abs(), minutes.abs())
+}
+
+fn parse_fixed_offset(setting: &str) -> Option<UtcOffset> {
+    let setting = setting
+        .strip_prefix("UTC")
+        .or_else(|| setting.strip_prefix("GMT"))
+        .unwrap_or(setting);
+
+    let (sign, rest) = match setting.chars().next()? {
+        '+' => (1, &setting[1..]),
+        '-' => (-1, &setting[1..]),
+        _ => return None,
+    };
+
+    let (hours, minutes) = match rest.split_once(':') {
+        Some((hours, minutes)) => (hours, minutes),
+        None if rest.len() == 4 => rest.split_at(2),
+        None => (rest, "0"),
+    };
+
+    if [hours, minutes]
+        .iter()
+        .any(|part| part.is_empty() || !part.bytes().all(|byte| byte.is_ascii_digit()))
+    {
+        return None;
+    }
+    let hours: i8 = hours.parse().ok()?;
+    let minutes: i8 = minutes.parse().ok()?;
+    UtcOffset::from_hms(sign * hours, sign * minutes, 0).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn offset(setting: &str) -> Option<String> {
+        match JournalTimeZone::from_setting(setting).ok()? {
+            JournalTimeZone::Fixed(offset) => Some(format_offset(offset)),
+            JournalTimeZone::Named(tz) => panic!("{} is a named zone: {}", setting, tz.name()),
+        }
+    }
+
+    #[test]
+    fn fixed_offsets_are_parsed() {
+        assert_eq!(offset("+05:30").as_deref(), Some("+05:30"));
+        assert_eq!(offset("+0530").as_deref(), Some("+05:30"));
+        assert_eq!(offset("-03:45").as_deref(), Some("-03:45"));
+        assert_eq!(offset("+9").as_deref(), Some("+09:00"));
+        assert_eq!(offset("UTC-8").as_deref(), Some("-08:00"));
+        assert_eq!(offset("GMT+01:00").as_deref(), Some("+01:00"));
+        assert_eq!(offset(" utc ").as_deref(), Some("+00:00"));
+        assert_eq!(offset("Z").as_deref(), Some("+00:00"));
+    }
+
+    #[test]
+    fn invalid_offsets_are_rejected() {
+        for setting in [
+            "05:30",
+            "+",
+            "+05:",
+            "+05:60",
+            "+26",
+            "+530",
+            "+-05",
+            "+05:-30",
+            "+5h",
+            "UTC+",
+            "Mars/Olympus",
+        ] {
+            assert!(
+                parse_fixed_offset(setting).is_none(),
+                "{:?} was accepted",
+                setting
+            );
+            assert!(
+                JournalTimeZone::from_setting(setting).is_err(),
+                "{:?} was accepted",
+                setting
+            );
+        }
+
+        let error = JournalTimeZone::from_setting("Mars/Olympus").err().unwrap();
+        assert!(error
+            .to_string()
+            .contains("Unknown time zone 'Mars/Olympus'"));
+    }
+
+    #[test]
+    fn iana_names_follow_daylight_saving() {
+        let time_zone = JournalTimeZone::from_setting("Europe/Berlin").unwrap();
+        assert_eq!(time_zone.name(), "Europe/Berlin");
+
+        let winter = OffsetDateTime::parse("2024-01-15T12:00:00Z", &Rfc3339).unwrap();
+        let summer = OffsetDateTime::parse("2024-07-15T12:00:00Z", &Rfc3339).unwrap();
+        assert_eq!(
+            format_timestamp(time_zone.convert(winter)),
+            "2024-01-15T13:00:00+01:00"
+        );
+        assert_eq!(
+            format_timestamp(time_zone.convert(summer)),
+            "2024-07-15T14:00:00+02:00"
+        );
+    }
+
+    #[test]
+    fn local_is_the_system_time_zone() {
+        let local = JournalTimeZone::local().name();
+
+        assert_eq!(
+            JournalTimeZone::from_setting("local").unwrap().name(),
+            local
+        );
+        assert_eq!(
+            JournalTimeZone::from_setting("LOCAL").unwrap().name(),
+            local
+        );
+        assert_eq!(JournalTimeZone::from_setting("").unwrap().name(), local);
+    }
+
+    #[test]
+    fn fixed_offsets_move_the_day() {
+        let time_zone = JournalTimeZone::from_setting("UTC-8").unwrap();
+        let utc = OffsetDateTime::parse("2024-06-01T04:00:00Z", &Rfc3339).unwrap();
+
+        let converted = time_zone.convert(utc);
+        assert_eq!(converted.date().to_string(), "2024-05-31");
+        assert_eq!(time_zone.name(), "-08:00");
+    }
+}